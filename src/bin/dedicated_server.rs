@@ -1,9 +1,52 @@
-use minecraft_clone_rust::server::{run_server, ServerConfig};
+use minecraft_clone_rust::server::{run_server, ServerConfig, DEFAULT_CONFIG};
+use minecraft_clone_rust::world::game_mode::GameMode;
+use std::path::PathBuf;
 use tokio::runtime::Runtime;
 
+const USAGE: &str =
+    "usage: dedicated-server [--config <file>] [--world <dir>] [--allow-game-mode-changes] [--game-mode <mode>]";
+
+/// Read the config file, then apply command-line overrides on top of it.
+fn load_config() -> Result<ServerConfig, String> {
+    let mut args = std::env::args().skip(1);
+    let mut config_path = PathBuf::from(DEFAULT_CONFIG);
+    let mut allow_game_mode_changes = false;
+    let mut game_mode = None;
+    let mut world_dir = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--config" => config_path = args.next().ok_or(USAGE)?.into(),
+            "--world" => world_dir = Some(PathBuf::from(args.next().ok_or(USAGE)?)),
+            "--allow-game-mode-changes" => allow_game_mode_changes = true,
+            "--game-mode" => {
+                let name = args.next().ok_or(USAGE)?;
+                game_mode = Some(GameMode::from_name(&name).ok_or(format!("unknown game mode '{}'", name))?);
+            }
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+
+    let mut config = ServerConfig::load(&config_path)
+        .map_err(|e| format!("failed to read {}: {}", config_path.display(), e))?;
+    config.allow_game_mode_changes |= allow_game_mode_changes;
+    if let Some(mode) = game_mode {
+        config.default_game_mode = mode;
+    }
+    if world_dir.is_some() {
+        config.world_dir = world_dir;
+    }
+    Ok(config)
+}
+
 fn main() {
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
     let rt = Runtime::new().expect("Failed to create tokio runtime");
-    let config = ServerConfig { bind: "0.0.0.0".to_string(), port: 25565 };
     rt.block_on(async move {
         if let Err(e) = run_server(config).await {
             eprintln!("Server error: {}", e);
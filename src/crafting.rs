@@ -0,0 +1,415 @@
+#![allow(dead_code)]
+/// Crafting recipes driven by `data/recipes.json`.
+///
+/// A recipe turns the items laid out in a crafting grid into a result stack.
+/// Shaped recipes give a pattern of up to 3×3 characters, each standing for an
+/// ingredient in `key` (a space is an empty cell); the pattern may sit anywhere
+/// in the grid and may be mirrored left to right. Shapeless recipes only list
+/// their ingredients. An ingredient is an item id, or a list of ids any of
+/// which will do. Crafting uses up one item from every filled cell.
+///
+/// Mods add recipes by dropping files in the same format into `mods/recipes/`.
+/// Every item a recipe names must be in the item registry.
+use crate::inventory::{Inventory, ItemStack};
+use crate::item::ItemRegistry;
+use anyhow::{bail, Context};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+pub const RECIPES_PATH: &str = "data/recipes.json";
+pub const MOD_RECIPES_DIR: &str = "mods/recipes";
+const DEFAULT_RECIPES: &str = include_str!("../data/recipes.json");
+
+/// Widest grid there is, at a crafting table.
+pub const MAX_GRID_SIZE: usize = 3;
+/// The grid in the player's inventory.
+pub const INVENTORY_GRID_SIZE: usize = 2;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Ingredient {
+    Item(String),
+    AnyOf(Vec<String>),
+}
+
+impl Ingredient {
+    pub fn matches(&self, id: &str) -> bool {
+        self.items().iter().any(|item| item == id)
+    }
+
+    /// The items that satisfy this ingredient, preferred one first.
+    pub fn items(&self) -> &[String] {
+        match self {
+            Ingredient::Item(item) => std::slice::from_ref(item),
+            Ingredient::AnyOf(items) => items,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RecipeShape {
+    Shaped {
+        pattern: Vec<String>,
+        key: HashMap<char, Ingredient>,
+    },
+    Shapeless {
+        ingredients: Vec<Ingredient>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recipe {
+    #[serde(flatten)]
+    pub shape: RecipeShape,
+    pub result: ItemStack,
+}
+
+impl Recipe {
+    /// Width and height of a shaped recipe's pattern. Shapeless recipes need a
+    /// grid with room for all their ingredients.
+    fn dimensions(&self) -> (usize, usize) {
+        match &self.shape {
+            RecipeShape::Shaped { pattern, .. } => {
+                (pattern.first().map_or(0, |row| row.chars().count()), pattern.len())
+            }
+            RecipeShape::Shapeless { ingredients } => {
+                let side = (1..=MAX_GRID_SIZE)
+                    .find(|side| side * side >= ingredients.len())
+                    .unwrap_or(MAX_GRID_SIZE);
+                (side, side)
+            }
+        }
+    }
+
+    /// Whether the recipe can be made in a `size`×`size` grid.
+    pub fn fits(&self, size: usize) -> bool {
+        let (width, height) = self.dimensions();
+        width <= size && height <= size
+    }
+
+    fn validate(&self, items: &ItemRegistry) -> anyhow::Result<()> {
+        if !items.contains(&self.result.id) {
+            bail!("result {} is not a known item", self.result.id);
+        }
+        if self.result.count == 0 || self.result.count > self.result.max_stack_size() {
+            bail!("result count {} is out of range", self.result.count);
+        }
+        let ingredients: Vec<&Ingredient> = match &self.shape {
+            RecipeShape::Shaped { key, .. } => key.values().collect(),
+            RecipeShape::Shapeless { ingredients } => ingredients.iter().collect(),
+        };
+        for ingredient in ingredients {
+            if ingredient.items().is_empty() {
+                bail!("an ingredient lists no items");
+            }
+            if let Some(unknown) = ingredient.items().iter().find(|item| !items.contains(item)) {
+                bail!("ingredient {} is not a known item", unknown);
+            }
+        }
+        match &self.shape {
+            RecipeShape::Shaped { pattern, key } => {
+                let (width, height) = self.dimensions();
+                if width == 0 || height == 0 || width > MAX_GRID_SIZE || height > MAX_GRID_SIZE {
+                    bail!("pattern must be 1 to {MAX_GRID_SIZE} cells on each side");
+                }
+                for row in pattern {
+                    if row.chars().count() != width {
+                        bail!("pattern rows differ in length");
+                    }
+                    if let Some(symbol) = row.chars().find(|c| *c != ' ' && !key.contains_key(c)) {
+                        bail!("pattern uses {symbol:?}, which is not in the key");
+                    }
+                }
+            }
+            RecipeShape::Shapeless { ingredients } => {
+                if ingredients.is_empty() || ingredients.len() > MAX_GRID_SIZE * MAX_GRID_SIZE {
+                    bail!("shapeless recipes take 1 to {} ingredients", MAX_GRID_SIZE * MAX_GRID_SIZE);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the items in `grid` make this recipe.
+    pub fn matches(&self, grid: &CraftingGrid) -> bool {
+        match &self.shape {
+            RecipeShape::Shaped { pattern, key } => {
+                let Some((left, top, right, bottom)) = grid.bounds() else {
+                    return false;
+                };
+                let (width, height) = self.dimensions();
+                if right - left + 1 != width || bottom - top + 1 != height {
+                    return false;
+                }
+                let rows: Vec<Vec<char>> = pattern.iter().map(|row| row.chars().collect()).collect();
+                [false, true].into_iter().any(|mirrored| {
+                    (0..height).all(|y| {
+                        (0..width).all(|x| {
+                            let column = if mirrored { width - 1 - x } else { x };
+                            let cell = grid.get(left + x, top + y);
+                            match rows[y][column] {
+                                ' ' => cell.is_none(),
+                                symbol => cell.is_some_and(|stack| key[&symbol].matches(&stack.id)),
+                            }
+                        })
+                    })
+                })
+            }
+            RecipeShape::Shapeless { ingredients } => {
+                let items: Vec<&str> = grid.slots.iter().flatten().map(|stack| stack.id.as_str()).collect();
+                items.len() == ingredients.len() && assign_ingredients(&items, ingredients, &mut vec![false; items.len()])
+            }
+        }
+    }
+
+    /// Clear `grid` into `inventory` and lay out one of each ingredient from the
+    /// inventory in the recipe's shape, as the recipe book does. Leaves both
+    /// untouched and returns false if the inventory lacks an ingredient or the
+    /// grid is too small.
+    pub fn fill_grid(&self, grid: &mut CraftingGrid, inventory: &mut Inventory) -> bool {
+        if !self.fits(grid.size) {
+            return false;
+        }
+        let mut stock = inventory.clone();
+        for stack in grid.slots.iter().flatten().cloned() {
+            if stock.add_item(stack).is_err() {
+                return false;
+            }
+        }
+
+        let cells: Vec<(usize, usize, &Ingredient)> = match &self.shape {
+            RecipeShape::Shaped { pattern, key } => pattern
+                .iter()
+                .enumerate()
+                .flat_map(|(y, row)| {
+                    row.chars()
+                        .enumerate()
+                        .filter(|(_, symbol)| *symbol != ' ')
+                        .map(move |(x, symbol)| (x, y, &key[&symbol]))
+                })
+                .collect(),
+            RecipeShape::Shapeless { ingredients } => ingredients
+                .iter()
+                .enumerate()
+                .map(|(i, ingredient)| (i % grid.size, i / grid.size, ingredient))
+                .collect(),
+        };
+
+        let mut laid_out = CraftingGrid::new(grid.size);
+        for (x, y, ingredient) in cells {
+            let Some(id) = ingredient.items().iter().find(|id| stock.contains(id)) else {
+                return false;
+            };
+            stock.remove_item(id, 1);
+            laid_out.set(x, y, Some(ItemStack { id: id.clone(), count: 1 }));
+        }
+        *grid = laid_out;
+        *inventory = stock;
+        true
+    }
+}
+
+/// Give every grid item a distinct ingredient it satisfies, backtracking when
+/// several ingredients accept the same item.
+fn assign_ingredients(items: &[&str], ingredients: &[Ingredient], used: &mut [bool]) -> bool {
+    let Some((first, rest)) = ingredients.split_first() else {
+        return true;
+    };
+    for (i, item) in items.iter().enumerate() {
+        if !used[i] && first.matches(item) {
+            used[i] = true;
+            if assign_ingredients(items, rest, used) {
+                return true;
+            }
+            used[i] = false;
+        }
+    }
+    false
+}
+
+/// The cells a player crafts in: 2×2 in the inventory, 3×3 at a crafting table.
+#[derive(Debug, Clone)]
+pub struct CraftingGrid {
+    size: usize,
+    slots: Vec<Option<ItemStack>>,
+}
+
+impl Default for CraftingGrid {
+    fn default() -> Self {
+        Self::new(INVENTORY_GRID_SIZE)
+    }
+}
+
+impl CraftingGrid {
+    pub fn new(size: usize) -> Self {
+        let size = size.clamp(1, MAX_GRID_SIZE);
+        Self {
+            size,
+            slots: vec![None; size * size],
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&ItemStack> {
+        self.slots.get(y * self.size + x).and_then(Option::as_ref)
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, stack: Option<ItemStack>) {
+        self.slots[y * self.size + x] = stack;
+    }
+
+    /// Every cell, row by row.
+    pub fn slots_mut(&mut self) -> impl Iterator<Item = &mut Option<ItemStack>> {
+        self.slots.iter_mut()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.iter().all(Option::is_none)
+    }
+
+    /// Empty the grid, handing back everything that was in it.
+    pub fn take_all(&mut self) -> Vec<ItemStack> {
+        self.slots.iter_mut().filter_map(Option::take).collect()
+    }
+
+    /// Smallest rectangle holding every item, as (left, top, right, bottom).
+    fn bounds(&self) -> Option<(usize, usize, usize, usize)> {
+        let filled = (0..self.size)
+            .flat_map(|y| (0..self.size).map(move |x| (x, y)))
+            .filter(|(x, y)| self.get(*x, *y).is_some());
+        filled.fold(None, |bounds, (x, y)| {
+            Some(match bounds {
+                None => (x, y, x, y),
+                Some((left, top, right, bottom)) => (left.min(x), top.min(y), right.max(x), bottom.max(y)),
+            })
+        })
+    }
+
+    /// Use up one item from every filled cell.
+    fn consume(&mut self) {
+        for slot in self.slots.iter_mut() {
+            if let Some(stack) = slot {
+                stack.count -= 1;
+                if stack.count == 0 {
+                    *slot = None;
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RecipeFile {
+    recipes: Vec<Recipe>,
+}
+
+#[derive(Debug, Default)]
+pub struct RecipeRegistry {
+    recipes: Vec<Recipe>,
+}
+
+impl RecipeRegistry {
+    pub fn from_json(contents: &str) -> anyhow::Result<Self> {
+        let mut registry = Self::default();
+        registry.add_json(contents)?;
+        Ok(registry)
+    }
+
+    /// Add the recipes from a recipe file. Nothing is added if any of them is invalid.
+    pub fn add_json(&mut self, contents: &str) -> anyhow::Result<usize> {
+        let file: RecipeFile = serde_json::from_str(contents)?;
+        for (index, recipe) in file.recipes.iter().enumerate() {
+            recipe
+                .validate(ItemRegistry::global())
+                .with_context(|| format!("recipe {} for {}", index, recipe.result.id))?;
+        }
+        let count = file.recipes.len();
+        self.recipes.extend(file.recipes);
+        Ok(count)
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("reading {}", path.display()))?;
+        Self::from_json(&contents).with_context(|| format!("loading {}", path.display()))
+    }
+
+    /// `RECIPES_PATH` relative to the working directory if present, otherwise the
+    /// built-in recipes, followed by any recipes mods provide in `MOD_RECIPES_DIR`.
+    pub fn load_or_default() -> Self {
+        let path = Path::new(RECIPES_PATH);
+        let mut registry = None;
+        if path.exists() {
+            match Self::load(path) {
+                Ok(loaded) => registry = Some(loaded),
+                Err(err) => log::warn!("Failed to load {}: {:#}; using built-in recipes", RECIPES_PATH, err),
+            }
+        }
+        let mut registry = registry
+            .unwrap_or_else(|| Self::from_json(DEFAULT_RECIPES).expect("Built-in recipes are invalid"));
+        registry.load_dir(Path::new(MOD_RECIPES_DIR));
+        registry
+    }
+
+    /// Add every `.json` recipe file in `dir`, skipping (and logging) broken ones.
+    pub fn load_dir(&mut self, dir: &Path) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        let mut paths: Vec<_> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().and_then(|s| s.to_str()) == Some("json"))
+            .collect();
+        paths.sort();
+        for path in paths {
+            let added = std::fs::read_to_string(&path)
+                .context("reading file")
+                .and_then(|contents| self.add_json(&contents));
+            match added {
+                Ok(count) => log::info!("Loaded {} recipes from {}", count, path.display()),
+                Err(err) => log::warn!("Failed to load recipes from {}: {:#}", path.display(), err),
+            }
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Recipe> {
+        self.recipes.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.recipes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.recipes.is_empty()
+    }
+
+    /// The recipe the items in `grid` make, if any. The first one listed wins.
+    pub fn find(&self, grid: &CraftingGrid) -> Option<&Recipe> {
+        if grid.is_empty() {
+            return None;
+        }
+        self.recipes.iter().find(|recipe| recipe.matches(grid))
+    }
+
+    /// Make the recipe in `grid` once, using up its ingredients.
+    pub fn craft(&self, grid: &mut CraftingGrid) -> Option<ItemStack> {
+        let result = self.find(grid)?.result.clone();
+        grid.consume();
+        Some(result)
+    }
+
+    /// Recipe book lookup: recipes whose result id contains `query`.
+    pub fn search<'a>(&'a self, query: &'a str) -> impl Iterator<Item = &'a Recipe> + 'a {
+        let query = query.trim();
+        self.recipes
+            .iter()
+            .filter(move |recipe| recipe.result.id.contains(query))
+    }
+}
@@ -1,9 +1,11 @@
 use std::collections::HashSet;
+use winit::event::MouseButton;
 use winit::keyboard::KeyCode;
 
 #[derive(Default)]
 pub struct InputState {
     keys_pressed: HashSet<KeyCode>,
+    buttons_pressed: HashSet<MouseButton>,
     mouse_delta: (f64, f64),
     mouse_captured: bool,
 }
@@ -25,6 +27,19 @@ impl InputState {
         self.keys_pressed.contains(&key)
     }
 
+    pub fn button_pressed(&mut self, button: MouseButton) {
+        self.buttons_pressed.insert(button);
+    }
+
+    pub fn button_released(&mut self, button: MouseButton) {
+        self.buttons_pressed.remove(&button);
+    }
+
+    /// Held to mine the targeted block.
+    pub fn is_attacking(&self) -> bool {
+        self.buttons_pressed.contains(&MouseButton::Left)
+    }
+
     pub fn update_mouse_delta(&mut self, delta: (f64, f64)) {
         // Accumulate mouse delta per-frame (device events may arrive multiple times)
         self.mouse_delta.0 += delta.0;
@@ -67,7 +82,12 @@ impl InputState {
         self.is_key_pressed(KeyCode::Space)
     }
 
+    /// Descends while flying, sneaks on foot.
     pub fn is_down(&self) -> bool {
         self.is_key_pressed(KeyCode::ShiftLeft) || self.is_key_pressed(KeyCode::ShiftRight)
     }
+
+    pub fn is_sprint(&self) -> bool {
+        self.is_key_pressed(KeyCode::ControlLeft) || self.is_key_pressed(KeyCode::ControlRight)
+    }
 }
@@ -1,4 +1,11 @@
+use crate::item::ItemRegistry;
 use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// Most of any item a slot holds; items can set a lower limit in the item registry.
+pub const MAX_STACK_SIZE: u16 = 64;
+/// The first slots of the player's inventory make up the hotbar.
+pub const HOTBAR_SIZE: usize = 9;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ItemStack {
@@ -6,6 +13,21 @@ pub struct ItemStack {
     pub count: u16,
 }
 
+impl ItemStack {
+    /// Most of this item one slot holds, going by the item registry. 0 for items
+    /// the registry doesn't know.
+    pub fn max_stack_size(&self) -> u16 {
+        ItemRegistry::global().max_stack_size(&self.id)
+    }
+}
+
+/// Mouse button a slot in an inventory window is clicked or dragged with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClickButton {
+    Left,
+    Right,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Inventory {
     pub size: usize,
@@ -20,44 +42,491 @@ impl Inventory {
         }
     }
 
-    pub fn first_empty(&self) -> Option<usize> {
-        self.slots.iter().position(|s| s.is_none())
+    /// Add a stack, topping up matching stacks before using empty slots. Stacks
+    /// never grow past the item's max stack size; whatever doesn't fit is handed
+    /// back, as is the whole stack if the item isn't in the item registry.
+    pub fn add_item(&mut self, item: ItemStack) -> Result<(), ItemStack> {
+        if item.max_stack_size() == 0 {
+            log::warn!("Refusing unknown item {} in inventory", item.id);
+            return Err(item);
+        }
+        match self.add_to_range(item, 0..self.slots.len()) {
+            None => Ok(()),
+            Some(left) => Err(left),
+        }
     }
 
-    pub fn add_item(&mut self, item: ItemStack) -> Result<(), ItemStack> {
-        if let Some(i) = self.slots.iter().position(|s| match s {
-            Some(s) => s.id == item.id && s.count < u16::MAX,
-            None => false,
-        }) {
-            if let Some(slot) = &mut self.slots[i] {
-                slot.count = slot.count.saturating_add(item.count);
-                return Ok(());
+    /// Bring an inventory read from disk in line with the item registry: empty
+    /// stacks are cleared and stacks over their item's max stack size split up with
+    /// `add_item`. Returns what found no room. Stacks of unknown items, say from a
+    /// mod that isn't loaded, are left as they are so saving doesn't lose them.
+    pub fn sanitize(&mut self) -> Vec<ItemStack> {
+        let mut excess = Vec::new();
+        for slot in &mut self.slots {
+            let Some(stack) = slot else {
+                continue;
+            };
+            let max = stack.max_stack_size();
+            if max == 0 {
+                log::warn!("Keeping {} of unknown item {} in the inventory", stack.count, stack.id);
+            } else if stack.count == 0 {
+                *slot = None;
+            } else if stack.count > max {
+                excess.push(ItemStack { id: stack.id.clone(), count: stack.count - max });
+                stack.count = max;
             }
         }
+        excess
+            .into_iter()
+            .filter_map(|stack| self.add_item(stack).err())
+            .collect()
+    }
 
-        if let Some(i) = self.first_empty() {
-            self.slots[i] = Some(item);
-            Ok(())
-        } else {
-            Err(item)
+    /// `add_item` limited to the slots in `range`. Returns what doesn't fit.
+    fn add_to_range(&mut self, item: ItemStack, range: Range<usize>) -> Option<ItemStack> {
+        let range = range.start.min(self.slots.len())..range.end.min(self.slots.len());
+        let slots = &mut self.slots[range];
+        let mut left = Some(item);
+        for slot in slots.iter_mut().filter(|slot| slot.is_some()) {
+            left = merge_into_slot(slot, left?);
+        }
+        for slot in slots.iter_mut().filter(|slot| slot.is_none()) {
+            left = merge_into_slot(slot, left?);
+        }
+        left
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.slots.iter().flatten().any(|s| s.id == id && s.count > 0)
+    }
+
+    /// Remove up to `count` of an item, from whichever slots hold it. Returns how many were removed.
+    pub fn remove_item(&mut self, id: &str, count: u16) -> u16 {
+        let mut removed = 0;
+        for slot in self.slots.iter_mut() {
+            if removed == count {
+                break;
+            }
+            if let Some(stack) = slot {
+                if stack.id == id {
+                    let taken = stack.count.min(count - removed);
+                    stack.count -= taken;
+                    removed += taken;
+                    if stack.count == 0 {
+                        *slot = None;
+                    }
+                }
+            }
         }
+        removed
     }
 
     pub fn remove_at(&mut self, idx: usize, count: u16) -> Option<ItemStack> {
         if idx >= self.size {
             return None;
         }
-        if let Some(mut stack) = self.slots[idx].take() {
-            if count >= stack.count {
-                return Some(stack);
-            } else {
-                let remaining = stack.count - count;
-                let taken = ItemStack { id: stack.id.clone(), count };
-                stack.count = remaining;
-                self.slots[idx] = Some(stack);
-                return Some(taken);
-            }
+        take_from_slot(&mut self.slots[idx], count)
+    }
+
+    /// Click slot `idx` with `cursor` holding whatever the player has picked up.
+    /// See `click_slot`.
+    pub fn click(&mut self, idx: usize, cursor: &mut Option<ItemStack>, button: ClickButton) {
+        if let Some(slot) = self.slots.get_mut(idx) {
+            click_slot(slot, cursor, button);
         }
-        None
+    }
+
+    /// Shift-click slot `idx`: move its stack from the hotbar to the rest of the
+    /// inventory or the other way round. Returns whether anything moved.
+    pub fn quick_move(&mut self, idx: usize) -> bool {
+        let target = if idx < HOTBAR_SIZE {
+            HOTBAR_SIZE..self.slots.len()
+        } else {
+            0..HOTBAR_SIZE
+        };
+        let Some(stack) = self.slots.get_mut(idx).and_then(Option::take) else {
+            return false;
+        };
+        let count = stack.count;
+        let left = self.add_to_range(stack, target);
+        let moved = left.as_ref().is_none_or(|left| left.count < count);
+        self.slots[idx] = left;
+        moved
+    }
+
+    /// Shift-click slot `idx` while another container, such as a chest, is open:
+    /// move its stack into `other`. Returns whether anything moved.
+    pub fn quick_move_to(&mut self, idx: usize, other: &mut Inventory) -> bool {
+        let Some(stack) = self.slots.get_mut(idx).and_then(Option::take) else {
+            return false;
+        };
+        let count = stack.count;
+        let left = other.add_to_range(stack, 0..other.slots.len());
+        let moved = left.as_ref().is_none_or(|left| left.count < count);
+        self.slots[idx] = left;
+        moved
+    }
+
+    /// Swap slot `idx` with hotbar slot `hotbar`, as pressing that slot's number
+    /// key over it does.
+    pub fn swap_with_hotbar(&mut self, idx: usize, hotbar: usize) {
+        if idx < self.slots.len() && hotbar < HOTBAR_SIZE.min(self.slots.len()) {
+            self.slots.swap(idx, hotbar);
+        }
+    }
+}
+
+/// Put as much of `stack` into a single slot as fits there, returning the rest.
+/// Unknown items don't fit anywhere.
+pub fn merge_into_slot(slot: &mut Option<ItemStack>, stack: ItemStack) -> Option<ItemStack> {
+    let max = stack.max_stack_size();
+    match slot {
+        _ if max == 0 => Some(stack),
+        None => {
+            let count = stack.count.min(max);
+            *slot = Some(ItemStack { id: stack.id.clone(), count });
+            (stack.count > count).then(|| ItemStack { id: stack.id, count: stack.count - count })
+        }
+        Some(existing) if existing.id == stack.id => {
+            let moved = stack.count.min(max.saturating_sub(existing.count));
+            existing.count += moved;
+            (stack.count > moved).then(|| ItemStack { id: stack.id, count: stack.count - moved })
+        }
+        Some(_) => Some(stack),
+    }
+}
+
+/// Take up to `count` items out of a slot.
+pub fn take_from_slot(slot: &mut Option<ItemStack>, count: u16) -> Option<ItemStack> {
+    let stack = slot.as_mut()?;
+    if count >= stack.count {
+        return slot.take();
+    }
+    stack.count -= count;
+    Some(ItemStack { id: stack.id.clone(), count })
+}
+
+/// Click a slot with `cursor` holding whatever the player has picked up.
+///
+/// With an empty cursor, the left button picks up the whole stack and the right
+/// button half of it, rounded up. Holding a stack, the left button puts down as
+/// much of it as fits and the right button a single item. A stack of another
+/// item is swapped with the cursor's.
+pub fn click_slot(slot: &mut Option<ItemStack>, cursor: &mut Option<ItemStack>, button: ClickButton) {
+    let Some(held) = cursor.take() else {
+        let count = match (slot.as_ref(), button) {
+            (None, _) => return,
+            (Some(stack), ClickButton::Left) => stack.count,
+            (Some(stack), ClickButton::Right) => stack.count.div_ceil(2),
+        };
+        *cursor = take_from_slot(slot, count);
+        return;
+    };
+    if slot.as_ref().is_some_and(|stack| stack.id != held.id) {
+        *cursor = slot.replace(held);
+        return;
+    }
+    let count = match button {
+        ClickButton::Left => held.count,
+        ClickButton::Right => 1,
+    };
+    let put = ItemStack { id: held.id.clone(), count };
+    let kept = held.count - count + merge_into_slot(slot, put).map_or(0, |left| left.count);
+    *cursor = (kept > 0).then_some(ItemStack { id: held.id, count: kept });
+}
+
+/// Click a slot items can only be taken out of, such as a furnace's output. Its
+/// stack goes onto an empty cursor, or onto a matching stack as far as that has room.
+pub fn click_output_slot(slot: &mut Option<ItemStack>, cursor: &mut Option<ItemStack>) {
+    let matches = match (slot.as_ref(), cursor.as_ref()) {
+        (Some(_), None) => true,
+        (Some(stack), Some(held)) => stack.id == held.id,
+        (None, _) => false,
+    };
+    if matches {
+        if let Some(stack) = slot.take() {
+            *slot = merge_into_slot(cursor, stack);
+        }
+    }
+}
+
+/// Spread the cursor's stack over the slots dragged across: evenly with the left
+/// button, one each with the right. Slots holding another item are skipped, and
+/// whatever is left over stays on the cursor.
+pub fn distribute(slots: &mut [&mut Option<ItemStack>], cursor: &mut Option<ItemStack>, button: ClickButton) {
+    let Some(held) = cursor.take() else {
+        return;
+    };
+    let targets: Vec<usize> = (0..slots.len())
+        .filter(|&i| slots[i].as_ref().is_none_or(|stack| stack.id == held.id))
+        .collect();
+    let share = match button {
+        ClickButton::Left => (held.count / targets.len().max(1) as u16).max(1),
+        ClickButton::Right => 1,
+    };
+    let mut remaining = held.count;
+    for i in targets {
+        if remaining == 0 {
+            break;
+        }
+        let count = share.min(remaining);
+        let put = ItemStack { id: held.id.clone(), count };
+        remaining -= count - merge_into_slot(&mut *slots[i], put).map_or(0, |left| left.count);
+    }
+    *cursor = (remaining > 0).then_some(ItemStack { id: held.id, count: remaining });
+}
+
+/// What falls out when the cursor is clicked outside the window: the whole stack
+/// with the left button, one item with the right.
+pub fn drop_from_cursor(cursor: &mut Option<ItemStack>, button: ClickButton) -> Option<ItemStack> {
+    let count = match button {
+        ClickButton::Left => u16::MAX,
+        ClickButton::Right => 1,
+    };
+    take_from_slot(cursor, count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STONE: &str = "minecraft:stone";
+    const DIRT: &str = "minecraft:dirt";
+    const SNOWBALL: &str = "minecraft:snowball";
+
+    fn stack(id: &str, count: u16) -> Option<ItemStack> {
+        Some(ItemStack { id: id.to_string(), count })
+    }
+
+    fn counts(slots: &[Option<ItemStack>]) -> Vec<u16> {
+        slots.iter().map(|slot| slot.as_ref().map_or(0, |stack| stack.count)).collect()
+    }
+
+    fn cursor_count(cursor: &Option<ItemStack>) -> u16 {
+        cursor.as_ref().map_or(0, |stack| stack.count)
+    }
+
+    #[test]
+    fn add_item_respects_per_item_stack_size() {
+        let mut inventory = Inventory::new(3);
+        let left = inventory.add_item(ItemStack { id: SNOWBALL.to_string(), count: 56 }).unwrap_err();
+        assert_eq!(counts(&inventory.slots), vec![16, 16, 16]);
+        assert_eq!(left.count, 8);
+    }
+
+    #[test]
+    fn add_item_rejects_unknown_items() {
+        let mut inventory = Inventory::new(3);
+        assert!(inventory.add_item(ItemStack { id: "test:nothing".to_string(), count: 1 }).is_err());
+        assert!(inventory.slots.iter().all(Option::is_none));
+    }
+
+    #[test]
+    fn sanitize_keeps_unknown_items_and_splits_oversized_stacks() {
+        let mut inventory = Inventory::new(4);
+        inventory.slots[0] = stack("test:nothing", 5);
+        inventory.slots[1] = stack(SNOWBALL, 40);
+        inventory.slots[2] = stack(STONE, 0);
+        assert!(inventory.sanitize().is_empty());
+        assert_eq!(inventory.slots[0].as_ref().unwrap().id, "test:nothing");
+        assert_eq!(counts(&inventory.slots), vec![5, 16, 16, 8]);
+    }
+
+    #[test]
+    fn sanitize_returns_what_finds_no_room() {
+        let mut inventory = Inventory::new(2);
+        inventory.slots[0] = stack(SNOWBALL, 40);
+        let left = inventory.sanitize();
+        assert_eq!(counts(&inventory.slots), vec![16, 16]);
+        assert_eq!(left.iter().map(|stack| stack.count).sum::<u16>(), 8);
+    }
+
+    #[test]
+    fn left_click_picks_up_and_puts_down_whole_stack() {
+        let mut slot = stack(STONE, 10);
+        let mut cursor = None;
+        click_slot(&mut slot, &mut cursor, ClickButton::Left);
+        assert!(slot.is_none());
+        assert_eq!(cursor_count(&cursor), 10);
+
+        click_slot(&mut slot, &mut cursor, ClickButton::Left);
+        assert_eq!(slot.as_ref().unwrap().count, 10);
+        assert!(cursor.is_none());
+    }
+
+    #[test]
+    fn left_click_merges_up_to_max_stack() {
+        let mut slot = stack(STONE, 60);
+        let mut cursor = stack(STONE, 10);
+        click_slot(&mut slot, &mut cursor, ClickButton::Left);
+        assert_eq!(slot.as_ref().unwrap().count, 64);
+        assert_eq!(cursor_count(&cursor), 6);
+    }
+
+    #[test]
+    fn clicking_another_item_swaps() {
+        let mut slot = stack(DIRT, 3);
+        let mut cursor = stack(STONE, 5);
+        click_slot(&mut slot, &mut cursor, ClickButton::Right);
+        assert_eq!(slot.as_ref().unwrap().id, STONE);
+        assert_eq!(cursor.as_ref().unwrap().id, DIRT);
+        assert_eq!(cursor_count(&cursor), 3);
+    }
+
+    #[test]
+    fn right_click_splits_half_rounded_up() {
+        let mut slot = stack(STONE, 7);
+        let mut cursor = None;
+        click_slot(&mut slot, &mut cursor, ClickButton::Right);
+        assert_eq!(cursor_count(&cursor), 4);
+        assert_eq!(slot.as_ref().unwrap().count, 3);
+
+        let mut single = stack(STONE, 1);
+        let mut cursor = None;
+        click_slot(&mut single, &mut cursor, ClickButton::Right);
+        assert!(single.is_none());
+        assert_eq!(cursor_count(&cursor), 1);
+    }
+
+    #[test]
+    fn right_click_places_one() {
+        let mut slot = None;
+        let mut cursor = stack(STONE, 2);
+        click_slot(&mut slot, &mut cursor, ClickButton::Right);
+        assert_eq!(slot.as_ref().unwrap().count, 1);
+        click_slot(&mut slot, &mut cursor, ClickButton::Right);
+        assert_eq!(slot.as_ref().unwrap().count, 2);
+        assert!(cursor.is_none());
+    }
+
+    #[test]
+    fn right_click_on_full_stack_keeps_cursor() {
+        let mut slot = stack(SNOWBALL, 16);
+        let mut cursor = stack(SNOWBALL, 4);
+        click_slot(&mut slot, &mut cursor, ClickButton::Right);
+        assert_eq!(slot.as_ref().unwrap().count, 16);
+        assert_eq!(cursor_count(&cursor), 4);
+    }
+
+    #[test]
+    fn output_slot_only_gives() {
+        let mut output = stack(STONE, 8);
+        let mut cursor = stack(DIRT, 1);
+        click_output_slot(&mut output, &mut cursor);
+        assert_eq!(output.as_ref().unwrap().id, STONE);
+        assert_eq!(cursor.as_ref().unwrap().id, DIRT);
+
+        let mut cursor = stack(STONE, 60);
+        click_output_slot(&mut output, &mut cursor);
+        assert_eq!(cursor_count(&cursor), 64);
+        assert_eq!(output.as_ref().unwrap().count, 4);
+
+        let mut empty = None;
+        let mut cursor = None;
+        click_output_slot(&mut empty, &mut cursor);
+        assert!(cursor.is_none());
+    }
+
+    #[test]
+    fn left_drag_splits_evenly() {
+        let mut slots = vec![None, stack(STONE, 1), stack(DIRT, 1), None];
+        let mut cursor = stack(STONE, 10);
+        {
+            let mut targets: Vec<&mut Option<ItemStack>> = slots.iter_mut().collect();
+            distribute(&mut targets, &mut cursor, ClickButton::Left);
+        }
+        // Three slots take stone; 10 / 3 leaves one on the cursor.
+        assert_eq!(counts(&slots), vec![3, 4, 1, 3]);
+        assert_eq!(cursor_count(&cursor), 1);
+    }
+
+    #[test]
+    fn left_drag_with_too_few_items_fills_in_order() {
+        let mut slots = vec![None, None, None];
+        let mut cursor = stack(STONE, 2);
+        {
+            let mut targets: Vec<&mut Option<ItemStack>> = slots.iter_mut().collect();
+            distribute(&mut targets, &mut cursor, ClickButton::Left);
+        }
+        assert_eq!(counts(&slots), vec![1, 1, 0]);
+        assert!(cursor.is_none());
+    }
+
+    #[test]
+    fn right_drag_places_one_each() {
+        let mut slots = vec![None, stack(STONE, 64), None];
+        let mut cursor = stack(STONE, 5);
+        {
+            let mut targets: Vec<&mut Option<ItemStack>> = slots.iter_mut().collect();
+            distribute(&mut targets, &mut cursor, ClickButton::Right);
+        }
+        assert_eq!(counts(&slots), vec![1, 64, 1]);
+        assert_eq!(cursor_count(&cursor), 3);
+    }
+
+    #[test]
+    fn quick_move_between_hotbar_and_main() {
+        let mut inventory = Inventory::new(36);
+        inventory.slots[0] = stack(STONE, 10);
+        inventory.slots[20] = stack(STONE, 60);
+        assert!(inventory.quick_move(0));
+        assert!(inventory.slots[0].is_none());
+        // Tops up the matching stack before using the first empty main slot.
+        assert_eq!(inventory.slots[20].as_ref().unwrap().count, 64);
+        assert_eq!(inventory.slots[9].as_ref().unwrap().count, 6);
+
+        assert!(inventory.quick_move(9));
+        assert_eq!(inventory.slots[0].as_ref().unwrap().count, 6);
+        assert!(inventory.slots[9].is_none());
+    }
+
+    #[test]
+    fn quick_move_into_full_section_does_nothing() {
+        let mut inventory = Inventory::new(10);
+        for slot in &mut inventory.slots[..HOTBAR_SIZE] {
+            *slot = stack(DIRT, 64);
+        }
+        inventory.slots[9] = stack(STONE, 5);
+        assert!(!inventory.quick_move(9));
+        assert_eq!(inventory.slots[9].as_ref().unwrap().count, 5);
+        assert!(!inventory.quick_move(0));
+        assert_eq!(inventory.slots[0].as_ref().unwrap().count, 64);
+    }
+
+    #[test]
+    fn quick_move_to_other_container() {
+        let mut inventory = Inventory::new(36);
+        let mut chest = Inventory::new(2);
+        chest.slots[0] = stack(DIRT, 64);
+        inventory.slots[3] = stack(STONE, 64);
+        assert!(inventory.quick_move_to(3, &mut chest));
+        assert!(inventory.slots[3].is_none());
+        assert_eq!(chest.slots[1].as_ref().unwrap().count, 64);
+
+        inventory.slots[4] = stack(STONE, 1);
+        assert!(!inventory.quick_move_to(4, &mut chest));
+        assert_eq!(inventory.slots[4].as_ref().unwrap().count, 1);
+    }
+
+    #[test]
+    fn swap_with_hotbar_swaps_slots() {
+        let mut inventory = Inventory::new(36);
+        inventory.slots[2] = stack(STONE, 1);
+        inventory.slots[30] = stack(DIRT, 2);
+        inventory.swap_with_hotbar(30, 2);
+        assert_eq!(inventory.slots[2].as_ref().unwrap().id, DIRT);
+        assert_eq!(inventory.slots[30].as_ref().unwrap().id, STONE);
+        inventory.swap_with_hotbar(30, HOTBAR_SIZE);
+        assert_eq!(inventory.slots[30].as_ref().unwrap().id, STONE);
+    }
+
+    #[test]
+    fn drop_from_cursor_whole_or_one() {
+        let mut cursor = stack(STONE, 3);
+        assert_eq!(drop_from_cursor(&mut cursor, ClickButton::Right).unwrap().count, 1);
+        assert_eq!(cursor_count(&cursor), 2);
+        assert_eq!(drop_from_cursor(&mut cursor, ClickButton::Left).unwrap().count, 2);
+        assert!(cursor.is_none());
+        assert!(drop_from_cursor(&mut cursor, ClickButton::Left).is_none());
     }
 }
@@ -0,0 +1,272 @@
+#![allow(dead_code)]
+/// Item definitions driven by `data/items.json`.
+///
+/// Every block that can be held gets an item of the same name that places it,
+/// so the file only lists items that aren't blocks, and blocks whose item
+/// differs from the default (a category other than building blocks, say).
+/// Mods add or override items with files in the same format in `mods/items/`.
+/// Inventories, crafting and furnaces look up stack limits here and refuse
+/// item ids the registry doesn't know.
+use crate::inventory::MAX_STACK_SIZE;
+use crate::world::block::BlockType;
+use crate::world::fluid::Fluid;
+use crate::world::mining::Tool;
+use crate::world::registry::BlockRegistry;
+use anyhow::{bail, Context};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+pub const ITEMS_PATH: &str = "data/items.json";
+pub const MOD_ITEMS_DIR: &str = "mods/items";
+const DEFAULT_ITEMS: &str = include_str!("../data/items.json");
+
+static REGISTRY: OnceLock<ItemRegistry> = OnceLock::new();
+
+/// Creative inventory tab an item is listed under.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemCategory {
+    #[default]
+    BuildingBlocks,
+    Natural,
+    Functional,
+    Tools,
+    Combat,
+    Ingredients,
+    Misc,
+}
+
+impl ItemCategory {
+    pub const ALL: [ItemCategory; 7] = [
+        ItemCategory::BuildingBlocks,
+        ItemCategory::Natural,
+        ItemCategory::Functional,
+        ItemCategory::Tools,
+        ItemCategory::Combat,
+        ItemCategory::Ingredients,
+        ItemCategory::Misc,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ItemCategory::BuildingBlocks => "Building Blocks",
+            ItemCategory::Natural => "Natural",
+            ItemCategory::Functional => "Functional",
+            ItemCategory::Tools => "Tools",
+            ItemCategory::Combat => "Combat",
+            ItemCategory::Ingredients => "Ingredients",
+            ItemCategory::Misc => "Miscellaneous",
+        }
+    }
+}
+
+fn default_max_stack() -> u16 {
+    MAX_STACK_SIZE
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ItemDefinition {
+    /// Namespaced name such as `minecraft:stick`; what `ItemStack.id` holds.
+    pub name: String,
+    /// Shown to players; made from the name when left out.
+    #[serde(default)]
+    pub display_name: String,
+    #[serde(default = "default_max_stack")]
+    pub max_stack: u16,
+    #[serde(default)]
+    pub category: ItemCategory,
+    #[serde(default)]
+    pub tool: Option<Tool>,
+    /// Uses before the item breaks; `None` for items that don't wear out.
+    #[serde(default)]
+    pub durability: Option<u16>,
+    /// Name of the block the item places, if it places one. Defaults to the
+    /// block of the same name.
+    #[serde(default)]
+    pub places_block: Option<String>,
+}
+
+impl ItemDefinition {
+    fn for_block(block: BlockType) -> Self {
+        let name = block.name().to_string();
+        Self {
+            display_name: display_name(&name),
+            places_block: Some(name.clone()),
+            name,
+            max_stack: MAX_STACK_SIZE,
+            category: ItemCategory::default(),
+            tool: None,
+            durability: None,
+        }
+    }
+
+    /// The block this item places, if any.
+    pub fn block(&self) -> Option<BlockType> {
+        self.places_block.as_deref().and_then(BlockType::from_name)
+    }
+}
+
+/// "minecraft:oak_planks" becomes "Oak Planks".
+fn display_name(name: &str) -> String {
+    let path = name.split_once(':').map_or(name, |(_, path)| path);
+    path.split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[derive(Debug, Deserialize)]
+struct ItemFile {
+    items: Vec<ItemDefinition>,
+}
+
+#[derive(Debug)]
+pub struct ItemRegistry {
+    items: Vec<ItemDefinition>,
+    by_name: HashMap<String, usize>,
+}
+
+impl ItemRegistry {
+    /// An item for every holdable block in `blocks`, then the items in `contents`.
+    pub fn from_json(contents: &str, blocks: &BlockRegistry) -> anyhow::Result<Self> {
+        let mut registry = Self {
+            items: Vec::new(),
+            by_name: HashMap::new(),
+        };
+        for (block, definition) in blocks.iter() {
+            if definition.solid && Fluid::of(block).is_none() {
+                registry.insert(ItemDefinition::for_block(block));
+            }
+        }
+        registry.add_json(contents)?;
+        Ok(registry)
+    }
+
+    /// Add the items from an item file, replacing any of the same name. Nothing is
+    /// added if any of them is invalid.
+    pub fn add_json(&mut self, contents: &str) -> anyhow::Result<usize> {
+        let file: ItemFile = serde_json::from_str(contents)?;
+        for item in &file.items {
+            if !item.name.contains(':') {
+                bail!("item name {:?} has no namespace", item.name);
+            }
+            if item.max_stack == 0 || item.max_stack > MAX_STACK_SIZE {
+                bail!("{} stacks to {}, which is not 1 to {}", item.name, item.max_stack, MAX_STACK_SIZE);
+            }
+            if let Some(block) = &item.places_block {
+                if BlockType::from_name(block).is_none() {
+                    bail!("{} places unknown block {}", item.name, block);
+                }
+            }
+        }
+        let count = file.items.len();
+        for mut item in file.items {
+            if item.display_name.is_empty() {
+                item.display_name = display_name(&item.name);
+            }
+            if item.places_block.is_none() && BlockType::from_name(&item.name).is_some() {
+                item.places_block = Some(item.name.clone());
+            }
+            self.insert(item);
+        }
+        Ok(count)
+    }
+
+    fn insert(&mut self, item: ItemDefinition) {
+        match self.by_name.get(&item.name) {
+            Some(&index) => self.items[index] = item,
+            None => {
+                self.by_name.insert(item.name.clone(), self.items.len());
+                self.items.push(item);
+            }
+        }
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("reading {}", path.display()))?;
+        Self::from_json(&contents, BlockRegistry::global()).with_context(|| format!("loading {}", path.display()))
+    }
+
+    /// `ITEMS_PATH` relative to the working directory if present, otherwise the
+    /// built-in items, followed by any items mods provide in `MOD_ITEMS_DIR`.
+    pub fn load_or_default() -> Self {
+        let path = Path::new(ITEMS_PATH);
+        let mut registry = None;
+        if path.exists() {
+            match Self::load(path) {
+                Ok(loaded) => registry = Some(loaded),
+                Err(err) => log::warn!("Failed to load {}: {:#}; using built-in items", ITEMS_PATH, err),
+            }
+        }
+        let mut registry = registry.unwrap_or_else(|| {
+            Self::from_json(DEFAULT_ITEMS, BlockRegistry::global()).expect("Built-in items are invalid")
+        });
+        registry.load_dir(Path::new(MOD_ITEMS_DIR));
+        registry
+    }
+
+    /// Add every `.json` item file in `dir`, skipping (and logging) broken ones.
+    pub fn load_dir(&mut self, dir: &Path) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        let mut paths: Vec<_> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().and_then(|s| s.to_str()) == Some("json"))
+            .collect();
+        paths.sort();
+        for path in paths {
+            let added = std::fs::read_to_string(&path)
+                .context("reading file")
+                .and_then(|contents| self.add_json(&contents));
+            match added {
+                Ok(count) => log::info!("Loaded {} items from {}", count, path.display()),
+                Err(err) => log::warn!("Failed to load items from {}: {:#}", path.display(), err),
+            }
+        }
+    }
+
+    /// The registry shared by the whole game, loaded on first use.
+    pub fn global() -> &'static Self {
+        REGISTRY.get_or_init(Self::load_or_default)
+    }
+
+    /// Install the shared registry before anything has used it. Returns the registry
+    /// back if one is already in place.
+    pub fn install(registry: Self) -> Result<(), Self> {
+        REGISTRY.set(registry)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ItemDefinition> {
+        self.by_name.get(name).map(|&index| &self.items[index])
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.by_name.contains_key(name)
+    }
+
+    /// The block placing `name` puts down, if it places one.
+    pub fn block_for(&self, name: &str) -> Option<BlockType> {
+        self.get(name)?.block()
+    }
+
+    /// Most of `name` one slot holds; 0 for unknown items, so they never stack.
+    pub fn max_stack_size(&self, name: &str) -> u16 {
+        self.get(name).map_or(0, |item| item.max_stack)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ItemDefinition> {
+        self.items.iter()
+    }
+}
@@ -1,6 +1,8 @@
+pub mod crafting;
 pub mod net;
 pub mod server;
 pub mod inventory;
+pub mod item;
 pub mod mods;
 pub mod renderer;
 pub mod world;
@@ -1,16 +1,24 @@
+mod crafting;
 mod input;
 mod renderer;
 mod inventory;
+mod item;
 mod mods;
+mod net;
 mod settings;
 mod ui;
 mod world;
 
 use glam::{IVec3, Vec3};
+use rhai::Dynamic;
+use crafting::{CraftingGrid, RecipeRegistry, INVENTORY_GRID_SIZE, MAX_GRID_SIZE};
 use input::InputState;
+use inventory::{merge_into_slot, take_from_slot, ItemStack, HOTBAR_SIZE};
+use item::ItemRegistry;
+use net::protocol::{ClientMessage, ServerMessage};
 use renderer::{
     camera::Vertex,
-    texture::{self, TextureResolver},
+    texture::{self, TextureKey, TextureResolver, DESTROY_STAGES},
     Camera,
     Renderer,
 };
@@ -19,7 +27,7 @@ use std::collections::HashSet;
 use std::sync::{mpsc::{self, TryRecvError}, Arc};
 use std::thread;
 use std::time::{Duration, Instant};
-use ui::Gui;
+use ui::{Gui, InventoryView};
 use winit::{
     application::ApplicationHandler,
     event::*,
@@ -27,6 +35,14 @@ use winit::{
     keyboard::{KeyCode, PhysicalKey},
     window::{CursorGrabMode, Window, WindowId},
 };
+use world::generator::GENERATOR_PRESETS;
+use world::fluid::Fluid;
+use world::game_mode::GameMode;
+use world::item_entity::ItemEntities;
+use world::mining::{self, Tool};
+use world::region::write_atomic;
+use world::shape::BlockBox;
+use world::state::{BlockState, Facing, Property};
 use world::{BlockType, World, CHUNK_SIZE, WORLD_HEIGHT};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -40,11 +56,64 @@ enum AppScreen {
 
 struct BlockHit {
     hit: IVec3,
+    /// Where the ray entered the hit block.
+    point: Vec3,
     place: Option<IVec3>,
 }
 
+/// Hold-to-mine progress on one block.
+struct Mining {
+    pos: IVec3,
+    block: BlockType,
+    /// 0.0 when started, breaks at 1.0.
+    progress: f32,
+}
+
+/// The client's end of a dedicated server connection. The server has the final
+/// say over game mode changes and block breaking.
+struct ServerLink {
+    handle: net::ClientHandle,
+    name: String,
+    /// Our player id, learned from the first world state that lists our name.
+    id: Option<u64>,
+    /// Blocks we asked to break that the server hasn't answered for yet.
+    pending_breaks: HashSet<IVec3>,
+}
+
+impl ServerLink {
+    /// Connect to the server in `SERVER_ADDR`, if set, as `PLAYER_NAME`.
+    fn from_env() -> Option<Self> {
+        let addr = std::env::var("SERVER_ADDR").ok()?;
+        let name = std::env::var("PLAYER_NAME").unwrap_or_else(|_| "Player".to_string());
+        let handle = match net::start_client(addr.clone()) {
+            Ok(handle) => handle,
+            Err(e) => {
+                eprintln!("Failed to connect to {}: {}", addr, e);
+                return None;
+            }
+        };
+        let link = Self {
+            handle,
+            name,
+            id: None,
+            pending_breaks: HashSet::new(),
+        };
+        link.send(ClientMessage::Connect { name: link.name.clone() });
+        Some(link)
+    }
+
+    fn send(&self, message: ClientMessage) {
+        let _ = self.handle.send.send(message);
+    }
+
+    fn is_me(&self, id: u64) -> bool {
+        self.id == Some(id)
+    }
+}
+
 struct ChunkMeshRequest {
     chunk: world::Chunk,
+    border_light: world::light::BorderLight,
     resolver: Arc<TextureResolver>,
 }
 
@@ -60,23 +129,47 @@ struct PendingAtlasUpload {
     pixels: Vec<u8>,
 }
 
-const HOTBAR_BLOCKS: &[(BlockType, &str)] = &[
-    (BlockType::GrassBlock, "Grass"),
-    (BlockType::Dirt, "Dirt"),
-    (BlockType::Stone, "Stone"),
-    (BlockType::Cobblestone, "Cobble"),
-    (BlockType::OakPlanks, "Planks"),
-    (BlockType::Glass, "Glass"),
-    (BlockType::Sand, "Sand"),
-    (BlockType::Water, "Water"),
-];
-
 const PLAYER_RADIUS: f32 = 0.4;
 const PLAYER_EYE_HEIGHT: f32 = 1.62;
 const PLAYER_HEADROOM: f32 = 0.2;
-const COLLISION_EPSILON: f32 = 0.001;
+/// Tallest ledge the player walks up without jumping, enough for a slab or stair.
+const PLAYER_STEP_HEIGHT: f32 = 0.6;
+// Movement speeds are in blocks per second, accelerations in blocks per second squared.
+const WALK_SPEED: f32 = 4.3;
+const SPRINT_SPEED: f32 = 5.6;
+const SNEAK_SPEED: f32 = 1.3;
+const FLY_SPEED: f32 = 20.0;
+const GRAVITY: f32 = 32.0;
+const TERMINAL_VELOCITY: f32 = 78.0;
+/// Launch speed of a jump, reaching a little over one block.
+const JUMP_VELOCITY: f32 = 9.0;
+const WATER_SPEED_FACTOR: f32 = 0.5;
+const LAVA_SPEED_FACTOR: f32 = 0.3;
+const SOUL_SAND_SPEED_FACTOR: f32 = 0.4;
+const SWIM_ACCELERATION: f32 = 12.0;
+const FLUID_SINK_ACCELERATION: f32 = 4.0;
+/// Fraction of vertical velocity kept per game tick in each fluid.
+const WATER_DRAG: f32 = 0.8;
+const LAVA_DRAG: f32 = 0.5;
+const FLUID_EXIT_VELOCITY: f32 = 6.0;
+const MAX_PHYSICS_STEP: f32 = 0.1;
+const MAX_HEALTH: f32 = 20.0;
+/// Falls shorter than this do no damage; each block beyond costs one point of health.
+const SAFE_FALL_DISTANCE: f32 = 3.0;
+const LAVA_DAMAGE_PER_SECOND: f32 = 8.0;
+/// What a bucket of fluid leaves behind once poured out.
+const EMPTY_BUCKET: &str = "minecraft:bucket";
+/// The player's inventory, kept in the worlds folder and shared by every world.
+const PLAYER_INVENTORY_FILE: &str = "player_inv.json";
+/// Two presses of Space within this time toggle flight.
+const FLIGHT_TOGGLE_WINDOW: Duration = Duration::from_millis(300);
 const CROSSHAIR_GAP: f32 = 5.0;
 const CROSSHAIR_ARM: f32 = 12.0;
+const CHUNK_UNLOAD_INTERVAL: Duration = Duration::from_secs(1);
+/// Length of one game tick (20 per second).
+const TICK_INTERVAL: Duration = Duration::from_millis(50);
+/// Ticks run in a single frame at most, so a long stall doesn't freeze the game catching up.
+const MAX_TICKS_PER_FRAME: u32 = 10;
 // const SAVE_MESSAGE_DURATION: Duration = Duration::from_secs(4); // reserved for future save UI
 
 struct App {
@@ -90,10 +183,27 @@ struct App {
     screen_prev: Option<AppScreen>,
     /// Temporary buffer for creating new worlds
     new_world_name: String,
+    /// Generator name for the world being created
+    new_world_generator: &'static str,
+    /// Last error from opening or creating a world, shown on the worlds screen
+    world_error: Option<String>,
     /// Pending delete confirmation for a world path
     confirm_delete: Option<std::path::PathBuf>,
     selected_hotbar: usize,
     camera: Camera,
+    /// Player velocity in blocks per second; only the vertical part carries over between frames.
+    velocity: Vec3,
+    on_ground: bool,
+    flying: bool,
+    health: f32,
+    /// Height fallen since last standing on something, for fall damage.
+    fall_distance: f32,
+    /// When Space was last pressed, for double-tap flight toggling.
+    last_jump_press: Option<Instant>,
+    mining: Option<Mining>,
+    item_entities: ItemEntities,
+    /// Crack overlay the renderer is currently showing.
+    shown_overlay: Option<([i32; 3], u8)>,
     input: InputState,
     settings: GameSettings,
     last_frame: Instant,
@@ -106,12 +216,21 @@ struct App {
     mesh_response_rx: mpsc::Receiver<ChunkMeshResponse>,
     pending_chunk_meshes: HashSet<(i32, i32)>,
     last_auto_save: Instant,
+    last_chunk_unload: Instant,
+    /// Frame time not yet consumed by fixed game ticks.
+    tick_accumulator: Duration,
     inventory: crate::inventory::Inventory,
     inventory_open: bool,
-    game_time: f64,
+    recipes: RecipeRegistry,
+    /// The open crafting grid; 3×3 while using a crafting table.
+    crafting: CraftingGrid,
+    inventory_view: InventoryView,
+    /// Chest or furnace whose window is open.
+    open_container: Option<IVec3>,
     mod_manager: crate::mods::ModManager,
     mod_command_rx: std::sync::mpsc::Receiver<crate::mods::ModCommand>,
     mod_reload_rx: std::sync::mpsc::Receiver<std::path::PathBuf>,
+    server: Option<ServerLink>,
 }
 
 impl App {
@@ -126,7 +245,7 @@ impl App {
             height: atlas_build.height,
             pixels: atlas_build.pixels,
         };
-        let world = World::new(None, None);
+        let world = World::new(None, None).expect("Failed to create in-memory world");
     let mut camera = Camera::new(Vec3::new(8.0, 80.0, 8.0), 1.0);
         camera.fov = settings.graphics.fov;
 
@@ -138,9 +257,10 @@ impl App {
     thread::Builder::new()
             .name("chunk-mesh-worker".into())
             .spawn(move || {
-                while let Ok(ChunkMeshRequest { chunk, resolver }) = mesh_request_rx.recv() {
+                while let Ok(ChunkMeshRequest { chunk, border_light, resolver }) = mesh_request_rx.recv() {
                     let coords = (chunk.x, chunk.z);
-                    let (vertices, indices) = Renderer::build_chunk_mesh(&chunk, resolver.as_ref());
+                    let (vertices, indices) =
+                        Renderer::build_chunk_mesh(&chunk, &border_light, resolver.as_ref());
                     let _ = mesh_response_tx.send(ChunkMeshResponse {
                         coords,
                         vertices,
@@ -150,13 +270,17 @@ impl App {
             })
             .expect("Failed to start chunk mesh worker");
 
-        // Try to load inventory from worlds_dir/player_inv.json (fallback to default hotbar)
-        let inv_path = worlds_dir.join("player_inv.json");
-        let inventory = if let Ok(contents) = std::fs::read_to_string(&inv_path) {
+        // Try to load inventory from worlds_dir/player_inv.json (fallback to an empty one)
+        let inv_path = worlds_dir.join(PLAYER_INVENTORY_FILE);
+        let mut inventory = if let Ok(contents) = std::fs::read_to_string(&inv_path) {
             serde_json::from_str(&contents).unwrap_or_else(|_| crate::inventory::Inventory::new(36))
         } else {
             crate::inventory::Inventory::new(36)
         };
+        // The file may predate the item registry or have been edited by hand.
+        for stack in inventory.sanitize() {
+            log::warn!("No room for {} {} from {:?}; dropped", stack.count, stack.id, inv_path);
+        }
 
         // Prepare mod manager and attempt to load scripts from ./mods
         let mut mod_manager = crate::mods::ModManager::new();
@@ -227,9 +351,20 @@ impl App {
             worlds_dir,
             screen_prev: None,
             new_world_name: String::new(),
+            new_world_generator: world::generator::DEFAULT_GENERATOR,
+            world_error: None,
             confirm_delete: None,
             selected_hotbar: 0,
             camera,
+            velocity: Vec3::ZERO,
+            on_ground: false,
+            flying: false,
+            health: MAX_HEALTH,
+            fall_distance: 0.0,
+            last_jump_press: None,
+            mining: None,
+            item_entities: ItemEntities::default(),
+            shown_overlay: None,
             input: InputState::new(),
             settings,
             last_frame: Instant::now(),
@@ -238,16 +373,22 @@ impl App {
             mesh_response_rx,
             pending_chunk_meshes: HashSet::new(),
             last_auto_save: Instant::now(),
+            last_chunk_unload: Instant::now(),
+            tick_accumulator: Duration::ZERO,
             inventory,
             mod_manager,
             mod_command_rx: mod_cmd_rx,
             mod_reload_rx: reload_rx,
-            game_time: 0.0,
+            server: ServerLink::from_env(),
             // last_save_timestamp/save_feedback removed; see struct comment above
             texture_resolver: initial_resolver,
             pending_atlas_upload: Some(pending_atlas_upload),
             active_texture_quality,
             inventory_open: false,
+            recipes: RecipeRegistry::load_or_default(),
+            crafting: CraftingGrid::default(),
+            inventory_view: InventoryView::default(),
+            open_container: None,
         }
     }
 
@@ -301,11 +442,15 @@ impl App {
             return;
         }
 
-        let chunk = self.world.get_chunk(chunk_x, chunk_z).clone();
+        let Some(chunk) = self.world.loaded_chunk(chunk_x, chunk_z).cloned() else {
+            self.world.request_chunk(chunk_x, chunk_z);
+            return;
+        };
         if self
             .mesh_request_tx
             .send(ChunkMeshRequest {
                 chunk,
+                border_light: self.world.border_light(chunk_x, chunk_z),
                 resolver: self.texture_resolver.clone(),
             })
             .is_ok()
@@ -371,7 +516,8 @@ impl App {
                     }
                     crate::mods::ModCommand::SetBlock { x, y, z, id, responder } => {
                         let mut applied = false;
-                        if let Some((cx, cz)) = self.world.set_block_at(x, y, z, crate::world::BlockType::from_id(id)) {
+                        let block = crate::world::BlockType::from_id(id);
+                        if let Some((cx, cz)) = block.and_then(|block| self.world.set_block_at(x, y, z, block)) {
                             self.invalidate_chunk_and_neighbors(cx, cz, glam::IVec3::new(x, y, z));
                             applied = true;
                         }
@@ -387,7 +533,7 @@ impl App {
                     }
                     crate::mods::ModCommand::SetTimeOfDay { time, responder } => {
                         // in this simple engine we'll just set a game_time value
-                        self.game_time = time;
+                        self.world.meta_mut().game_time = time;
                         let _ = responder.send(true);
                     }
                     crate::mods::ModCommand::SpawnEntity { ty, x, y, z, responder } => {
@@ -402,6 +548,50 @@ impl App {
         }
     }
 
+    /// Apply what the server sent since the last frame.
+    fn process_server_messages(&mut self) {
+        while let Some(message) = self.server.as_ref().and_then(|s| s.handle.recv.try_recv().ok()) {
+            match message {
+                ServerMessage::WorldState { players } => {
+                    let Some(server) = self.server.as_mut() else { break };
+                    let me = players.iter().find(|p| match server.id {
+                        Some(id) => p.id == id,
+                        None => p.name == server.name,
+                    });
+                    if let Some(me) = me {
+                        server.id = Some(me.id);
+                        if me.game_mode != self.game_mode() {
+                            self.set_game_mode(me.game_mode);
+                        }
+                    }
+                }
+                ServerMessage::GameMode { id, mode } => {
+                    if self.server.as_ref().is_some_and(|s| s.is_me(id)) && mode != self.game_mode() {
+                        self.set_game_mode(mode);
+                    }
+                }
+                ServerMessage::BlockBroken { x, y, z } => {
+                    let pos = IVec3::new(x, y, z);
+                    // Only the player who broke the block gets its drops.
+                    let ours = self
+                        .server
+                        .as_mut()
+                        .is_some_and(|s| s.pending_breaks.remove(&pos));
+                    if self.world.get_loaded_block(x, y, z).is_some() {
+                        self.remove_block(pos, ours);
+                    }
+                }
+                ServerMessage::BreakRefused { id, x, y, z } => {
+                    if let Some(server) = self.server.as_mut().filter(|s| s.is_me(id)) {
+                        server.pending_breaks.remove(&IVec3::new(x, y, z));
+                        log::info!("Server refused breaking the block at {}, {}, {}", x, y, z);
+                    }
+                }
+                ServerMessage::Ack => {}
+            }
+        }
+    }
+
     fn process_mod_reloads(&mut self) {
         loop {
             match self.mod_reload_rx.try_recv() {
@@ -444,12 +634,14 @@ impl App {
             if last_checked != Some(block_pos) {
                 last_checked = Some(block_pos);
                 if block_pos.y >= 0 && block_pos.y < WORLD_HEIGHT as i32 {
+                    // Don't reach into chunks that are still loading.
                     let block = self
                         .world
-                        .get_block_at(block_pos.x, block_pos.y, block_pos.z);
+                        .get_loaded_block(block_pos.x, block_pos.y, block_pos.z)?;
                     if block.is_solid() {
                         return Some(BlockHit {
                             hit: block_pos,
+                            point: sample,
                             place: last_empty,
                         });
                     } else {
@@ -466,27 +658,204 @@ impl App {
         None
     }
 
+    /// Break the targeted block on the spot, as creative players do.
     fn break_block(&mut self) {
+        if !self.game_mode().can_modify_world() {
+            return;
+        }
         if let Some(hit) = self.pick_block(8.0) {
-            if hit.hit.y < 0 || hit.hit.y >= WORLD_HEIGHT as i32 {
-                return;
+            self.break_block_at(hit.hit);
+        }
+    }
+
+    /// Remove a block, unless it is unbreakable in this game mode or a mod vetoes it.
+    /// When connected, the server is asked instead and the block goes once it agrees.
+    fn break_block_at(&mut self, pos: IVec3) -> bool {
+        if pos.y < 0 || pos.y >= WORLD_HEIGHT as i32 {
+            return false;
+        }
+
+        let block = self.world.get_block_at(pos.x, pos.y, pos.z);
+        if block.hardness() < 0.0 && !self.game_mode().breaks_instantly() {
+            return false;
+        }
+
+        let args = vec![
+            Dynamic::from(pos.x as i64),
+            Dynamic::from(pos.y as i64),
+            Dynamic::from(pos.z as i64),
+            Dynamic::from(block.name().to_string()),
+        ];
+        if !self.mod_manager.trigger_cancellable_event("block_break", args) {
+            return false;
+        }
+
+        if let Some(server) = &mut self.server {
+            if server.pending_breaks.insert(pos) {
+                server.send(ClientMessage::BreakBlock { x: pos.x, y: pos.y, z: pos.z });
             }
+            return true;
+        }
+        self.remove_block(pos, true);
+        true
+    }
 
-            let block = self.world.get_block_at(hit.hit.x, hit.hit.y, hit.hit.z);
-            if block.hardness() < 0.0 {
-                return;
+    /// Take a block out of the world, dropping what it yields if `loot` is set.
+    fn remove_block(&mut self, pos: IVec3, loot: bool) {
+        let block = self.world.get_block_at(pos.x, pos.y, pos.z);
+        // Containers spill what they hold, whatever the game mode.
+        if let Some(entity) = self.world.take_block_entity(pos.x, pos.y, pos.z) {
+            for stack in entity.into_contents() {
+                self.item_entities.drop_at_block(pos, stack);
             }
+        }
+        if let Some((chunk_x, chunk_z)) = self.world.set_block_at(pos.x, pos.y, pos.z, BlockType::Air) {
+            self.invalidate_chunk_and_neighbors(chunk_x, chunk_z, pos);
+        }
+        // Creative players get nothing for what they break.
+        if loot && !self.game_mode().breaks_instantly() && self.world.meta().game_rules.do_tile_drops {
+            self.item_entities.drop_block_loot(pos, block, self.held_tool());
+        }
+    }
 
-            if let Some((chunk_x, chunk_z)) =
-                self.world
-                    .set_block_at(hit.hit.x, hit.hit.y, hit.hit.z, BlockType::Air)
-            {
-                self.invalidate_chunk_and_neighbors(chunk_x, chunk_z, hit.hit);
+    /// Right-click on blocks that do something when used. Returns whether the
+    /// targeted block was used, in which case nothing is placed. Sneaking
+    /// places against it instead.
+    fn use_targeted_block(&mut self) -> bool {
+        if self.input.is_down() || self.game_mode() == GameMode::Spectator {
+            return false;
+        }
+        let Some(hit) = self.pick_block(8.0) else {
+            return false;
+        };
+        match self.world.get_block_at(hit.hit.x, hit.hit.y, hit.hit.z) {
+            BlockType::CraftingTable => {
+                self.set_inventory_open(true, MAX_GRID_SIZE);
+                true
+            }
+            BlockType::Chest | BlockType::Furnace => {
+                if self.world.block_entity(hit.hit.x, hit.hit.y, hit.hit.z).is_none() {
+                    return false;
+                }
+                self.set_inventory_open(true, INVENTORY_GRID_SIZE);
+                self.open_container = Some(hit.hit);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Open the inventory window with a crafting grid of `grid_size`, or close it.
+    /// Closing hands whatever is left in the grid or on the cursor back to the
+    /// inventory, throwing out what doesn't fit.
+    fn set_inventory_open(&mut self, open: bool, grid_size: usize) {
+        let cursor = self.inventory_view.cursor.take();
+        for stack in self.crafting.take_all().into_iter().chain(cursor) {
+            if let Err(left) = self.inventory.add_item(stack) {
+                self.item_entities
+                    .throw(left, self.camera.position, self.camera.get_front());
+            }
+        }
+        self.crafting = CraftingGrid::new(grid_size);
+        self.inventory_view.cancel_drag();
+        self.open_container = None;
+        self.inventory_open = open;
+        self.mining = None;
+        self.set_cursor_capture(!open);
+    }
+
+    /// Throw one item from the selected hotbar slot, or the whole stack.
+    fn throw_held_item(&mut self, whole_stack: bool) {
+        let count = if whole_stack { u16::MAX } else { 1 };
+        if let Some(stack) = self.inventory.remove_at(self.selected_hotbar, count) {
+            self.item_entities
+                .throw(stack, self.camera.position, self.camera.get_front());
+        }
+    }
+
+    /// Move dropped items and pick up those the player reaches.
+    fn update_item_entities(&mut self, dt: f32) {
+        self.item_entities.update(&self.world, dt);
+        if self.game_mode().picks_up_items() {
+            let body = self.camera.position - Vec3::Y * (PLAYER_EYE_HEIGHT / 2.0);
+            self.item_entities.collect(body, &mut self.inventory, dt);
+        }
+    }
+
+    /// The tool in the selected hotbar slot, if it holds one.
+    fn held_tool(&self) -> Option<Tool> {
+        self.inventory
+            .slots
+            .get(self.selected_hotbar)
+            .and_then(Option::as_ref)
+            .and_then(|stack| Tool::from_item(&stack.id))
+    }
+
+    /// Advance hold-to-mine on the targeted block. Mining starts over whenever the
+    /// button is let go or the crosshair moves to another block.
+    fn update_mining(&mut self, dt: f32) {
+        let mode = self.game_mode();
+        let target = if self.input.is_attacking() && mode.can_modify_world() && !mode.breaks_instantly() {
+            self.pick_block(8.0).map(|hit| hit.hit)
+        } else {
+            None
+        };
+        let Some(pos) = target else {
+            self.mining = None;
+            self.show_mining_overlay();
+            return;
+        };
+
+        let block = self.world.get_block_at(pos.x, pos.y, pos.z);
+        if !matches!(&self.mining, Some(m) if m.pos == pos && m.block == block) {
+            self.mining = Some(Mining {
+                pos,
+                block,
+                progress: 0.0,
+            });
+        }
+
+        match mining::break_time(block, self.held_tool()) {
+            Some(time) => {
+                let done = match self.mining.as_mut() {
+                    Some(m) => {
+                        m.progress += if time > 0.0 { dt / time } else { 1.0 };
+                        m.progress >= 1.0
+                    }
+                    None => false,
+                };
+                if done {
+                    self.mining = None;
+                    self.break_block_at(pos);
+                }
             }
+            // Unbreakable: the cracks never appear.
+            None => self.mining = None,
+        }
+        self.show_mining_overlay();
+    }
+
+    /// Keep the renderer's crack overlay in step with the mining progress.
+    fn show_mining_overlay(&mut self) {
+        let overlay = self.mining.as_ref().map(|m| {
+            let stage = ((m.progress * DESTROY_STAGES as f32) as u8).min(DESTROY_STAGES - 1);
+            (m.pos.to_array(), stage)
+        });
+        if overlay == self.shown_overlay {
+            return;
+        }
+        self.shown_overlay = overlay;
+        if let Some(renderer) = &mut self.renderer {
+            let overlay = overlay.map(|(pos, stage)| (pos, TextureKey::DestroyStage(stage)));
+            renderer.set_block_overlay(overlay, &self.texture_resolver);
         }
     }
 
     fn place_block(&mut self) {
+        let mode = self.game_mode();
+        if !mode.can_modify_world() {
+            return;
+        }
         if let Some(hit) = self.pick_block(8.0) {
             let Some(place_pos) = hit.place else {
                 return;
@@ -496,19 +865,63 @@ impl App {
                 return;
             }
 
+            // Only items that place a block do anything here.
+            let Some(block_to_place) = self.held_block() else {
+                return;
+            };
+
+            // Pouring water onto a block that can hold it waterlogs the block instead.
+            let clicked = self.world.get_state_at(hit.hit.x, hit.hit.y, hit.hit.z);
+            if block_to_place == BlockType::Water && clicked.has(Property::Waterlogged) {
+                if let Some((chunk_x, chunk_z)) = self.world.set_state_at(
+                    hit.hit.x,
+                    hit.hit.y,
+                    hit.hit.z,
+                    clicked.with_waterlogged(true),
+                ) {
+                    self.invalidate_chunk_and_neighbors(chunk_x, chunk_z, hit.hit);
+                    self.use_placed_item(mode, block_to_place);
+                }
+                return;
+            }
+
             let target = self
                 .world
-                .get_block_at(place_pos.x, place_pos.y, place_pos.z);
-            if target.is_solid() {
+                .get_state_at(place_pos.x, place_pos.y, place_pos.z);
+            if target.block.is_solid() {
                 return;
             }
 
-            let block_to_place = self.current_block_type();
+            let face = Facing::from_offset((place_pos - hit.hit).into()).unwrap_or(Facing::Up);
+            let hit_y = hit.point.y - hit.point.y.floor();
+            let state = BlockState::for_placement(block_to_place, face, hit_y, self.camera.yaw)
+                .with_waterlogged(target.block == BlockType::Water && target.level() == 0);
             if let Some((chunk_x, chunk_z)) =
                 self.world
-                    .set_block_at(place_pos.x, place_pos.y, place_pos.z, block_to_place)
+                    .set_state_at(place_pos.x, place_pos.y, place_pos.z, state)
             {
                 self.invalidate_chunk_and_neighbors(chunk_x, chunk_z, place_pos);
+                self.use_placed_item(mode, block_to_place);
+            }
+        }
+    }
+
+    /// Outside creative, placing uses up the held item. Pouring out a bucket of
+    /// fluid leaves the empty bucket in its place.
+    fn use_placed_item(&mut self, mode: GameMode, block: BlockType) {
+        if !mode.consumes_items() {
+            return;
+        }
+        let Some(slot) = self.inventory.slots.get_mut(self.selected_hotbar) else {
+            return;
+        };
+        take_from_slot(slot, 1);
+        if Fluid::of(block).is_some() {
+            let bucket = ItemStack { id: EMPTY_BUCKET.to_string(), count: 1 };
+            let left = merge_into_slot(slot, bucket).and_then(|left| self.inventory.add_item(left).err());
+            if let Some(left) = left {
+                self.item_entities
+                    .throw(left, self.camera.position, self.camera.get_front());
             }
         }
     }
@@ -534,17 +947,25 @@ impl App {
         }
     }
 
-    fn current_block_type(&self) -> BlockType {
-        HOTBAR_BLOCKS
-            .get(self.selected_hotbar)
-            .map(|(block, _)| *block)
-            .unwrap_or(BlockType::GrassBlock)
+    /// The block the item in the selected hotbar slot places, if it places one.
+    fn held_block(&self) -> Option<BlockType> {
+        let stack = self.inventory.slots.get(self.selected_hotbar)?.as_ref()?;
+        ItemRegistry::global().block_for(&stack.id)
     }
 
-    fn current_block_label(&self) -> Option<&'static str> {
-        HOTBAR_BLOCKS
-            .get(self.selected_hotbar)
-            .map(|(_, label)| *label)
+    /// Display name and count of the item in each hotbar slot.
+    fn hotbar_labels(&self) -> Vec<Option<(String, u16)>> {
+        let items = ItemRegistry::global();
+        self.inventory
+            .slots
+            .iter()
+            .take(HOTBAR_SIZE)
+            .map(|slot| {
+                let stack = slot.as_ref()?;
+                let name = items.get(&stack.id).map_or_else(|| stack.id.clone(), |item| item.display_name.clone());
+                Some((name, stack.count))
+            })
+            .collect()
     }
 
     fn handle_hotbar_key(&mut self, key: KeyCode) -> bool {
@@ -557,6 +978,7 @@ impl App {
             KeyCode::Digit6 => Some(5),
             KeyCode::Digit7 => Some(6),
             KeyCode::Digit8 => Some(7),
+            KeyCode::Digit9 => Some(8),
             _ => None,
         };
 
@@ -568,17 +990,71 @@ impl App {
         }
     }
 
+    fn game_mode(&self) -> GameMode {
+        self.world.meta().game_mode
+    }
+
+    /// Switch game mode, or ask the server to when connected.
+    fn request_game_mode(&mut self, mode: GameMode) {
+        match &self.server {
+            Some(server) => server.send(ClientMessage::SetGameMode { mode }),
+            None => self.set_game_mode(mode),
+        }
+    }
+
+    fn set_game_mode(&mut self, mode: GameMode) {
+        self.world.meta_mut().game_mode = mode;
+        self.flying = mode.always_flying() || (self.flying && mode.allows_flight());
+        self.velocity = Vec3::ZERO;
+        self.fall_distance = 0.0;
+        log::info!("Game mode set to {}", mode.name());
+    }
+
+    /// Put the player back to a fresh state, after opening a world or dying.
+    fn reset_player(&mut self) {
+        self.velocity = Vec3::ZERO;
+        self.on_ground = false;
+        self.flying = self.game_mode().always_flying();
+        self.health = MAX_HEALTH;
+        self.fall_distance = 0.0;
+    }
+
+    fn damage_player(&mut self, amount: f32) {
+        if !self.game_mode().takes_damage() || amount <= 0.0 {
+            return;
+        }
+        self.health -= amount;
+        if self.health <= 0.0 {
+            log::info!("Player died; respawning");
+            self.camera.position = Vec3::from(self.world.meta().spawn);
+            self.reset_player();
+        }
+    }
+
+    fn handle_jump_press(&mut self) {
+        let mode = self.game_mode();
+        if !mode.allows_flight() || mode.always_flying() {
+            return;
+        }
+        let now = Instant::now();
+        match self.last_jump_press {
+            Some(last) if now - last < FLIGHT_TOGGLE_WINDOW => {
+                self.flying = !self.flying;
+                self.velocity = Vec3::ZERO;
+                self.last_jump_press = None;
+            }
+            _ => self.last_jump_press = Some(now),
+        }
+    }
+
     fn select_hotbar(&mut self, index: usize) {
-        if index < HOTBAR_BLOCKS.len() {
+        if index < HOTBAR_SIZE {
             self.selected_hotbar = index;
         }
     }
 
     fn cycle_hotbar(&mut self, offset: i32) {
-        let len = HOTBAR_BLOCKS.len() as i32;
-        if len == 0 {
-            return;
-        }
+        let len = HOTBAR_SIZE as i32;
         let current = self.selected_hotbar as i32;
         let mut next = (current + offset) % len;
         if next < 0 {
@@ -597,7 +1073,15 @@ impl App {
         }
     }
 
-    fn move_with_collisions(&mut self, position: Vec3, motion: Vec3) -> Vec3 {
+    /// The player's bounding box for an eye position.
+    fn player_box(position: Vec3) -> BlockBox {
+        BlockBox::new(
+            [position.x - PLAYER_RADIUS, position.y - PLAYER_EYE_HEIGHT, position.z - PLAYER_RADIUS],
+            [position.x + PLAYER_RADIUS, position.y + PLAYER_HEADROOM, position.z + PLAYER_RADIUS],
+        )
+    }
+
+    fn move_with_collisions(&self, position: Vec3, motion: Vec3) -> Vec3 {
         let mut pos = position;
         if motion.x.abs() > f32::EPSILON {
             pos = self.move_axis_x(pos, motion.x);
@@ -611,130 +1095,217 @@ impl App {
         pos
     }
 
-    fn move_axis_x(&mut self, position: Vec3, dx: f32) -> Vec3 {
+    fn move_axis_x(&self, position: Vec3, dx: f32) -> Vec3 {
+        self.move_axis(position, 0, dx)
+    }
+
+    fn move_axis_y(&self, position: Vec3, dy: f32) -> Vec3 {
+        self.move_axis(position, 1, dy)
+    }
+
+    fn move_axis_z(&self, position: Vec3, dz: f32) -> Vec3 {
+        self.move_axis(position, 2, dz)
+    }
+
+    /// Sweep the player's box along one axis, stopping at the first block in the way.
+    fn move_axis(&self, position: Vec3, axis: usize, delta: f32) -> Vec3 {
         let mut pos = position;
-        let mut new_x = pos.x + dx;
-
-        let min_y = (pos.y - PLAYER_EYE_HEIGHT).floor() as i32;
-        let max_y = (pos.y + PLAYER_HEADROOM).floor() as i32;
-        let min_z = (pos.z - PLAYER_RADIUS).floor() as i32;
-        let max_z = (pos.z + PLAYER_RADIUS).floor() as i32;
-
-        if dx > 0.0 {
-            let max_x = (new_x + PLAYER_RADIUS).floor() as i32;
-            for y in min_y..=max_y {
-                for z in min_z..=max_z {
-                    if self.world.get_block_at(max_x, y, z).is_solid() {
-                        new_x = max_x as f32 - PLAYER_RADIUS - COLLISION_EPSILON;
-                        pos.x = new_x;
-                        return pos;
-                    }
-                }
-            }
+        pos[axis] += self.world.sweep_box(Self::player_box(position), axis, delta);
+        pos
+    }
+
+    /// Whether there is something to stand on within a step below the player's feet.
+    fn has_ground_below(&self, position: Vec3) -> bool {
+        self.move_axis(position, 1, -PLAYER_STEP_HEIGHT).y > position.y - PLAYER_STEP_HEIGHT
+    }
+
+    /// The fluid the player's lower body is in, if any.
+    fn fluid_at_player(&self) -> Option<Fluid> {
+        let feet = self.camera.position.y - PLAYER_EYE_HEIGHT;
+        let (x, z) = (self.camera.position.x.floor() as i32, self.camera.position.z.floor() as i32);
+        [feet + 0.1, feet + 0.8].into_iter().find_map(|y| {
+            self.world
+                .get_loaded_state(x, y.floor() as i32, z)
+                .and_then(Fluid::of_state)
+        })
+    }
+
+    fn block_below_player(&self) -> Option<BlockType> {
+        let feet = self.camera.position - Vec3::Y * (PLAYER_EYE_HEIGHT + 0.01);
+        self.world
+            .get_loaded_block(feet.x.floor() as i32, feet.y.floor() as i32, feet.z.floor() as i32)
+    }
+
+    /// Free flight: Space rises, Shift descends faster, and there is no gravity.
+    /// Spectators pass through blocks.
+    fn update_flying(&mut self, dt: f32) {
+        let speed = if self.input.is_down() {
+            FLY_SPEED * 2.5 * dt
         } else {
-            let min_x = (new_x - PLAYER_RADIUS).floor() as i32;
-            for y in min_y..=max_y {
-                for z in min_z..=max_z {
-                    if self.world.get_block_at(min_x, y, z).is_solid() {
-                        new_x = min_x as f32 + 1.0 + PLAYER_RADIUS + COLLISION_EPSILON;
-                        pos.x = new_x;
-                        return pos;
-                    }
-                }
-            }
+            FLY_SPEED * dt
+        };
+
+        let front = self.camera.get_front();
+        let right = self.camera.get_right();
+        let mut movement = Vec3::ZERO;
+
+        if self.input.is_forward() {
+            movement += front;
+        }
+        if self.input.is_backward() {
+            movement -= front;
+        }
+        if self.input.is_left() {
+            movement -= right;
+        }
+        if self.input.is_right() {
+            movement += right;
+        }
+        if self.input.is_up() {
+            movement += Vec3::Y;
+        }
+        if self.input.is_down() {
+            movement -= Vec3::Y;
         }
 
-        pos.x = new_x;
-        pos
+        if movement.length_squared() > f32::EPSILON {
+            let movement = movement.normalize_or_zero() * speed;
+            self.camera.position = if self.game_mode().has_collision() {
+                self.move_with_collisions(self.camera.position, movement)
+            } else {
+                self.camera.position + movement
+            };
+        }
     }
 
-    fn move_axis_y(&mut self, position: Vec3, dy: f32) -> Vec3 {
-        let mut pos = position;
-        let mut new_y = pos.y + dy;
-
-        let min_x = (pos.x - PLAYER_RADIUS).floor() as i32;
-        let max_x = (pos.x + PLAYER_RADIUS).floor() as i32;
-        let min_z = (pos.z - PLAYER_RADIUS).floor() as i32;
-        let max_z = (pos.z + PLAYER_RADIUS).floor() as i32;
-
-        if dy > 0.0 {
-            let max_y = (new_y + PLAYER_HEADROOM).floor() as i32;
-            for x in min_x..=max_x {
-                for z in min_z..=max_z {
-                    if self.world.get_block_at(x, max_y, z).is_solid() {
-                        new_y = max_y as f32 - PLAYER_HEADROOM - COLLISION_EPSILON;
-                        pos.y = new_y;
-                        return pos;
-                    }
-                }
-            }
+    /// Walking, jumping and swimming under gravity.
+    fn update_walking(&mut self, dt: f32) {
+        let front = self.camera.get_front();
+        let front = Vec3::new(front.x, 0.0, front.z).normalize_or_zero();
+        let right = self.camera.get_right();
+        let right = Vec3::new(right.x, 0.0, right.z).normalize_or_zero();
+        let mut wish = Vec3::ZERO;
+        if self.input.is_forward() {
+            wish += front;
+        }
+        if self.input.is_backward() {
+            wish -= front;
+        }
+        if self.input.is_left() {
+            wish -= right;
+        }
+        if self.input.is_right() {
+            wish += right;
+        }
+
+        let fluid = self.fluid_at_player();
+        let sneaking = self.input.is_down() && fluid.is_none();
+        let sprinting = self.input.is_sprint() && self.input.is_forward() && !sneaking;
+        let mut speed = if sneaking {
+            SNEAK_SPEED
+        } else if sprinting {
+            SPRINT_SPEED
         } else {
-            let min_y = (new_y - PLAYER_EYE_HEIGHT).floor() as i32;
-            for x in min_x..=max_x {
-                for z in min_z..=max_z {
-                    if self.world.get_block_at(x, min_y, z).is_solid() {
-                        new_y = min_y as f32 + 1.0 + PLAYER_EYE_HEIGHT + COLLISION_EPSILON;
-                        pos.y = new_y;
-                        return pos;
-                    }
+            WALK_SPEED
+        };
+        speed *= match fluid {
+            Some(Fluid::Water) => WATER_SPEED_FACTOR,
+            Some(Fluid::Lava) => LAVA_SPEED_FACTOR,
+            None => 1.0,
+        };
+        if self.on_ground && self.block_below_player() == Some(BlockType::SoulSand) {
+            speed *= SOUL_SAND_SPEED_FACTOR;
+        }
+
+        match fluid {
+            Some(fluid) => {
+                // Fluids pull the player down slowly and damp all vertical motion;
+                // holding Space swims upwards.
+                let accel = if self.input.is_up() { SWIM_ACCELERATION } else { -FLUID_SINK_ACCELERATION };
+                let drag = match fluid {
+                    Fluid::Water => WATER_DRAG,
+                    Fluid::Lava => LAVA_DRAG,
+                };
+                self.velocity.y = (self.velocity.y + accel * dt) * drag.powf(dt * 20.0);
+            }
+            None => {
+                if self.on_ground && self.input.is_up() {
+                    self.velocity.y = JUMP_VELOCITY;
                 }
+                self.velocity.y = (self.velocity.y - GRAVITY * dt).max(-TERMINAL_VELOCITY);
             }
         }
 
-        pos.y = new_y;
-        pos
-    }
+        let start = self.camera.position;
+        let dy = self.velocity.y * dt;
+        let mut pos = self.move_axis_y(start, dy);
+        let blocked_y = (pos.y - (start.y + dy)).abs() > f32::EPSILON;
+        if blocked_y {
+            self.on_ground = dy < 0.0;
+            self.velocity.y = 0.0;
+        } else {
+            self.on_ground = false;
+        }
 
-    fn move_axis_z(&mut self, position: Vec3, dz: f32) -> Vec3 {
-        let mut pos = position;
-        let mut new_z = pos.z + dz;
-
-        let min_y = (pos.y - PLAYER_EYE_HEIGHT).floor() as i32;
-        let max_y = (pos.y + PLAYER_HEADROOM).floor() as i32;
-        let min_x = (pos.x - PLAYER_RADIUS).floor() as i32;
-        let max_x = (pos.x + PLAYER_RADIUS).floor() as i32;
-
-        if dz > 0.0 {
-            let max_z = (new_z + PLAYER_RADIUS).floor() as i32;
-            for y in min_y..=max_y {
-                for x in min_x..=max_x {
-                    if self.world.get_block_at(x, y, max_z).is_solid() {
-                        new_z = max_z as f32 - PLAYER_RADIUS - COLLISION_EPSILON;
-                        pos.z = new_z;
-                        return pos;
-                    }
-                }
+        let mut motion = wish.normalize_or_zero() * speed * dt;
+        // Sneaking players don't walk off edges: drop any part of the move that
+        // would leave them without ground to stand on.
+        if sneaking && self.on_ground {
+            if !self.has_ground_below(pos + Vec3::new(motion.x, 0.0, 0.0)) {
+                motion.x = 0.0;
             }
-        } else {
-            let min_z = (new_z - PLAYER_RADIUS).floor() as i32;
-            for y in min_y..=max_y {
-                for x in min_x..=max_x {
-                    if self.world.get_block_at(x, y, min_z).is_solid() {
-                        new_z = min_z as f32 + 1.0 + PLAYER_RADIUS + COLLISION_EPSILON;
-                        pos.z = new_z;
-                        return pos;
-                    }
-                }
+            if !self.has_ground_below(pos + Vec3::new(motion.x, 0.0, motion.z)) {
+                motion.z = 0.0;
             }
         }
 
-        pos.z = new_z;
-        pos
+        let before = pos;
+        let target = before + motion;
+        let walked = self.move_axis_z(self.move_axis_x(before, motion.x), motion.z);
+        let blocked = (walked.x - target.x).abs() > f32::EPSILON || (walked.z - target.z).abs() > f32::EPSILON;
+        pos = walked;
+        if blocked && self.on_ground {
+            // Step up onto slabs and stairs: repeat the move from a step higher and
+            // settle back down, keeping it if that got further.
+            let raised = self.move_axis_y(before, PLAYER_STEP_HEIGHT);
+            let stepped = self.move_axis_z(self.move_axis_x(raised, motion.x), motion.z);
+            let settled = self.move_axis_y(stepped, before.y - raised.y);
+            let progress = |p: Vec3| Vec3::new(p.x - target.x, 0.0, p.z - target.z).length();
+            if progress(settled) + f32::EPSILON < progress(walked) {
+                pos = settled;
+            }
+        }
+        if blocked && fluid.is_some() && self.input.is_up() {
+            // Climb out of water onto a bank.
+            self.velocity.y = self.velocity.y.max(FLUID_EXIT_VELOCITY);
+        }
+
+        self.camera.position = pos;
+
+        // Last, since dying moves the player back to spawn.
+        let mut damage = 0.0;
+        if fluid.is_some() {
+            self.fall_distance = 0.0;
+        } else if self.on_ground {
+            damage += (std::mem::take(&mut self.fall_distance) - SAFE_FALL_DISTANCE).max(0.0);
+        } else if pos.y < start.y {
+            self.fall_distance += start.y - pos.y;
+        }
+        if fluid == Some(Fluid::Lava) {
+            damage += LAVA_DAMAGE_PER_SECOND * dt;
+        }
+        self.damage_player(damage);
     }
 
     fn draw_hotbar_overlay(
         ctx: &egui::Context,
         selected_hotbar: usize,
-        current_label: Option<&'static str>,
+        slots: &[Option<(String, u16)>],
     ) -> Option<usize> {
-        if HOTBAR_BLOCKS.is_empty() {
-            return None;
-        }
-
         let slot_size = 48.0;
         let spacing = 6.0;
         let mut selection: Option<usize> = None;
-        let mut active_label = current_label;
+        let mut active_label = slots.get(selected_hotbar).and_then(|slot| slot.as_ref()).map(|(name, _)| name.as_str());
 
         egui::TopBottomPanel::bottom("hotbar_panel")
             .frame(
@@ -755,14 +1326,14 @@ impl App {
 
                     ui.horizontal_centered(|ui| {
                         ui.spacing_mut().item_spacing.x = spacing;
-                        for (index, (_, label)) in HOTBAR_BLOCKS.iter().enumerate() {
+                        for (index, slot) in slots.iter().enumerate() {
                             let (rect, response) = ui.allocate_exact_size(
                                 egui::vec2(slot_size, slot_size),
                                 egui::Sense::click(),
                             );
                             if response.clicked() {
                                 selection = Some(index);
-                                active_label = Some(*label);
+                                active_label = slot.as_ref().map(|(name, _)| name.as_str());
                             }
 
                             let is_selected = selection.unwrap_or(selected_hotbar) == index;
@@ -796,13 +1367,24 @@ impl App {
                             } else {
                                 egui::Color32::from_rgb(220, 220, 220)
                             };
-                            painter.text(
-                                inner.center(),
-                                egui::Align2::CENTER_CENTER,
-                                *label,
-                                egui::FontId::proportional(15.0),
-                                text_color,
-                            );
+                            if let Some((name, count)) = slot {
+                                let galley = painter.layout(
+                                    name.clone(),
+                                    egui::FontId::proportional(11.0),
+                                    text_color,
+                                    inner.width() - 4.0,
+                                );
+                                painter.galley(inner.center() - galley.size() / 2.0, galley, text_color);
+                                if *count > 1 {
+                                    painter.text(
+                                        rect.right_bottom() + egui::vec2(-6.0, -4.0),
+                                        egui::Align2::RIGHT_BOTTOM,
+                                        count.to_string(),
+                                        egui::FontId::proportional(13.0),
+                                        egui::Color32::from_rgb(245, 245, 245),
+                                    );
+                                }
+                            }
 
                             painter.text(
                                 rect.left_bottom() + egui::vec2(6.0, -4.0),
@@ -866,6 +1448,8 @@ impl App {
         if self.screen != AppScreen::Playing {
             // Ensure mouse deltas don't accumulate while in menus
             self.input.reset_mouse_delta();
+            self.mining = None;
+            self.show_mining_overlay();
             return;
         }
 
@@ -873,41 +1457,17 @@ impl App {
         self.process_mod_commands();
         // Process any requested mod reloads (file modifications detected by the poller thread)
         self.process_mod_reloads();
+        self.process_server_messages();
 
-        let dt = self.delta_time.as_secs_f32();
-        let speed = if self.input.is_down() {
-            50.0 * dt
+        // Clamped so a long frame can't launch the player through the floor.
+        let dt = self.delta_time.as_secs_f32().min(MAX_PHYSICS_STEP);
+        if self.flying || self.game_mode().always_flying() {
+            self.update_flying(dt);
         } else {
-            20.0 * dt
-        };
-
-        let front = self.camera.get_front();
-        let right = self.camera.get_right();
-        let mut movement = Vec3::ZERO;
-
-        if self.input.is_forward() {
-            movement += front;
-        }
-        if self.input.is_backward() {
-            movement -= front;
-        }
-        if self.input.is_left() {
-            movement -= right;
-        }
-        if self.input.is_right() {
-            movement += right;
-        }
-        if self.input.is_up() {
-            movement += Vec3::Y;
-        }
-        if self.input.is_down() {
-            movement -= Vec3::Y;
-        }
-
-        if movement.length_squared() > f32::EPSILON {
-            let movement = movement.normalize_or_zero() * speed;
-            self.camera.position = self.move_with_collisions(self.camera.position, movement);
+            self.update_walking(dt);
         }
+        self.update_mining(dt);
+        self.update_item_entities(dt);
 
         // Update camera rotation based on mouse
         if self.input.is_mouse_captured() {
@@ -923,12 +1483,61 @@ impl App {
         let player_chunk_z = (self.camera.position.z / world::CHUNK_SIZE as f32).floor() as i32;
         let render_distance = self.settings.graphics.render_distance as i32;
 
+        self.world
+            .set_load_center(player_chunk_x, player_chunk_z, render_distance + 1);
+        for (chunk_x, chunk_z) in self.world.process_loaded_chunks() {
+            // Loaded neighbours may have just received overhanging trees or light.
+            self.invalidate_chunk_mesh(chunk_x, chunk_z);
+        }
+
+        self.tick_accumulator += self.delta_time;
+        let mut ticks = 0;
+        while self.tick_accumulator >= TICK_INTERVAL && ticks < MAX_TICKS_PER_FRAME {
+            self.tick_accumulator -= TICK_INTERVAL;
+            ticks += 1;
+            for (chunk_x, chunk_z) in self.world.tick() {
+                self.invalidate_chunk_mesh(chunk_x, chunk_z);
+            }
+        }
+        if ticks == MAX_TICKS_PER_FRAME {
+            self.tick_accumulator = Duration::ZERO;
+        }
+        // Once per game tick is plenty for the server to keep our chunks loaded.
+        if let Some(server) = self.server.as_ref().filter(|_| ticks > 0) {
+            let position = self.camera.position;
+            server.send(ClientMessage::Position {
+                x: position.x,
+                y: position.y,
+                z: position.z,
+                yaw: self.camera.yaw,
+                pitch: self.camera.pitch,
+            });
+        }
+
+        for (chunk_x, chunk_z) in self.world.take_relit_chunks() {
+            self.invalidate_chunk_mesh(chunk_x, chunk_z);
+        }
+
         for x in (player_chunk_x - render_distance)..=(player_chunk_x + render_distance) {
             for z in (player_chunk_z - render_distance)..=(player_chunk_z + render_distance) {
                 self.ensure_chunk_mesh(x, z);
             }
         }
 
+        if self.last_chunk_unload.elapsed() >= CHUNK_UNLOAD_INTERVAL {
+            let policy = self.settings.chunk_retention_policy();
+            let unloaded = self
+                .world
+                .unload_chunks(&[(player_chunk_x, player_chunk_z)], policy);
+            for (chunk_x, chunk_z) in unloaded {
+                if let Some(renderer) = &mut self.renderer {
+                    renderer.invalidate_chunk(chunk_x, chunk_z);
+                }
+                self.pending_chunk_meshes.remove(&(chunk_x, chunk_z));
+            }
+            self.last_chunk_unload = Instant::now();
+        }
+
         let autosave_secs = self.settings.autosave_interval_secs.max(1) as u64;
         if self.last_auto_save.elapsed() >= Duration::from_secs(autosave_secs) {
             if self.world.has_dirty_chunks() {
@@ -936,9 +1545,7 @@ impl App {
                 self.world.save_meta();
             }
             // Save player inventory as part of autosave
-            if let Err(e) = self.save_player_inventory() {
-                eprintln!("Failed to save player inventory: {}", e);
-            }
+            Self::save_player_inventory(&self.worlds_dir, &self.inventory);
             self.last_auto_save = Instant::now();
         }
 
@@ -949,16 +1556,22 @@ impl App {
         if let Some(renderer) = &mut self.renderer {
             renderer.update_camera(&self.camera);
             renderer.update_chunks(&self.world, self.camera.position, render_distance);
+            renderer.update_item_entities(&self.world, &self.item_entities, &self.texture_resolver);
         }
     }
 }
 
 impl App {
-    fn save_player_inventory(&self) -> anyhow::Result<()> {
-        let inv_path = self.worlds_dir.join("player_inv.json");
-        let contents = serde_json::to_string_pretty(&self.inventory)?;
-        std::fs::write(inv_path, contents)?;
-        Ok(())
+    /// Replace the saved inventory in `worlds_dir` atomically. Takes the fields
+    /// rather than `self` so the UI closures can call it too.
+    fn save_player_inventory(worlds_dir: &std::path::Path, inventory: &crate::inventory::Inventory) {
+        let path = worlds_dir.join(PLAYER_INVENTORY_FILE);
+        let result = serde_json::to_vec_pretty(inventory)
+            .map_err(std::io::Error::from)
+            .and_then(|contents| write_atomic(&path, &contents));
+        if let Err(e) = result {
+            eprintln!("Failed to save player inventory to {:?}: {}", path, e);
+        }
     }
 }
 
@@ -1011,10 +1624,11 @@ impl ApplicationHandler for App {
 
         match event {
             WindowEvent::CloseRequested => {
-                // Persist settings and world metadata before exiting
+                // Persist settings, the world and the inventory before exiting
                 let _ = self.settings.save();
                 self.world.save_dirty_chunks();
                 self.world.save_meta();
+                Self::save_player_inventory(&self.worlds_dir, &self.inventory);
                 event_loop.exit();
             }
             WindowEvent::Resized(physical_size) => {
@@ -1030,6 +1644,7 @@ impl ApplicationHandler for App {
                     KeyEvent {
                         physical_key: PhysicalKey::Code(key),
                         state,
+                        repeat,
                         ..
                     },
                 ..
@@ -1040,7 +1655,22 @@ impl ApplicationHandler for App {
                 match state {
                     ElementState::Pressed => {
                         self.input.key_pressed(key);
-                        let _ = self.handle_hotbar_key(key);
+                        // With the inventory open, number keys swap slots with the hotbar instead.
+                        if !self.inventory_open {
+                            let _ = self.handle_hotbar_key(key);
+                        }
+                        if key == KeyCode::Space && !repeat && self.screen == AppScreen::Playing {
+                            self.handle_jump_press();
+                        }
+                        if key == KeyCode::F4 && !repeat && self.screen == AppScreen::Playing {
+                            self.request_game_mode(self.game_mode().next());
+                        }
+                        if key == KeyCode::KeyE && !repeat && self.screen == AppScreen::Playing {
+                            self.set_inventory_open(!self.inventory_open, INVENTORY_GRID_SIZE);
+                        }
+                        if key == KeyCode::KeyQ && self.screen == AppScreen::Playing {
+                            self.throw_held_item(self.input.is_sprint());
+                        }
                         if key == KeyCode::Escape {
                             // Toggle pause when playing
                             if self.screen == AppScreen::Playing {
@@ -1068,6 +1698,13 @@ impl ApplicationHandler for App {
                     self.adjust_hotbar_from_scroll(&delta);
                 }
             }
+            WindowEvent::MouseInput {
+                state: ElementState::Released,
+                button,
+                ..
+            } => {
+                self.input.button_released(button);
+            }
             WindowEvent::MouseInput {
                 state: ElementState::Pressed,
                 button,
@@ -1081,14 +1718,25 @@ impl ApplicationHandler for App {
                     return;
                 }
 
+                // The inventory window keeps the cursor until it is closed.
+                if self.inventory_open {
+                    return;
+                }
+
                 if !self.input.is_mouse_captured() {
                     self.set_cursor_capture(true);
                     return;
                 }
 
+                self.input.button_pressed(button);
                 match button {
-                    MouseButton::Left => self.break_block(),
-                    MouseButton::Right => self.place_block(),
+                    // Outside creative, holding the button mines; see `update_mining`.
+                    MouseButton::Left if self.game_mode().breaks_instantly() => self.break_block(),
+                    MouseButton::Right => {
+                        if !self.use_targeted_block() {
+                            self.place_block();
+                        }
+                    }
                     _ => {}
                 }
             }
@@ -1098,10 +1746,14 @@ impl ApplicationHandler for App {
 
                 if self.renderer.is_some() && self.gui.is_some() && self.window.is_some() {
                     let mut pending_hotbar_selection: Option<usize> = None;
+                    let mut window_overflow = Vec::new();
+                    let inventory_was_open = self.inventory_open;
                     let window_arc = self.window.as_ref().cloned().unwrap();
                     let window_ref = window_arc.as_ref();
                     let selected_hotbar = self.selected_hotbar;
-                    let current_hotbar_label = self.current_block_label();
+                    let hotbar_labels = self.hotbar_labels();
+                    let game_mode = self.game_mode();
+                    let health = self.health;
                     let gui = self.gui.as_mut().unwrap();
 
                     // Take raw input from winit
@@ -1124,6 +1776,12 @@ impl ApplicationHandler for App {
                                                 1.0 / self.delta_time.as_secs_f32()
                                             ));
                                         }
+                                        if matches!(self.screen, AppScreen::Playing | AppScreen::Paused) {
+                                            if game_mode.takes_damage() {
+                                                ui.label(format!("Health: {:.0}/{:.0}", health.ceil(), MAX_HEALTH));
+                                            }
+                                            ui.label(format!("Mode: {} (F4)", game_mode.name()));
+                                        }
                                     },
                                 );
                             });
@@ -1131,13 +1789,37 @@ impl ApplicationHandler for App {
 
                         if matches!(self.screen, AppScreen::Playing | AppScreen::Paused) {
                             if let Some(new_selection) =
-                                App::draw_hotbar_overlay(ctx, selected_hotbar, current_hotbar_label)
+                                App::draw_hotbar_overlay(ctx, selected_hotbar, &hotbar_labels)
                             {
                                 pending_hotbar_selection = Some(new_selection);
                             }
                         }
-                        // Draw inventory UI (hotbar + full inventory window)
-                        gui.draw_inventory(ctx, &mut self.inventory, &mut self.selected_hotbar, &mut self.inventory_open);
+                        // Draw the inventory window; the hotbar above is its first row.
+                        // An open chest or furnace shows the inventory in its own window.
+                        let mut inventory_window_open = self.inventory_open && self.open_container.is_none();
+                        window_overflow = gui.draw_inventory(
+                            ctx,
+                            &mut self.inventory,
+                            &mut inventory_window_open,
+                            &mut self.crafting,
+                            &self.recipes,
+                            &mut self.inventory_view,
+                            game_mode == GameMode::Creative,
+                        );
+                        match self.open_container {
+                            None => self.inventory_open = inventory_window_open,
+                            Some(pos) => match self.world.block_entity_mut(pos.x, pos.y, pos.z) {
+                                Some(entity) => window_overflow.extend(gui.draw_block_entity(
+                                    ctx,
+                                    entity,
+                                    &mut self.inventory,
+                                    &mut self.inventory_open,
+                                    &mut self.inventory_view,
+                                )),
+                                // Broken while open.
+                                None => self.inventory_open = false,
+                            },
+                        }
                         if matches!(self.screen, AppScreen::Playing) {
                             App::draw_crosshair(ctx);
                         }
@@ -1205,10 +1887,30 @@ impl ApplicationHandler for App {
                                                     let p = path.clone();
                                                     self.world.save_dirty_chunks();
                                                     self.world.save_meta();
-                                                    self.world = World::new(Some(p.clone()), None);
-                                                    self.screen = AppScreen::Playing;
-                                                    self.input.set_mouse_captured(true);
-                                                    App::apply_cursor_capture(window_ref, true);
+                                                    match World::new(Some(p.clone()), None) {
+                                                        Ok(world) => {
+                                                            self.world = world;
+                                                            self.item_entities.clear();
+                                                            self.camera.position =
+                                                                Vec3::from(self.world.meta().spawn);
+                                                            // Same as `reset_player`, which the UI closure can't call.
+                                                            self.velocity = Vec3::ZERO;
+                                                            self.on_ground = false;
+                                                            self.flying = self.world.meta().game_mode.always_flying();
+                                                            self.health = MAX_HEALTH;
+                                                            self.fall_distance = 0.0;
+                                                            self.world_error = None;
+                                                            self.screen = AppScreen::Playing;
+                                                            self.input.set_mouse_captured(true);
+                                                            App::apply_cursor_capture(window_ref, true);
+                                                        }
+                                                        Err(err) => {
+                                                            self.world_error = Some(format!(
+                                                                "Could not open '{}': {:#}",
+                                                                name, err
+                                                            ));
+                                                        }
+                                                    }
                                                 }
                                                 if ui.button("Delete").clicked() {
                                                     self.confirm_delete = Some(path.clone());
@@ -1216,18 +1918,47 @@ impl ApplicationHandler for App {
                                             });
                                         }
 
+                                        if let Some(error) = &self.world_error {
+                                            ui.colored_label(egui::Color32::LIGHT_RED, error);
+                                        }
+
                                         ui.add_space(8.0);
                                         ui.horizontal(|ui| {
                                             ui.label("Create new world:");
                                             ui.text_edit_singleline(&mut self.new_world_name);
+                                            let selected_label = GENERATOR_PRESETS
+                                                .iter()
+                                                .find(|(name, _)| *name == self.new_world_generator)
+                                                .map(|(_, label)| *label)
+                                                .unwrap_or_default();
+                                            egui::ComboBox::from_id_salt("new_world_generator")
+                                                .selected_text(selected_label)
+                                                .show_ui(ui, |ui| {
+                                                    for (name, label) in GENERATOR_PRESETS {
+                                                        ui.selectable_value(
+                                                            &mut self.new_world_generator,
+                                                            *name,
+                                                            *label,
+                                                        );
+                                                    }
+                                                });
                                             if ui.button("Create").clicked() {
                                                 if !self.new_world_name.trim().is_empty() {
                                                     let mut new_path = self.worlds_dir.clone();
                                                     new_path.push(self.new_world_name.trim());
                                                     let _ = std::fs::create_dir_all(&new_path);
                                                     // Initialize world metadata by constructing World
-                                                    let _ =
-                                                        World::new(Some(new_path.clone()), None);
+                                                    if let Err(err) = World::with_generator(
+                                                        Some(new_path.clone()),
+                                                        None,
+                                                        self.new_world_generator,
+                                                        serde_json::Value::Null,
+                                                    ) {
+                                                        self.world_error = Some(format!(
+                                                            "Could not create world: {:#}",
+                                                            err
+                                                        ));
+                                                    }
                                                     self.new_world_name.clear();
                                                 }
                                             }
@@ -1301,6 +2032,7 @@ impl ApplicationHandler for App {
                                         // Save world metadata and go back to main menu
                                         self.world.save_dirty_chunks();
                                         self.world.save_meta();
+                                        App::save_player_inventory(&self.worlds_dir, &self.inventory);
                                         let _ = self.settings.save();
                                         self.screen = AppScreen::MainMenu;
                                         self.input.set_mouse_captured(false);
@@ -1319,6 +2051,7 @@ impl ApplicationHandler for App {
                         if request_quit {
                             self.world.save_dirty_chunks();
                             self.world.save_meta();
+                            App::save_player_inventory(&self.worlds_dir, &self.inventory);
                             event_loop.exit();
                             return;
                         }
@@ -1479,6 +2212,14 @@ impl ApplicationHandler for App {
                     if let Some(index) = pending_hotbar_selection {
                         self.select_hotbar(index);
                     }
+                    for stack in window_overflow {
+                        self.item_entities
+                            .throw(stack, self.camera.position, self.camera.get_front());
+                    }
+                    if inventory_was_open && !self.inventory_open {
+                        // Closed from the window rather than with E.
+                        self.set_inventory_open(false, INVENTORY_GRID_SIZE);
+                    }
                 }
 
                 let cap = if self.screen == AppScreen::Playing {
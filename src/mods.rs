@@ -1,6 +1,7 @@
 #![allow(dead_code)]
+use crate::world::BlockType;
 use anyhow::{Context, Result};
-use rhai::{Engine, AST, Dynamic, Scope};
+use rhai::{Engine, EvalAltResult, AST, Dynamic, Scope};
 use std::collections::HashMap;
 use std::path::Path;
 use std::sync::mpsc::{self, Sender};
@@ -32,6 +33,17 @@ impl ModManager {
             println!("[mod] {}", s);
         });
 
+        // Block registry lookups, so mods can refer to blocks by name
+        engine.register_fn("block_id", |name: &str| {
+            BlockType::from_name(name).map_or(-1, |block| block as i64)
+        });
+        engine.register_fn("block_name", |id: i64| {
+            u16::try_from(id)
+                .ok()
+                .and_then(BlockType::from_id)
+                .map_or_else(String::new, |block| block.name().to_string())
+        });
+
         // Provide a small time helper
         engine.register_fn("now", || {
             SystemTime::now()
@@ -136,11 +148,34 @@ impl ModManager {
             // is missing or errors, log the error.
             let call_result = self
                 .engine
-                .call_fn::<()>(&mut scope, ast, "on_event", (event.to_string(), args.clone()));
+                .call_fn::<Dynamic>(&mut scope, ast, "on_event", (event.to_string(), args.clone()));
             if let Err(e) = call_result {
                 let msg = format!("mod '{}' on_event error: {}", name, e);
                 eprintln!("{}", msg);
             }
         }
     }
+
+    /// Trigger an event mods can veto by returning `false` from `on_event`. Every mod
+    /// sees the event even after one has vetoed it. Returns whether it may go ahead.
+    pub fn trigger_cancellable_event(&self, event: &str, args: Vec<Dynamic>) -> bool {
+        let mut allowed = true;
+        for (name, ast) in &self.scripts {
+            let mut scope = Scope::new();
+            let call_result = self
+                .engine
+                .call_fn::<Dynamic>(&mut scope, ast, "on_event", (event.to_string(), args.clone()));
+            match call_result {
+                Ok(result) => {
+                    if result.as_bool() == Ok(false) {
+                        allowed = false;
+                    }
+                }
+                // Mods without an `on_event` have no say.
+                Err(e) if matches!(*e, EvalAltResult::ErrorFunctionNotFound(..)) => {}
+                Err(e) => eprintln!("mod '{}' on_event error: {}", name, e),
+            }
+        }
+        allowed
+    }
 }
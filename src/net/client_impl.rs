@@ -5,7 +5,6 @@ use bytes::Bytes;
 use futures::{SinkExt, StreamExt};
 
 use std::error::Error;
-use std::net::SocketAddr;
 
 use std::sync::mpsc::{self, Receiver, Sender};
 use tokio::net::TcpStream;
@@ -54,7 +53,7 @@ async fn run_connection(
     out_rx: &mut tokio::sync::mpsc::UnboundedReceiver<ClientMessage>,
     in_tx: Sender<ServerMessage>,
 ) -> Result<(), Box<dyn Error>> {
-    let mut framed = Framed::new(stream, LengthDelimitedCodec::new());
+    let framed = Framed::new(stream, LengthDelimitedCodec::new());
     let (mut sink, mut stream) = framed.split();
     // task to forward incoming server messages to main thread
     let in_task = tokio::spawn(async move {
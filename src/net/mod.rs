@@ -1,4 +1,4 @@
 pub mod client_impl;
 pub use client_impl as client;
-pub use client_impl::{start_client, ClientHandle};
+pub use client::{start_client, ClientHandle};
 pub mod protocol;
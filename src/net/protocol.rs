@@ -1,15 +1,26 @@
+use crate::world::game_mode::GameMode;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum ClientMessage {
     Connect { name: String },
     Position { x: f32, y: f32, z: f32, yaw: f32, pitch: f32 },
+    /// Ask to switch game mode; the server decides and answers with `GameMode`.
+    SetGameMode { mode: GameMode },
+    /// Ask to break a block; the server answers with `BlockBroken` or `BreakRefused`.
+    BreakBlock { x: i32, y: i32, z: i32 },
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum ServerMessage {
     WorldState { players: Vec<PlayerSnapshot> },
     Ack,
+    /// The game mode the server has given player `id`.
+    GameMode { id: u64, mode: GameMode },
+    /// A block was broken and should be removed by every client.
+    BlockBroken { x: i32, y: i32, z: i32 },
+    /// Player `id` may not break this block; its client should put it back.
+    BreakRefused { id: u64, x: i32, y: i32, z: i32 },
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -21,5 +32,5 @@ pub struct PlayerSnapshot {
     pub z: f32,
     pub yaw: f32,
     pub pitch: f32,
+    pub game_mode: GameMode,
 }
-
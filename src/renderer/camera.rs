@@ -7,6 +7,8 @@ pub struct Vertex {
     pub tex_coords: [f32; 2],
     pub color: [f32; 3],
     pub normal: [f32; 3],
+    /// Sky and block light of the face, 0.0 to 1.0.
+    pub light: [f32; 2],
 }
 
 impl Vertex {
@@ -35,6 +37,11 @@ impl Vertex {
                     shader_location: 3,
                     format: wgpu::VertexFormat::Float32x3,
                 },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 11]>() as wgpu::BufferAddress,
+                    shader_location: 4,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
             ],
         }
     }
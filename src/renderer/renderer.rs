@@ -1,6 +1,11 @@
 use super::camera::{Camera, CameraUniform, Vertex};
-use super::texture::{texture_key_for, AtlasUV, BlockFace, TextureResolver};
+use super::texture::{texture_key_for, BlockFace, TextureKey, TextureResolver};
 use super::advanced::AdvancedRenderer;
+use crate::world::item_entity::{ItemEntities, ITEM_HALF_SIZE};
+use crate::world::light::{BorderLight, LightKind, MAX_LIGHT};
+use crate::world::section::SECTION_SIZE;
+use crate::world::shape::{self, BlockBox};
+use crate::world::state::{Axis, BlockState, Facing, Property};
 use crate::world::{BlockType, Chunk, World, CHUNK_SIZE, WORLD_HEIGHT};
 use glam::Vec3;
 use std::collections::HashMap;
@@ -27,6 +32,10 @@ pub struct Renderer {
     atlas_texture_view: Option<wgpu::TextureView>,
     atlas_sampler: wgpu::Sampler,
     chunk_meshes: HashMap<(i32, i32), ChunkMesh>,
+    /// Cracks over the block being mined.
+    overlay_mesh: Option<ChunkMesh>,
+    /// Dropped items, rebuilt every frame since they move.
+    item_mesh: Option<ChunkMesh>,
     pub advanced: AdvancedRenderer,
     // Shadow mapping resources (placeholder)
     shadow_texture: Option<wgpu::Texture>,
@@ -382,6 +391,8 @@ impl Renderer {
             atlas_texture_view: None,
             atlas_sampler,
             chunk_meshes: HashMap::new(),
+            overlay_mesh: None,
+            item_mesh: None,
             advanced: AdvancedRenderer::new(),
             shadow_texture: None,
             shadow_view: None,
@@ -542,6 +553,144 @@ impl Renderer {
         self.chunk_meshes.contains_key(&(chunk_x, chunk_z))
     }
 
+    /// Draw `texture` over every face of the block at `pos`, or clear the overlay with `None`.
+    pub fn set_block_overlay(&mut self, overlay: Option<([i32; 3], TextureKey)>, texture_resolver: &TextureResolver) {
+        let Some((pos, texture)) = overlay else {
+            self.overlay_mesh = None;
+            return;
+        };
+        // Slightly larger than the block so the overlay doesn't fight it for depth.
+        const GROW: f32 = 0.002;
+        let rect = texture_resolver.uv(texture);
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        for face in [
+            BlockFace::Top,
+            BlockFace::Bottom,
+            BlockFace::North,
+            BlockFace::South,
+            BlockFace::East,
+            BlockFace::West,
+        ] {
+            let start_index = vertices.len() as u32;
+            for corner in Self::face_vertices(face) {
+                let [u, v] = Self::face_uv(face, corner);
+                vertices.push(Vertex {
+                    position: [0, 1, 2].map(|axis| {
+                        pos[axis] as f32 + corner[axis] * (1.0 + 2.0 * GROW) - GROW
+                    }),
+                    tex_coords: [
+                        rect.u_min + u * (rect.u_max - rect.u_min),
+                        rect.v_min + v * (rect.v_max - rect.v_min),
+                    ],
+                    color: [1.0, 1.0, 1.0],
+                    normal: Self::face_normal(face),
+                    light: [1.0, 1.0],
+                });
+            }
+            indices.extend_from_slice(&[
+                start_index,
+                start_index + 1,
+                start_index + 2,
+                start_index,
+                start_index + 2,
+                start_index + 3,
+            ]);
+        }
+
+        self.overlay_mesh = Some(self.create_mesh("Overlay", &vertices, &indices));
+    }
+
+    /// Draw each dropped item as a small spinning, bobbing cube textured like
+    /// its block. Items that aren't blocks use the atlas tile of the same name,
+    /// if there is one.
+    pub fn update_item_entities(&mut self, world: &World, items: &ItemEntities, texture_resolver: &TextureResolver) {
+        if items.is_empty() {
+            self.item_mesh = None;
+            return;
+        }
+        const SIZE: f32 = ITEM_HALF_SIZE * 2.0;
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        for item in items.iter() {
+            let block = BlockType::from_name(&item.stack.id);
+            let color = block.map_or([1.0; 3], |block| block.definition().color);
+            let item_texture = item
+                .stack
+                .id
+                .strip_prefix("minecraft:")
+                .and_then(TextureKey::from_name)
+                .unwrap_or(TextureKey::Stone);
+            let cell = item.position.floor().as_ivec3();
+            let light = [LightKind::Sky, LightKind::Block]
+                .map(|kind| world.light_at(kind, cell.x, cell.y, cell.z) as f32 / MAX_LIGHT as f32);
+            let center = item.position + Vec3::Y * (item.age * 2.0).sin() * 0.05;
+            let spin = glam::Quat::from_rotation_y(item.age);
+
+            for face in [
+                BlockFace::Top,
+                BlockFace::Bottom,
+                BlockFace::North,
+                BlockFace::South,
+                BlockFace::East,
+                BlockFace::West,
+            ] {
+                let texture = match block {
+                    Some(block) => texture_key_for(BlockState::new(block), face),
+                    None => item_texture,
+                };
+                let rect = texture_resolver.uv(texture);
+                let normal = spin * Vec3::from(Self::face_normal(face));
+                let start_index = vertices.len() as u32;
+                for corner in Self::face_vertices(face) {
+                    let [u, v] = Self::face_uv(face, corner);
+                    let local = (Vec3::from(corner) - Vec3::splat(0.5)) * SIZE;
+                    vertices.push(Vertex {
+                        position: (center + spin * local).to_array(),
+                        tex_coords: [
+                            rect.u_min + u * (rect.u_max - rect.u_min),
+                            rect.v_min + v * (rect.v_max - rect.v_min),
+                        ],
+                        color,
+                        normal: normal.to_array(),
+                        light,
+                    });
+                }
+                indices.extend_from_slice(&[
+                    start_index,
+                    start_index + 1,
+                    start_index + 2,
+                    start_index,
+                    start_index + 2,
+                    start_index + 3,
+                ]);
+            }
+        }
+        self.item_mesh = Some(self.create_mesh("Item", &vertices, &indices));
+    }
+
+    fn create_mesh(&self, label: &str, vertices: &[Vertex], indices: &[u32]) -> ChunkMesh {
+        let vertex_buffer = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(&format!("{label} Vertex Buffer")),
+                contents: bytemuck::cast_slice(vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+        let index_buffer = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(&format!("{label} Index Buffer")),
+                contents: bytemuck::cast_slice(indices),
+                usage: wgpu::BufferUsages::INDEX,
+            });
+        ChunkMesh {
+            vertex_buffer,
+            index_buffer,
+            num_indices: indices.len() as u32,
+        }
+    }
+
     pub fn upload_chunk_mesh(
         &mut self,
         coords: (i32, i32),
@@ -581,6 +730,7 @@ impl Renderer {
 
     pub fn build_chunk_mesh(
         chunk: &Chunk,
+        border_light: &BorderLight,
         texture_resolver: &TextureResolver,
     ) -> (Vec<Vertex>, Vec<u32>) {
         let mut vertices = Vec::new();
@@ -594,56 +744,81 @@ impl Renderer {
 
         for x in 0..CHUNK_SIZE {
             for y in 0..WORLD_HEIGHT {
+                // All-air sections have no faces of their own; neighbours handle the borders.
+                if chunk.is_section_empty(y / SECTION_SIZE) {
+                    continue;
+                }
                 for z in 0..CHUNK_SIZE {
-                    let block = chunk.get_block(x, y, z);
+                    let state = chunk.get_state(x, y, z);
+                    let block = state.block;
                     if !block.is_solid() {
                         continue;
                     }
 
                     let pos = Vec3::new(x as f32, y as f32, z as f32) + chunk_offset;
-                    let color = block.get_color();
-
-                    let faces = [
-                        (
-                            BlockFace::Top,
-                            y + 1 >= WORLD_HEIGHT
-                                || !chunk.get_block(x, y + 1, z).is_solid(),
-                        ),
-                        (
-                            BlockFace::Bottom,
-                            y == 0 || !chunk.get_block(x, y.wrapping_sub(1), z).is_solid(),
-                        ),
-                        (
-                            BlockFace::North,
-                            z + 1 >= CHUNK_SIZE
-                                || !chunk.get_block(x, y, z + 1).is_solid(),
-                        ),
-                        (
-                            BlockFace::South,
-                            z == 0 || !chunk.get_block(x, y, z - 1).is_solid(),
-                        ),
-                        (
-                            BlockFace::East,
-                            x + 1 >= CHUNK_SIZE
-                                || !chunk.get_block(x + 1, y, z).is_solid(),
-                        ),
-                        (
-                            BlockFace::West,
-                            x == 0 || !chunk.get_block(x - 1, y, z).is_solid(),
-                        ),
-                    ];
-
-                    for (face, visible) in faces {
-                        if visible {
-                            Self::emit_face(
-                                block,
-                                face,
-                                pos,
-                                color,
-                                texture_resolver,
-                                &mut vertices,
-                                &mut indices,
-                            );
+                    let color = match block {
+                        BlockType::GrassBlock => chunk.biome(x, z).grass_tint(),
+                        BlockType::OakLeaves => chunk.biome(x, z).foliage_tint(),
+                        _ => block.get_color(),
+                    };
+
+                    let open = |x: usize, y: usize, z: usize| !chunk.get_block(x, y, z).is_full_cube();
+                    // Fences and panes only join neighbours in this chunk; the
+                    // mesh doesn't see across chunk borders.
+                    let shape = block.shape();
+                    let connected = |facing: Facing| {
+                        let (dx, _, dz) = facing.offset();
+                        let (nx, nz) = (x as i32 + dx, z as i32 + dz);
+                        let size = CHUNK_SIZE as i32;
+                        (0..size).contains(&nx)
+                            && (0..size).contains(&nz)
+                            && shape.connects_to(chunk.get_block(nx as usize, y, nz as usize))
+                    };
+
+                    for block_box in shape::block_boxes(state, connected) {
+                        // Faces inside the block space are always drawn; faces on its
+                        // boundary only when the neighbour doesn't cover them.
+                        let faces = [
+                            (
+                                BlockFace::Top,
+                                block_box.max[1] < 1.0 || y + 1 >= WORLD_HEIGHT || open(x, y + 1, z),
+                            ),
+                            (
+                                BlockFace::Bottom,
+                                block_box.min[1] > 0.0 || y == 0 || open(x, y - 1, z),
+                            ),
+                            (
+                                BlockFace::North,
+                                block_box.max[2] < 1.0 || z + 1 >= CHUNK_SIZE || open(x, y, z + 1),
+                            ),
+                            (
+                                BlockFace::South,
+                                block_box.min[2] > 0.0 || z == 0 || open(x, y, z - 1),
+                            ),
+                            (
+                                BlockFace::East,
+                                block_box.max[0] < 1.0 || x + 1 >= CHUNK_SIZE || open(x + 1, y, z),
+                            ),
+                            (
+                                BlockFace::West,
+                                block_box.min[0] > 0.0 || x == 0 || open(x - 1, y, z),
+                            ),
+                        ];
+
+                        for (face, visible) in faces {
+                            if visible {
+                                Self::emit_face(
+                                    state,
+                                    face,
+                                    block_box,
+                                    pos,
+                                    color,
+                                    Self::face_light(chunk, border_light, [x, y, z], face),
+                                    texture_resolver,
+                                    &mut vertices,
+                                    &mut indices,
+                                );
+                            }
                         }
                     }
                 }
@@ -654,19 +829,46 @@ impl Renderer {
     }
 
     fn emit_face(
-        block: BlockType,
+        state: BlockState,
         face: BlockFace,
+        block_box: BlockBox,
         base_pos: Vec3,
         color: [f32; 3],
+        light: [f32; 2],
         texture_resolver: &TextureResolver,
         vertices: &mut Vec<Vertex>,
         indices: &mut Vec<u32>,
     ) {
-        let rect = texture_resolver.uv(texture_key_for(block, face));
-        let positions = Self::face_vertices(face);
-        let uvs = Self::face_uvs(face, rect);
+        let rect = texture_resolver.uv(texture_key_for(state, face));
+        let positions = Self::face_vertices(face).map(|corner| {
+            [0, 1, 2].map(|axis| {
+                let (min, max) = (block_box.min[axis], block_box.max[axis]);
+                min + corner[axis] * (max - min)
+            })
+        });
+        // Crop the texture to the part of the face the box covers, so partial
+        // shapes show the same texels a full cube would in that spot.
+        let mut uvs = positions.map(|position| {
+            let [u, v] = Self::face_uv(face, position);
+            [
+                rect.u_min + u * (rect.u_max - rect.u_min),
+                rect.v_min + v * (rect.v_max - rect.v_min),
+            ]
+        });
         let normal = Self::face_normal(face);
 
+        // Lying logs turn their bark to run along the log.
+        if state.has(Property::Axis) {
+            let rotate = match state.axis() {
+                Axis::X => matches!(face, BlockFace::Top | BlockFace::Bottom | BlockFace::North | BlockFace::South),
+                Axis::Z => matches!(face, BlockFace::East | BlockFace::West),
+                Axis::Y => false,
+            };
+            if rotate {
+                uvs.rotate_left(1);
+            }
+        }
+
         let start_index = vertices.len() as u32;
         for (pos_offset, tex) in positions.iter().zip(uvs.iter()) {
             vertices.push(Vertex {
@@ -678,6 +880,7 @@ impl Renderer {
                 tex_coords: *tex,
                 color,
                 normal,
+                light,
             });
         }
 
@@ -691,6 +894,30 @@ impl Renderer {
         ]);
     }
 
+    /// Sky and block light of the block a face looks into, scaled to 0.0..=1.0.
+    fn face_light(chunk: &Chunk, border_light: &BorderLight, pos: [usize; 3], face: BlockFace) -> [f32; 2] {
+        let normal = Self::face_normal(face);
+        let x = pos[0] as i32 + normal[0] as i32;
+        let y = pos[1] as i32 + normal[1] as i32;
+        let z = pos[2] as i32 + normal[2] as i32;
+        let size = CHUNK_SIZE as i32;
+
+        let [sky, block] = [LightKind::Sky, LightKind::Block].map(|kind| {
+            if y >= WORLD_HEIGHT as i32 {
+                return if kind == LightKind::Sky { MAX_LIGHT } else { 0 };
+            }
+            if y < 0 {
+                return 0;
+            }
+            if (0..size).contains(&x) && (0..size).contains(&z) {
+                chunk.light(kind, x as usize, y as usize, z as usize)
+            } else {
+                border_light.get(kind, x, y as usize, z)
+            }
+        });
+        [sky as f32 / MAX_LIGHT as f32, block as f32 / MAX_LIGHT as f32]
+    }
+
     fn face_vertices(face: BlockFace) -> [[f32; 3]; 4] {
         match face {
             BlockFace::Top => [
@@ -732,44 +959,15 @@ impl Renderer {
         }
     }
 
-    fn face_uvs(face: BlockFace, rect: AtlasUV) -> [[f32; 2]; 4] {
+    /// Where a point on a face falls within the face's texture, 0.0 to 1.0 on each axis.
+    fn face_uv(face: BlockFace, [x, y, z]: [f32; 3]) -> [f32; 2] {
         match face {
-            BlockFace::Top => [
-                [rect.u_min, rect.v_max],
-                [rect.u_min, rect.v_min],
-                [rect.u_max, rect.v_min],
-                [rect.u_max, rect.v_max],
-            ],
-            BlockFace::Bottom => [
-                [rect.u_min, rect.v_min],
-                [rect.u_max, rect.v_min],
-                [rect.u_max, rect.v_max],
-                [rect.u_min, rect.v_max],
-            ],
-            BlockFace::North => [
-                [rect.u_min, rect.v_max],
-                [rect.u_max, rect.v_max],
-                [rect.u_max, rect.v_min],
-                [rect.u_min, rect.v_min],
-            ],
-            BlockFace::South => [
-                [rect.u_max, rect.v_max],
-                [rect.u_max, rect.v_min],
-                [rect.u_min, rect.v_min],
-                [rect.u_min, rect.v_max],
-            ],
-            BlockFace::East => [
-                [rect.u_max, rect.v_max],
-                [rect.u_max, rect.v_min],
-                [rect.u_min, rect.v_min],
-                [rect.u_min, rect.v_max],
-            ],
-            BlockFace::West => [
-                [rect.u_min, rect.v_max],
-                [rect.u_max, rect.v_max],
-                [rect.u_max, rect.v_min],
-                [rect.u_min, rect.v_min],
-            ],
+            BlockFace::Top => [x, 1.0 - z],
+            BlockFace::Bottom => [x, z],
+            BlockFace::North => [x, 1.0 - y],
+            BlockFace::South => [1.0 - x, 1.0 - y],
+            BlockFace::East => [1.0 - z, 1.0 - y],
+            BlockFace::West => [z, 1.0 - y],
         }
     }
 
@@ -824,6 +1022,19 @@ impl Renderer {
             render_pass.set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
             render_pass.draw_indexed(0..mesh.num_indices, 0, 0..1);
         }
+
+        if let Some(mesh) = &self.item_mesh {
+            render_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+            render_pass.set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+            render_pass.draw_indexed(0..mesh.num_indices, 0, 0..1);
+        }
+
+        // After the chunks, so the cracks blend over the block beneath them.
+        if let Some(mesh) = &self.overlay_mesh {
+            render_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+            render_pass.set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+            render_pass.draw_indexed(0..mesh.num_indices, 0, 0..1);
+        }
     }
 
     /// Accessor for the depth view used by the renderer (read-only).
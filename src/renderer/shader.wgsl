@@ -12,6 +12,7 @@ struct VertexInput {
     @location(1) tex_coords: vec2<f32>,
     @location(2) color: vec3<f32>,
     @location(3) normal: vec3<f32>,
+    @location(4) light: vec2<f32>,
 };
 
 struct VertexOutput {
@@ -20,6 +21,7 @@ struct VertexOutput {
     @location(1) color: vec3<f32>,
     @location(2) normal: vec3<f32>,
     @location(3) world_pos: vec3<f32>,
+    @location(4) light: vec2<f32>,
 };
 
 @vertex
@@ -32,6 +34,7 @@ fn vs_main(
     out.tex_coords = model.tex_coords;
     out.color = model.color;
     out.normal = model.normal;
+    out.light = model.light;
     return out;
 }
 
@@ -50,9 +53,14 @@ var shadow_sampler: sampler_comparison;
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     let light_dir = normalize(vec3<f32>(0.5, 1.0, 0.3));
     let ambient = 0.4;
-    let diffuse = max(dot(normalize(in.normal), light_dir), 0.0) * 0.6;
+    // The sun only reaches what sky light reaches; torches and lava light all sides evenly.
+    let diffuse = max(dot(normalize(in.normal), light_dir), 0.0) * 0.6 * in.light.x;
     var lighting = ambient + diffuse;
 
+    // Each light level below 15 is about 20% darker, with a little left over so unlit caves aren't pitch black.
+    let level = max(in.light.x, in.light.y) * 15.0;
+    lighting = lighting * (0.03 + 0.97 * pow(0.8, 15.0 - level));
+
     // Simple shadow lookup (placeholder): project world_pos by light matrix supplied as part of camera
     // NOTE: real integration requires a light-view-proj uniform; for now we assume the shader receives
     // shadow coordinates in in.world_pos.xyzw via a precomputed pipeline. This is placeholder logic.
@@ -1,34 +1,326 @@
 #![allow(dead_code)]
-use crate::world::BlockType;
+use crate::world::registry::BlockRegistry;
+use crate::world::state::{Axis, BlockState, Facing, Property};
 use image::{ImageBuffer, Rgba, RgbaImage};
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
+
+/// Wood species, for the textures every species has its own version of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Wood {
+    Oak,
+    Spruce,
+    Birch,
+    Jungle,
+    Acacia,
+    DarkOak,
+    Mangrove,
+    Cherry,
+    Crimson,
+    Warped,
+}
+
+impl Wood {
+    pub const ALL: [Wood; 10] = [
+        Wood::Oak,
+        Wood::Spruce,
+        Wood::Birch,
+        Wood::Jungle,
+        Wood::Acacia,
+        Wood::DarkOak,
+        Wood::Mangrove,
+        Wood::Cherry,
+        Wood::Crimson,
+        Wood::Warped,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Wood::Oak => "oak",
+            Wood::Spruce => "spruce",
+            Wood::Birch => "birch",
+            Wood::Jungle => "jungle",
+            Wood::Acacia => "acacia",
+            Wood::DarkOak => "dark_oak",
+            Wood::Mangrove => "mangrove",
+            Wood::Cherry => "cherry",
+            Wood::Crimson => "crimson",
+            Wood::Warped => "warped",
+        }
+    }
+
+    /// Nether fungi grow stems rather than logs and have no leaves.
+    fn is_fungus(self) -> bool {
+        matches!(self, Wood::Crimson | Wood::Warped)
+    }
+
+    fn planks_color(self) -> [u8; 3] {
+        match self {
+            Wood::Oak => [160, 120, 70],
+            Wood::Spruce => [115, 85, 50],
+            Wood::Birch => [195, 175, 120],
+            Wood::Jungle => [160, 115, 80],
+            Wood::Acacia => [170, 90, 50],
+            Wood::DarkOak => [65, 42, 20],
+            Wood::Mangrove => [118, 54, 48],
+            Wood::Cherry => [226, 178, 172],
+            Wood::Crimson => [101, 48, 70],
+            Wood::Warped => [43, 104, 99],
+        }
+    }
+
+    fn bark_color(self) -> [u8; 3] {
+        match self {
+            Wood::Oak => [100, 70, 45],
+            Wood::Spruce => [60, 40, 22],
+            Wood::Birch => [215, 215, 205],
+            Wood::Jungle => [85, 68, 25],
+            Wood::Acacia => [105, 98, 88],
+            Wood::DarkOak => [60, 46, 26],
+            Wood::Mangrove => [84, 66, 40],
+            Wood::Cherry => [55, 30, 40],
+            Wood::Crimson => [95, 25, 45],
+            Wood::Warped => [58, 58, 77],
+        }
+    }
+
+    fn leaves_color(self) -> [u8; 3] {
+        match self {
+            Wood::Oak => [50, 120, 50],
+            Wood::Spruce => [60, 95, 60],
+            Wood::Birch => [110, 150, 70],
+            Wood::Jungle => [50, 130, 30],
+            Wood::Acacia => [80, 120, 30],
+            Wood::DarkOak => [50, 110, 30],
+            Wood::Mangrove => [70, 130, 40],
+            Wood::Cherry => [230, 170, 200],
+            Wood::Crimson | Wood::Warped => [0, 0, 0],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DyeColor {
+    White,
+    Orange,
+    Magenta,
+    LightBlue,
+    Yellow,
+    Lime,
+    Pink,
+    Gray,
+    LightGray,
+    Cyan,
+    Purple,
+    Blue,
+    Brown,
+    Green,
+    Red,
+    Black,
+}
+
+impl DyeColor {
+    pub const ALL: [DyeColor; 16] = [
+        DyeColor::White,
+        DyeColor::Orange,
+        DyeColor::Magenta,
+        DyeColor::LightBlue,
+        DyeColor::Yellow,
+        DyeColor::Lime,
+        DyeColor::Pink,
+        DyeColor::Gray,
+        DyeColor::LightGray,
+        DyeColor::Cyan,
+        DyeColor::Purple,
+        DyeColor::Blue,
+        DyeColor::Brown,
+        DyeColor::Green,
+        DyeColor::Red,
+        DyeColor::Black,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DyeColor::White => "white",
+            DyeColor::Orange => "orange",
+            DyeColor::Magenta => "magenta",
+            DyeColor::LightBlue => "light_blue",
+            DyeColor::Yellow => "yellow",
+            DyeColor::Lime => "lime",
+            DyeColor::Pink => "pink",
+            DyeColor::Gray => "gray",
+            DyeColor::LightGray => "light_gray",
+            DyeColor::Cyan => "cyan",
+            DyeColor::Purple => "purple",
+            DyeColor::Blue => "blue",
+            DyeColor::Brown => "brown",
+            DyeColor::Green => "green",
+            DyeColor::Red => "red",
+            DyeColor::Black => "black",
+        }
+    }
+
+    pub fn rgb(self) -> [u8; 3] {
+        match self {
+            DyeColor::White => [233, 236, 236],
+            DyeColor::Orange => [240, 118, 19],
+            DyeColor::Magenta => [189, 68, 179],
+            DyeColor::LightBlue => [58, 175, 217],
+            DyeColor::Yellow => [248, 197, 39],
+            DyeColor::Lime => [112, 185, 25],
+            DyeColor::Pink => [237, 141, 172],
+            DyeColor::Gray => [62, 68, 71],
+            DyeColor::LightGray => [142, 142, 134],
+            DyeColor::Cyan => [21, 137, 145],
+            DyeColor::Purple => [121, 42, 172],
+            DyeColor::Blue => [53, 57, 157],
+            DyeColor::Brown => [114, 71, 40],
+            DyeColor::Green => [84, 109, 27],
+            DyeColor::Red => [160, 39, 34],
+            DyeColor::Black => [20, 21, 25],
+        }
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum TextureKey {
     GrassTop,
     GrassSide,
     Dirt,
+    CoarseDirt,
+    PodzolTop,
+    MyceliumTop,
+    RootedDirt,
+    Mud,
+    PackedMud,
+    MudBricks,
+    Clay,
+    Gravel,
+    Sand,
+    RedSand,
     Stone,
+    SmoothStone,
+    Granite,
+    PolishedGranite,
+    Diorite,
+    PolishedDiorite,
+    Andesite,
+    PolishedAndesite,
+    Calcite,
+    Tuff,
+    DripstoneBlock,
     Deepslate,
+    CobbledDeepslate,
+    PolishedDeepslate,
+    DeepslateBricks,
+    DeepslateTiles,
     Bedrock,
-    Sand,
     Cobblestone,
-    OakPlanks,
-    OakLog,
-    OakLeaves,
+    MossyCobblestone,
+    StoneBricks,
+    MossyStoneBricks,
+    CrackedStoneBricks,
+    ChiseledStoneBricks,
+    Sandstone,
+    SandstoneTop,
+    CutSandstone,
+    ChiseledSandstone,
+    RedSandstone,
+    RedSandstoneTop,
+    CutRedSandstone,
+    ChiseledRedSandstone,
+    Bricks,
+    Terracotta,
     Glass,
     CoalOre,
     IronOre,
+    CopperOre,
     GoldOre,
+    RedstoneOre,
+    EmeraldOre,
+    LapisOre,
     DiamondOre,
     DeepslateCoalOre,
     DeepslateIronOre,
+    DeepslateCopperOre,
     DeepslateGoldOre,
+    DeepslateRedstoneOre,
+    DeepslateEmeraldOre,
+    DeepslateLapisOre,
     DeepslateDiamondOre,
+    CoalBlock,
+    IronBlock,
+    CopperBlock,
+    GoldBlock,
+    RedstoneBlock,
+    EmeraldBlock,
+    LapisBlock,
+    DiamondBlock,
+    Obsidian,
+    CryingObsidian,
     Water,
+    Lava,
+    SnowBlock,
+    Ice,
+    PackedIce,
+    Prismarine,
+    PrismarineBricks,
+    DarkPrismarine,
+    SeaLantern,
+    CraftingTableTop,
+    CraftingTableSide,
+    FurnaceFront,
+    ChestFront,
+    Bookshelf,
+    Netherrack,
+    NetherBricks,
+    RedNetherBricks,
+    SoulSand,
+    SoulSoil,
+    Glowstone,
+    Basalt,
+    BasaltTop,
+    PolishedBasalt,
+    PolishedBasaltTop,
+    SmoothBasalt,
+    Blackstone,
+    PolishedBlackstone,
+    PolishedBlackstoneBricks,
+    GildedBlackstone,
+    NetherQuartzOre,
+    NetherGoldOre,
+    QuartzBlock,
+    QuartzPillar,
+    QuartzPillarTop,
+    MagmaBlock,
+    CrimsonNylium,
+    WarpedNylium,
+    NetherWartBlock,
+    WarpedWartBlock,
+    Shroomlight,
+    AncientDebris,
+    AncientDebrisTop,
+    EndStone,
+    EndStoneBricks,
+    PurpurBlock,
+    PurpurPillar,
+    PurpurPillarTop,
+    Planks(Wood),
+    /// Bark, or the outside of a nether stem.
+    Log(Wood),
+    /// Cut end of a log or stem.
+    LogTop(Wood),
+    Leaves(Wood),
+    Wool(DyeColor),
+    Concrete(DyeColor),
+    DyedTerracotta(DyeColor),
+    StainedGlass(DyeColor),
+    /// Cracks drawn over a block being mined, from 0 (barely started) to `DESTROY_STAGES - 1`.
+    DestroyStage(u8),
 }
 
+pub const DESTROY_STAGES: u8 = 10;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum BlockFace {
     Top,
@@ -39,6 +331,27 @@ pub enum BlockFace {
     West,
 }
 
+impl BlockFace {
+    pub fn from_facing(facing: Facing) -> Self {
+        match facing {
+            Facing::North => BlockFace::North,
+            Facing::South => BlockFace::South,
+            Facing::East => BlockFace::East,
+            Facing::West => BlockFace::West,
+            Facing::Up => BlockFace::Top,
+            Facing::Down => BlockFace::Bottom,
+        }
+    }
+
+    pub fn axis(self) -> Axis {
+        match self {
+            BlockFace::East | BlockFace::West => Axis::X,
+            BlockFace::Top | BlockFace::Bottom => Axis::Y,
+            BlockFace::North | BlockFace::South => Axis::Z,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct AtlasUV {
     pub u_min: f32,
@@ -71,33 +384,174 @@ pub struct AtlasBuildOutput {
     pub resolver: Arc<TextureResolver>,
 }
 
-const TEXTURE_SEQUENCE: &[TextureKey] = &[
-    TextureKey::GrassTop,
-    TextureKey::GrassSide,
-    TextureKey::Dirt,
-    TextureKey::Stone,
-    TextureKey::Deepslate,
-    TextureKey::Bedrock,
-    TextureKey::Sand,
-    TextureKey::Cobblestone,
-    TextureKey::OakPlanks,
-    TextureKey::OakLog,
-    TextureKey::OakLeaves,
-    TextureKey::Glass,
-    TextureKey::CoalOre,
-    TextureKey::IronOre,
-    TextureKey::GoldOre,
-    TextureKey::DiamondOre,
-    TextureKey::DeepslateCoalOre,
-    TextureKey::DeepslateIronOre,
-    TextureKey::DeepslateGoldOre,
-    TextureKey::DeepslateDiamondOre,
-    TextureKey::Water,
+/// Single textures with the names block definitions refer to them by. The
+/// per-wood and per-colour families are added by `texture_sequence`.
+const TEXTURE_SEQUENCE: &[(TextureKey, &str)] = &[
+    (TextureKey::GrassTop, "grass_top"),
+    (TextureKey::GrassSide, "grass_side"),
+    (TextureKey::Dirt, "dirt"),
+    (TextureKey::CoarseDirt, "coarse_dirt"),
+    (TextureKey::PodzolTop, "podzol_top"),
+    (TextureKey::MyceliumTop, "mycelium_top"),
+    (TextureKey::RootedDirt, "rooted_dirt"),
+    (TextureKey::Mud, "mud"),
+    (TextureKey::PackedMud, "packed_mud"),
+    (TextureKey::MudBricks, "mud_bricks"),
+    (TextureKey::Clay, "clay"),
+    (TextureKey::Gravel, "gravel"),
+    (TextureKey::Sand, "sand"),
+    (TextureKey::RedSand, "red_sand"),
+    (TextureKey::Stone, "stone"),
+    (TextureKey::SmoothStone, "smooth_stone"),
+    (TextureKey::Granite, "granite"),
+    (TextureKey::PolishedGranite, "polished_granite"),
+    (TextureKey::Diorite, "diorite"),
+    (TextureKey::PolishedDiorite, "polished_diorite"),
+    (TextureKey::Andesite, "andesite"),
+    (TextureKey::PolishedAndesite, "polished_andesite"),
+    (TextureKey::Calcite, "calcite"),
+    (TextureKey::Tuff, "tuff"),
+    (TextureKey::DripstoneBlock, "dripstone_block"),
+    (TextureKey::Deepslate, "deepslate"),
+    (TextureKey::CobbledDeepslate, "cobbled_deepslate"),
+    (TextureKey::PolishedDeepslate, "polished_deepslate"),
+    (TextureKey::DeepslateBricks, "deepslate_bricks"),
+    (TextureKey::DeepslateTiles, "deepslate_tiles"),
+    (TextureKey::Bedrock, "bedrock"),
+    (TextureKey::Cobblestone, "cobblestone"),
+    (TextureKey::MossyCobblestone, "mossy_cobblestone"),
+    (TextureKey::StoneBricks, "stone_bricks"),
+    (TextureKey::MossyStoneBricks, "mossy_stone_bricks"),
+    (TextureKey::CrackedStoneBricks, "cracked_stone_bricks"),
+    (TextureKey::ChiseledStoneBricks, "chiseled_stone_bricks"),
+    (TextureKey::Sandstone, "sandstone"),
+    (TextureKey::SandstoneTop, "sandstone_top"),
+    (TextureKey::CutSandstone, "cut_sandstone"),
+    (TextureKey::ChiseledSandstone, "chiseled_sandstone"),
+    (TextureKey::RedSandstone, "red_sandstone"),
+    (TextureKey::RedSandstoneTop, "red_sandstone_top"),
+    (TextureKey::CutRedSandstone, "cut_red_sandstone"),
+    (TextureKey::ChiseledRedSandstone, "chiseled_red_sandstone"),
+    (TextureKey::Bricks, "bricks"),
+    (TextureKey::Terracotta, "terracotta"),
+    (TextureKey::Glass, "glass"),
+    (TextureKey::CoalOre, "coal_ore"),
+    (TextureKey::IronOre, "iron_ore"),
+    (TextureKey::CopperOre, "copper_ore"),
+    (TextureKey::GoldOre, "gold_ore"),
+    (TextureKey::RedstoneOre, "redstone_ore"),
+    (TextureKey::EmeraldOre, "emerald_ore"),
+    (TextureKey::LapisOre, "lapis_ore"),
+    (TextureKey::DiamondOre, "diamond_ore"),
+    (TextureKey::DeepslateCoalOre, "deepslate_coal_ore"),
+    (TextureKey::DeepslateIronOre, "deepslate_iron_ore"),
+    (TextureKey::DeepslateCopperOre, "deepslate_copper_ore"),
+    (TextureKey::DeepslateGoldOre, "deepslate_gold_ore"),
+    (TextureKey::DeepslateRedstoneOre, "deepslate_redstone_ore"),
+    (TextureKey::DeepslateEmeraldOre, "deepslate_emerald_ore"),
+    (TextureKey::DeepslateLapisOre, "deepslate_lapis_ore"),
+    (TextureKey::DeepslateDiamondOre, "deepslate_diamond_ore"),
+    (TextureKey::CoalBlock, "coal_block"),
+    (TextureKey::IronBlock, "iron_block"),
+    (TextureKey::CopperBlock, "copper_block"),
+    (TextureKey::GoldBlock, "gold_block"),
+    (TextureKey::RedstoneBlock, "redstone_block"),
+    (TextureKey::EmeraldBlock, "emerald_block"),
+    (TextureKey::LapisBlock, "lapis_block"),
+    (TextureKey::DiamondBlock, "diamond_block"),
+    (TextureKey::Obsidian, "obsidian"),
+    (TextureKey::CryingObsidian, "crying_obsidian"),
+    (TextureKey::Water, "water"),
+    (TextureKey::Lava, "lava"),
+    (TextureKey::SnowBlock, "snow_block"),
+    (TextureKey::Ice, "ice"),
+    (TextureKey::PackedIce, "packed_ice"),
+    (TextureKey::Prismarine, "prismarine"),
+    (TextureKey::PrismarineBricks, "prismarine_bricks"),
+    (TextureKey::DarkPrismarine, "dark_prismarine"),
+    (TextureKey::SeaLantern, "sea_lantern"),
+    (TextureKey::CraftingTableTop, "crafting_table_top"),
+    (TextureKey::CraftingTableSide, "crafting_table_side"),
+    (TextureKey::FurnaceFront, "furnace_front"),
+    (TextureKey::ChestFront, "chest_front"),
+    (TextureKey::Bookshelf, "bookshelf"),
+    (TextureKey::Netherrack, "netherrack"),
+    (TextureKey::NetherBricks, "nether_bricks"),
+    (TextureKey::RedNetherBricks, "red_nether_bricks"),
+    (TextureKey::SoulSand, "soul_sand"),
+    (TextureKey::SoulSoil, "soul_soil"),
+    (TextureKey::Glowstone, "glowstone"),
+    (TextureKey::Basalt, "basalt"),
+    (TextureKey::BasaltTop, "basalt_top"),
+    (TextureKey::PolishedBasalt, "polished_basalt"),
+    (TextureKey::PolishedBasaltTop, "polished_basalt_top"),
+    (TextureKey::SmoothBasalt, "smooth_basalt"),
+    (TextureKey::Blackstone, "blackstone"),
+    (TextureKey::PolishedBlackstone, "polished_blackstone"),
+    (TextureKey::PolishedBlackstoneBricks, "polished_blackstone_bricks"),
+    (TextureKey::GildedBlackstone, "gilded_blackstone"),
+    (TextureKey::NetherQuartzOre, "nether_quartz_ore"),
+    (TextureKey::NetherGoldOre, "nether_gold_ore"),
+    (TextureKey::QuartzBlock, "quartz_block"),
+    (TextureKey::QuartzPillar, "quartz_pillar"),
+    (TextureKey::QuartzPillarTop, "quartz_pillar_top"),
+    (TextureKey::MagmaBlock, "magma_block"),
+    (TextureKey::CrimsonNylium, "crimson_nylium"),
+    (TextureKey::WarpedNylium, "warped_nylium"),
+    (TextureKey::NetherWartBlock, "nether_wart_block"),
+    (TextureKey::WarpedWartBlock, "warped_wart_block"),
+    (TextureKey::Shroomlight, "shroomlight"),
+    (TextureKey::AncientDebris, "ancient_debris"),
+    (TextureKey::AncientDebrisTop, "ancient_debris_top"),
+    (TextureKey::EndStone, "end_stone"),
+    (TextureKey::EndStoneBricks, "end_stone_bricks"),
+    (TextureKey::PurpurBlock, "purpur_block"),
+    (TextureKey::PurpurPillar, "purpur_pillar"),
+    (TextureKey::PurpurPillarTop, "purpur_pillar_top"),
 ];
 
+/// Every atlas tile in order, with its name.
+fn texture_sequence() -> Vec<(TextureKey, String)> {
+    let mut sequence: Vec<(TextureKey, String)> = TEXTURE_SEQUENCE
+        .iter()
+        .map(|(key, name)| (*key, name.to_string()))
+        .collect();
+    for wood in Wood::ALL {
+        let log = if wood.is_fungus() { "stem" } else { "log" };
+        sequence.push((TextureKey::Planks(wood), format!("{}_planks", wood.name())));
+        sequence.push((TextureKey::Log(wood), format!("{}_{}", wood.name(), log)));
+        sequence.push((TextureKey::LogTop(wood), format!("{}_{}_top", wood.name(), log)));
+        if !wood.is_fungus() {
+            sequence.push((TextureKey::Leaves(wood), format!("{}_leaves", wood.name())));
+        }
+    }
+    for color in DyeColor::ALL {
+        sequence.push((TextureKey::Wool(color), format!("{}_wool", color.name())));
+        sequence.push((TextureKey::Concrete(color), format!("{}_concrete", color.name())));
+        sequence.push((TextureKey::DyedTerracotta(color), format!("{}_terracotta", color.name())));
+        sequence.push((TextureKey::StainedGlass(color), format!("{}_stained_glass", color.name())));
+    }
+    for stage in 0..DESTROY_STAGES {
+        sequence.push((TextureKey::DestroyStage(stage), format!("destroy_stage_{}", stage)));
+    }
+    sequence
+}
+
+fn texture_names() -> &'static HashMap<String, TextureKey> {
+    static NAMES: OnceLock<HashMap<String, TextureKey>> = OnceLock::new();
+    NAMES.get_or_init(|| {
+        texture_sequence()
+            .into_iter()
+            .map(|(key, name)| (name, key))
+            .collect()
+    })
+}
+
 pub fn build_atlas(tile_size: u32) -> AtlasBuildOutput {
-    let count = TEXTURE_SEQUENCE.len() as u32;
-    let columns = (count.min(6)).max(1);
+    let sequence = texture_sequence();
+    let count = sequence.len() as u32;
+    // Roughly square, so the atlas stays within texture size limits at high tile sizes.
+    let columns = ((count as f32).sqrt().ceil() as u32).max(1);
     let rows = (count + columns - 1) / columns;
     let width = columns * tile_size;
     let height = rows * tile_size;
@@ -105,7 +559,7 @@ pub fn build_atlas(tile_size: u32) -> AtlasBuildOutput {
     let mut atlas: RgbaImage = ImageBuffer::new(width, height);
     let mut uv_map = HashMap::new();
 
-    for (index, key) in TEXTURE_SEQUENCE.iter().enumerate() {
+    for (index, (key, _)) in sequence.iter().enumerate() {
         let tile = generate_tile(*key, tile_size);
         let idx = index as u32;
         let x = idx % columns;
@@ -126,6 +580,16 @@ pub fn build_atlas(tile_size: u32) -> AtlasBuildOutput {
         });
     }
 
+    for (block, definition) in BlockRegistry::global().iter() {
+        let textures = &definition.textures;
+        let names = [&textures.all, &textures.top, &textures.bottom, &textures.side, &textures.end, &textures.front];
+        for name in names.into_iter().flatten() {
+            if TextureKey::from_name(name).is_none() {
+                log::warn!("{} ({:?}) uses unknown texture {:?}", definition.name, block, name);
+            }
+        }
+    }
+
     let fallback = *uv_map
         .get(&TextureKey::Stone)
         .unwrap_or(uv_map.values().next().unwrap());
@@ -144,42 +608,36 @@ pub fn build_atlas(tile_size: u32) -> AtlasBuildOutput {
     }
 }
 
-pub fn texture_key_for(block: BlockType, face: BlockFace) -> TextureKey {
-    match block {
-        BlockType::GrassBlock => match face {
-            BlockFace::Top => TextureKey::GrassTop,
-            BlockFace::Bottom => TextureKey::Dirt,
-            _ => TextureKey::GrassSide,
-        },
-        BlockType::Dirt | BlockType::CoarseDirt | BlockType::RootedDirt | BlockType::Podzol =>
-            TextureKey::Dirt,
-        BlockType::Stone | BlockType::Granite | BlockType::Diorite | BlockType::Andesite =>
-            TextureKey::Stone,
-        BlockType::Deepslate | BlockType::Calcite | BlockType::Tuff => TextureKey::Deepslate,
-        BlockType::Bedrock => TextureKey::Bedrock,
-        BlockType::Sand | BlockType::RedSand => TextureKey::Sand,
-        BlockType::Cobblestone | BlockType::MossyCobblestone | BlockType::StoneBricks
-        | BlockType::SmoothStone | BlockType::Sandstone | BlockType::RedSandstone
-        | BlockType::Bricks => TextureKey::Cobblestone,
-        BlockType::OakPlanks | BlockType::SprucePlanks | BlockType::BirchPlanks =>
-            TextureKey::OakPlanks,
-        BlockType::OakLog | BlockType::SpruceLog | BlockType::BirchLog | BlockType::JungleLog
-        | BlockType::AcaciaLog | BlockType::DarkOakLog => TextureKey::OakLog,
-        BlockType::OakLeaves => TextureKey::OakLeaves,
-        BlockType::Glass | BlockType::WhiteStainedGlass => TextureKey::Glass,
-        BlockType::Water => TextureKey::Water,
-        BlockType::CoalOre => TextureKey::CoalOre,
-        BlockType::IronOre => TextureKey::IronOre,
-        BlockType::GoldOre => TextureKey::GoldOre,
-        BlockType::DiamondOre => TextureKey::DiamondOre,
-        BlockType::DeepslateCoalOre => TextureKey::DeepslateCoalOre,
-        BlockType::DeepslateIronOre => TextureKey::DeepslateIronOre,
-        BlockType::DeepslateGoldOre => TextureKey::DeepslateGoldOre,
-        BlockType::DeepslateDiamondOre => TextureKey::DeepslateDiamondOre,
-        _ => TextureKey::Stone,
+impl TextureKey {
+    pub fn from_name(name: &str) -> Option<Self> {
+        texture_names().get(name).copied()
     }
 }
 
+/// The atlas tile for one face of a block, from the block's definition. Unknown
+/// or missing textures fall back to stone.
+pub fn texture_key_for(state: BlockState, face: BlockFace) -> TextureKey {
+    let textures = &BlockRegistry::global().get(state.block).textures;
+    let name = if state.has(Property::Axis) {
+        // Rings on the cut ends, bark along the length.
+        if face.axis() == state.axis() {
+            textures.end()
+        } else {
+            textures.side()
+        }
+    } else if state.has(Property::HorizontalFacing) && face == BlockFace::from_facing(state.facing()) {
+        textures.front()
+    } else {
+        match face {
+            BlockFace::Top => textures.top(),
+            BlockFace::Bottom => textures.bottom(),
+            _ => textures.side(),
+        }
+    };
+    name.and_then(TextureKey::from_name)
+        .unwrap_or(TextureKey::Stone)
+}
+
 fn blit_tile(atlas: &mut RgbaImage, tile: &RgbaImage, offset_x: u32, offset_y: u32) {
     for y in 0..tile.height() {
         for x in 0..tile.width() {
@@ -190,28 +648,147 @@ fn blit_tile(atlas: &mut RgbaImage, tile: &RgbaImage, offset_x: u32, offset_y: u
 }
 
 fn generate_tile(key: TextureKey, tile_size: u32) -> RgbaImage {
+    let deepslate = [70, 70, 78];
+    let netherrack = [97, 38, 38];
     match key {
         TextureKey::GrassTop => generate_grass_top(tile_size),
         TextureKey::GrassSide => generate_grass_side(tile_size),
         TextureKey::Dirt => generate_noise_tile(tile_size, [110, 78, 48], 18, 1),
+        TextureKey::CoarseDirt => generate_speckled(tile_size, [119, 85, 59], [85, 62, 44], 110),
+        TextureKey::PodzolTop => generate_noise_tile(tile_size, [90, 63, 30], 24, 111),
+        TextureKey::MyceliumTop => generate_speckled(tile_size, [111, 99, 105], [150, 128, 150], 112),
+        TextureKey::RootedDirt => generate_speckled(tile_size, [144, 103, 76], [175, 135, 95], 113),
+        TextureKey::Mud => generate_noise_tile(tile_size, [60, 57, 60], 10, 114),
+        TextureKey::PackedMud => generate_noise_tile(tile_size, [142, 107, 80], 12, 115),
+        TextureKey::MudBricks => generate_bricks(tile_size, [137, 103, 79], [112, 85, 66], 116),
+        TextureKey::Clay => generate_noise_tile(tile_size, [160, 166, 179], 8, 117),
+        TextureKey::Gravel => generate_speckled(tile_size, [131, 127, 126], [95, 90, 90], 118),
+        TextureKey::Sand => generate_noise_tile(tile_size, [220, 214, 170], 10, 5),
+        TextureKey::RedSand => generate_noise_tile(tile_size, [190, 102, 33], 12, 119),
         TextureKey::Stone => generate_noise_tile(tile_size, [110, 110, 110], 12, 2),
-        TextureKey::Deepslate => generate_noise_tile(tile_size, [70, 70, 78], 10, 3),
+        TextureKey::SmoothStone => generate_polished(tile_size, [158, 158, 158], 120),
+        TextureKey::Granite => generate_speckled(tile_size, [149, 103, 85], [175, 125, 105], 121),
+        TextureKey::PolishedGranite => generate_polished(tile_size, [154, 106, 89], 122),
+        TextureKey::Diorite => generate_speckled(tile_size, [188, 188, 188], [125, 125, 125], 123),
+        TextureKey::PolishedDiorite => generate_polished(tile_size, [192, 193, 194], 124),
+        TextureKey::Andesite => generate_speckled(tile_size, [136, 136, 137], [108, 108, 110], 125),
+        TextureKey::PolishedAndesite => generate_polished(tile_size, [132, 134, 133], 126),
+        TextureKey::Calcite => generate_speckled(tile_size, [223, 224, 220], [198, 198, 196], 127),
+        TextureKey::Tuff => generate_layered(tile_size, [108, 109, 102], 128),
+        TextureKey::DripstoneBlock => generate_layered(tile_size, [134, 107, 92], 129),
+        TextureKey::Deepslate => generate_noise_tile(tile_size, deepslate, 10, 3),
+        TextureKey::CobbledDeepslate => generate_cobblestone(tile_size, [77, 77, 80], [50, 50, 55], 130),
+        TextureKey::PolishedDeepslate => generate_polished(tile_size, [72, 72, 73], 131),
+        TextureKey::DeepslateBricks => generate_bricks(tile_size, [70, 70, 71], [45, 45, 48], 132),
+        TextureKey::DeepslateTiles => generate_tiles(tile_size, [54, 54, 55], [35, 35, 36], 133),
         TextureKey::Bedrock => generate_noise_tile(tile_size, [32, 32, 32], 6, 4),
-        TextureKey::Sand => generate_noise_tile(tile_size, [220, 214, 170], 10, 5),
-        TextureKey::Cobblestone => generate_cobblestone(tile_size),
-        TextureKey::OakPlanks => generate_planks(tile_size),
-        TextureKey::OakLog => generate_log(tile_size),
-        TextureKey::OakLeaves => generate_leaves(tile_size),
+        TextureKey::Cobblestone => generate_cobblestone(tile_size, [110, 110, 115], [90, 90, 95], 41),
+        TextureKey::MossyCobblestone => {
+            generate_mossy(generate_cobblestone(tile_size, [110, 110, 115], [90, 90, 95], 41), 134)
+        }
+        TextureKey::StoneBricks => generate_bricks(tile_size, [122, 121, 122], [90, 90, 92], 135),
+        TextureKey::MossyStoneBricks => {
+            generate_mossy(generate_bricks(tile_size, [122, 121, 122], [90, 90, 92], 135), 136)
+        }
+        TextureKey::CrackedStoneBricks => {
+            generate_cracked(generate_bricks(tile_size, [122, 121, 122], [90, 90, 92], 135), 137)
+        }
+        TextureKey::ChiseledStoneBricks => generate_chiseled(tile_size, [119, 118, 119], 138),
+        TextureKey::Sandstone => generate_layered(tile_size, [216, 203, 155], 139),
+        TextureKey::SandstoneTop => generate_noise_tile(tile_size, [223, 214, 170], 8, 140),
+        TextureKey::CutSandstone => generate_polished(tile_size, [217, 206, 159], 141),
+        TextureKey::ChiseledSandstone => generate_chiseled(tile_size, [216, 203, 155], 142),
+        TextureKey::RedSandstone => generate_layered(tile_size, [181, 97, 31], 143),
+        TextureKey::RedSandstoneTop => generate_noise_tile(tile_size, [186, 100, 32], 8, 144),
+        TextureKey::CutRedSandstone => generate_polished(tile_size, [189, 101, 32], 145),
+        TextureKey::ChiseledRedSandstone => generate_chiseled(tile_size, [183, 96, 27], 146),
+        TextureKey::Bricks => generate_bricks(tile_size, [150, 97, 83], [175, 165, 155], 147),
+        TextureKey::Terracotta => generate_terracotta(tile_size, [152, 94, 67], 148),
         TextureKey::Glass => generate_glass(tile_size),
         TextureKey::CoalOre => generate_ore(tile_size, [60, 60, 60], [30, 30, 30], 10),
         TextureKey::IronOre => generate_ore(tile_size, [190, 140, 110], [110, 110, 110], 11),
+        TextureKey::CopperOre => generate_ore(tile_size, [200, 110, 80], [110, 110, 110], 18),
         TextureKey::GoldOre => generate_ore(tile_size, [223, 195, 51], [110, 110, 110], 12),
+        TextureKey::RedstoneOre => generate_ore(tile_size, [200, 20, 20], [110, 110, 110], 19),
+        TextureKey::EmeraldOre => generate_ore(tile_size, [40, 200, 90], [110, 110, 110], 20),
+        TextureKey::LapisOre => generate_ore(tile_size, [30, 70, 170], [110, 110, 110], 21),
         TextureKey::DiamondOre => generate_ore(tile_size, [80, 220, 225], [110, 110, 110], 13),
-        TextureKey::DeepslateCoalOre => generate_ore(tile_size, [60, 60, 60], [70, 70, 78], 14),
-        TextureKey::DeepslateIronOre => generate_ore(tile_size, [190, 140, 110], [70, 70, 78], 15),
-        TextureKey::DeepslateGoldOre => generate_ore(tile_size, [223, 195, 51], [70, 70, 78], 16),
-        TextureKey::DeepslateDiamondOre => generate_ore(tile_size, [80, 220, 225], [70, 70, 78], 17),
+        TextureKey::DeepslateCoalOre => generate_ore(tile_size, [60, 60, 60], deepslate, 14),
+        TextureKey::DeepslateIronOre => generate_ore(tile_size, [190, 140, 110], deepslate, 15),
+        TextureKey::DeepslateCopperOre => generate_ore(tile_size, [200, 110, 80], deepslate, 22),
+        TextureKey::DeepslateGoldOre => generate_ore(tile_size, [223, 195, 51], deepslate, 16),
+        TextureKey::DeepslateRedstoneOre => generate_ore(tile_size, [200, 20, 20], deepslate, 23),
+        TextureKey::DeepslateEmeraldOre => generate_ore(tile_size, [40, 200, 90], deepslate, 24),
+        TextureKey::DeepslateLapisOre => generate_ore(tile_size, [30, 70, 170], deepslate, 25),
+        TextureKey::DeepslateDiamondOre => generate_ore(tile_size, [80, 220, 225], deepslate, 17),
+        TextureKey::CoalBlock => generate_metal_block(tile_size, [20, 20, 22], 150),
+        TextureKey::IronBlock => generate_metal_block(tile_size, [220, 220, 220], 151),
+        TextureKey::CopperBlock => generate_metal_block(tile_size, [192, 107, 79], 152),
+        TextureKey::GoldBlock => generate_metal_block(tile_size, [246, 208, 61], 153),
+        TextureKey::RedstoneBlock => generate_metal_block(tile_size, [175, 24, 5], 154),
+        TextureKey::EmeraldBlock => generate_metal_block(tile_size, [42, 203, 87], 155),
+        TextureKey::LapisBlock => generate_metal_block(tile_size, [30, 67, 140], 156),
+        TextureKey::DiamondBlock => generate_metal_block(tile_size, [98, 237, 228], 157),
+        TextureKey::Obsidian => generate_noise_tile(tile_size, [20, 18, 30], 8, 158),
+        TextureKey::CryingObsidian => generate_speckled(tile_size, [32, 10, 60], [130, 40, 220], 159),
         TextureKey::Water => generate_water(tile_size),
+        TextureKey::Lava => generate_noise_tile(tile_size, [207, 92, 20], 40, 160),
+        TextureKey::SnowBlock => generate_noise_tile(tile_size, [249, 254, 254], 6, 161),
+        TextureKey::Ice => generate_ice(tile_size, [145, 183, 253], 200, 162),
+        TextureKey::PackedIce => generate_ice(tile_size, [141, 180, 250], 255, 163),
+        TextureKey::Prismarine => generate_speckled(tile_size, [99, 156, 151], [75, 120, 125], 164),
+        TextureKey::PrismarineBricks => generate_bricks(tile_size, [99, 171, 158], [70, 125, 115], 165),
+        TextureKey::DarkPrismarine => generate_tiles(tile_size, [51, 91, 75], [35, 65, 55], 166),
+        TextureKey::SeaLantern => generate_speckled(tile_size, [172, 199, 190], [225, 235, 230], 167),
+        TextureKey::CraftingTableTop => generate_crafting_table_top(tile_size),
+        TextureKey::CraftingTableSide => generate_crafting_table_side(tile_size),
+        TextureKey::FurnaceFront => generate_furnace_front(tile_size),
+        TextureKey::ChestFront => generate_chest_front(tile_size),
+        TextureKey::Bookshelf => generate_bookshelf(tile_size),
+        TextureKey::Netherrack => generate_noise_tile(tile_size, netherrack, 20, 170),
+        TextureKey::NetherBricks => generate_bricks(tile_size, [44, 21, 26], [25, 12, 15], 171),
+        TextureKey::RedNetherBricks => generate_bricks(tile_size, [69, 7, 9], [40, 4, 5], 172),
+        TextureKey::SoulSand => generate_speckled(tile_size, [81, 62, 50], [58, 44, 35], 173),
+        TextureKey::SoulSoil => generate_noise_tile(tile_size, [75, 57, 46], 14, 174),
+        TextureKey::Glowstone => generate_speckled(tile_size, [171, 131, 84], [250, 220, 140], 175),
+        TextureKey::Basalt => generate_pillar(tile_size, [80, 81, 86], 176),
+        TextureKey::BasaltTop => generate_rings(tile_size, [81, 81, 86], 177),
+        TextureKey::PolishedBasalt => generate_pillar(tile_size, [99, 98, 100], 178),
+        TextureKey::PolishedBasaltTop => generate_rings(tile_size, [88, 88, 91], 179),
+        TextureKey::SmoothBasalt => generate_noise_tile(tile_size, [72, 72, 78], 8, 180),
+        TextureKey::Blackstone => generate_noise_tile(tile_size, [42, 35, 41], 12, 181),
+        TextureKey::PolishedBlackstone => generate_polished(tile_size, [53, 48, 56], 182),
+        TextureKey::PolishedBlackstoneBricks => generate_bricks(tile_size, [48, 42, 49], [30, 26, 32], 183),
+        TextureKey::GildedBlackstone => generate_speckled(tile_size, [55, 42, 38], [220, 170, 50], 184),
+        TextureKey::NetherQuartzOre => generate_ore(tile_size, [230, 225, 215], netherrack, 26),
+        TextureKey::NetherGoldOre => generate_ore(tile_size, [240, 200, 60], netherrack, 27),
+        TextureKey::QuartzBlock => generate_polished(tile_size, [235, 229, 222], 185),
+        TextureKey::QuartzPillar => generate_pillar(tile_size, [235, 230, 224], 186),
+        TextureKey::QuartzPillarTop => generate_rings(tile_size, [235, 230, 224], 187),
+        TextureKey::MagmaBlock => generate_speckled(tile_size, [142, 63, 31], [255, 150, 40], 188),
+        TextureKey::CrimsonNylium => generate_speckled(tile_size, [130, 31, 31], [180, 50, 50], 189),
+        TextureKey::WarpedNylium => generate_speckled(tile_size, [43, 114, 101], [20, 150, 130], 190),
+        TextureKey::NetherWartBlock => generate_noise_tile(tile_size, [114, 3, 2], 30, 191),
+        TextureKey::WarpedWartBlock => generate_noise_tile(tile_size, [22, 119, 121], 30, 192),
+        TextureKey::Shroomlight => generate_speckled(tile_size, [240, 146, 70], [255, 210, 120], 193),
+        TextureKey::AncientDebris => generate_layered(tile_size, [96, 64, 56], 194),
+        TextureKey::AncientDebrisTop => generate_rings(tile_size, [94, 66, 58], 195),
+        TextureKey::EndStone => generate_speckled(tile_size, [219, 222, 158], [190, 190, 130], 196),
+        TextureKey::EndStoneBricks => generate_bricks(tile_size, [218, 224, 162], [180, 180, 130], 197),
+        TextureKey::PurpurBlock => generate_tiles(tile_size, [169, 125, 169], [140, 100, 140], 198),
+        TextureKey::PurpurPillar => generate_pillar(tile_size, [171, 129, 171], 199),
+        TextureKey::PurpurPillarTop => generate_rings(tile_size, [171, 129, 171], 200),
+        TextureKey::Planks(wood) => generate_planks(tile_size, wood.planks_color()),
+        TextureKey::Log(wood) => generate_log_side(tile_size, wood.bark_color()),
+        TextureKey::LogTop(wood) => generate_log(tile_size, wood.planks_color(), wood.bark_color()),
+        TextureKey::Leaves(wood) => generate_leaves(tile_size, wood.leaves_color()),
+        TextureKey::Wool(color) => generate_wool(tile_size, color.rgb()),
+        TextureKey::Concrete(color) => generate_noise_tile(tile_size, color.rgb(), 4, 210),
+        TextureKey::DyedTerracotta(color) => {
+            generate_terracotta(tile_size, blend([152, 94, 67], color.rgb(), 0.6), 211)
+        }
+        TextureKey::StainedGlass(color) => generate_stained_glass(tile_size, color.rgb()),
+        TextureKey::DestroyStage(stage) => generate_destroy_stage(tile_size, stage),
     }
 }
 
@@ -293,17 +870,15 @@ fn generate_noise_color(base: [u8; 3], variation: u8, x: u32, y: u32, seed: u32)
     ]
 }
 
-fn generate_cobblestone(tile_size: u32) -> RgbaImage {
+fn generate_cobblestone(tile_size: u32, base: [u8; 3], mortar_color: [u8; 3], seed: u32) -> RgbaImage {
     let mut img = ImageBuffer::new(tile_size, tile_size);
-    let mortar_color = [90, 90, 95];
     for y in 0..tile_size {
         for x in 0..tile_size {
-            let noise = jitter(x, y, 41) as i32 - 128;
-            let base = [110i32, 110, 115];
+            let noise = jitter(x, y, seed) as i32 - 128;
             let mut color = [
-                (base[0] + noise / 12).clamp(60, 180) as u8,
-                (base[1] + noise / 16).clamp(60, 180) as u8,
-                (base[2] + noise / 12).clamp(60, 180) as u8,
+                (base[0] as i32 + noise / 12).clamp(0, 255) as u8,
+                (base[1] as i32 + noise / 16).clamp(0, 255) as u8,
+                (base[2] as i32 + noise / 12).clamp(0, 255) as u8,
             ];
             if x % (tile_size / 4).max(2) == 0 || y % (tile_size / 4).max(2) == 0 {
                 color = mortar_color;
@@ -314,19 +889,19 @@ fn generate_cobblestone(tile_size: u32) -> RgbaImage {
     img
 }
 
-fn generate_planks(tile_size: u32) -> RgbaImage {
+fn generate_planks(tile_size: u32, base: [u8; 3]) -> RgbaImage {
     let mut img = ImageBuffer::new(tile_size, tile_size);
+    let seam = scale(base, 0.75);
     for y in 0..tile_size {
         for x in 0..tile_size {
             let noise = jitter(x, y, 51) as i32 - 128;
-            let base = [160i32, 120, 70];
             let mut color = [
-                (base[0] + noise / 16).clamp(60, 220) as u8,
-                (base[1] + noise / 18).clamp(60, 200) as u8,
-                (base[2] + noise / 20).clamp(40, 180) as u8,
+                (base[0] as i32 + noise / 16).clamp(0, 255) as u8,
+                (base[1] as i32 + noise / 18).clamp(0, 255) as u8,
+                (base[2] as i32 + noise / 20).clamp(0, 255) as u8,
             ];
             if y % (tile_size / 4).max(2) == 0 {
-                color = [120, 90, 60];
+                color = seam;
             }
             img.put_pixel(x, y, Rgba([color[0], color[1], color[2], 255]));
         }
@@ -334,7 +909,8 @@ fn generate_planks(tile_size: u32) -> RgbaImage {
     img
 }
 
-fn generate_log(tile_size: u32) -> RgbaImage {
+/// The cut end of a log: heartwood in the middle, a ring of bark around it.
+fn generate_log(tile_size: u32, inner: [u8; 3], bark: [u8; 3]) -> RgbaImage {
     let mut img = ImageBuffer::new(tile_size, tile_size);
     let radius = tile_size as f32 / 2.0;
     let center = radius - 0.5;
@@ -344,15 +920,15 @@ fn generate_log(tile_size: u32) -> RgbaImage {
             let dy = y as f32 - center;
             let dist = (dx * dx + dy * dy).sqrt();
             let base = if dist < radius * 0.8 {
-                [160, 110, 70]
+                inner
             } else {
-                [90, 60, 40]
+                scale(bark, 0.9)
             };
             let noise = jitter(x, y, 61) as i32 - 128;
             let color = [
-                (base[0] as i32 + noise / 20).clamp(40, 200) as u8,
-                (base[1] as i32 + noise / 20).clamp(40, 180) as u8,
-                (base[2] as i32 + noise / 25).clamp(20, 160) as u8,
+                (base[0] as i32 + noise / 20).clamp(0, 255) as u8,
+                (base[1] as i32 + noise / 20).clamp(0, 255) as u8,
+                (base[2] as i32 + noise / 25).clamp(0, 255) as u8,
             ];
             img.put_pixel(x, y, Rgba([color[0], color[1], color[2], 255]));
         }
@@ -360,17 +936,66 @@ fn generate_log(tile_size: u32) -> RgbaImage {
     img
 }
 
-fn generate_leaves(tile_size: u32) -> RgbaImage {
+fn generate_log_side(tile_size: u32, bark: [u8; 3]) -> RgbaImage {
+    let mut img = ImageBuffer::new(tile_size, tile_size);
+    let stripe = (tile_size / 8).max(2);
+    for y in 0..tile_size {
+        for x in 0..tile_size {
+            // Vertical bark grooves, broken up a little so they don't look ruled.
+            let groove = (x + jitter(x / stripe, y / stripe, 63) % 2) % stripe == 0;
+            let base = if groove { scale(bark, 0.7) } else { bark };
+            let noise = jitter(x, y, 62) as i32 - 128;
+            let color = [
+                (base[0] as i32 + noise / 20).clamp(0, 255) as u8,
+                (base[1] as i32 + noise / 20).clamp(0, 255) as u8,
+                (base[2] as i32 + noise / 25).clamp(0, 255) as u8,
+            ];
+            img.put_pixel(x, y, Rgba([color[0], color[1], color[2], 255]));
+        }
+    }
+    img
+}
+
+fn generate_furnace_front(tile_size: u32) -> RgbaImage {
+    let mut img = generate_cobblestone(tile_size, [110, 110, 115], [90, 90, 95], 41);
+    let (left, right) = (tile_size / 4, tile_size - tile_size / 4);
+    let (top, bottom) = (tile_size / 2, tile_size - tile_size / 8);
+    for y in top..bottom {
+        for x in left..right {
+            let noise = jitter(x, y, 101) as i32 - 128;
+            let shade = (25 + noise / 16).clamp(10, 40) as u8;
+            img.put_pixel(x, y, Rgba([shade, shade, shade, 255]));
+        }
+    }
+    img
+}
+
+fn generate_chest_front(tile_size: u32) -> RgbaImage {
+    let mut img = generate_planks(tile_size, Wood::Oak.planks_color());
+    let lid = tile_size * 3 / 8;
+    for x in 0..tile_size {
+        img.put_pixel(x, lid, Rgba([70, 50, 30, 255]));
+    }
+    let latch = (tile_size / 8).max(1);
+    let center = tile_size / 2;
+    for y in lid.saturating_sub(latch)..(lid + latch + 1).min(tile_size) {
+        for x in center.saturating_sub(latch / 2 + 1)..(center + latch / 2 + 1).min(tile_size) {
+            img.put_pixel(x, y, Rgba([190, 190, 200, 255]));
+        }
+    }
+    img
+}
+
+fn generate_leaves(tile_size: u32, base: [u8; 3]) -> RgbaImage {
     let mut img = ImageBuffer::new(tile_size, tile_size);
     for y in 0..tile_size {
         for x in 0..tile_size {
             let noise = jitter(x, y, 71) as i32 - 128;
-            let base = [50i32, 120, 50];
             let alpha = (200 + (noise / 8).clamp(-40, 40)) as u8;
             let color = [
-                (base[0] + noise / 15).clamp(10, 140) as u8,
-                (base[1] + noise / 10).clamp(50, 200) as u8,
-                (base[2] + noise / 15).clamp(10, 140) as u8,
+                (base[0] as i32 + noise / 15).clamp(0, 255) as u8,
+                (base[1] as i32 + noise / 10).clamp(0, 255) as u8,
+                (base[2] as i32 + noise / 15).clamp(0, 255) as u8,
             ];
             img.put_pixel(x, y, Rgba([color[0], color[1], color[2], alpha]));
         }
@@ -435,6 +1060,328 @@ fn generate_water(tile_size: u32) -> RgbaImage {
     img
 }
 
+/// Noise with scattered flecks of a second colour, for grainy stone and soil.
+fn generate_speckled(tile_size: u32, base: [u8; 3], fleck: [u8; 3], seed: u32) -> RgbaImage {
+    let mut img = ImageBuffer::new(tile_size, tile_size);
+    for y in 0..tile_size {
+        for x in 0..tile_size {
+            let color = if jitter(x, y, seed + 1) % 255 > 200 {
+                generate_noise_color(fleck, 12, x, y, seed)
+            } else {
+                generate_noise_color(base, 14, x, y, seed)
+            };
+            img.put_pixel(x, y, Rgba([color[0], color[1], color[2], 255]));
+        }
+    }
+    img
+}
+
+/// Running-bond brickwork: four courses, each offset by half a brick.
+fn generate_bricks(tile_size: u32, brick: [u8; 3], mortar: [u8; 3], seed: u32) -> RgbaImage {
+    let mut img = ImageBuffer::new(tile_size, tile_size);
+    let course = (tile_size / 4).max(2);
+    let length = (tile_size / 2).max(2);
+    for y in 0..tile_size {
+        let offset = if (y / course) % 2 == 0 { 0 } else { length / 2 };
+        for x in 0..tile_size {
+            let joint = y % course == course - 1 || (x + offset) % length == length - 1;
+            let color = if joint {
+                mortar
+            } else {
+                generate_noise_color(brick, 12, x, y, seed)
+            };
+            img.put_pixel(x, y, Rgba([color[0], color[1], color[2], 255]));
+        }
+    }
+    img
+}
+
+/// A two by two grid of square tiles.
+fn generate_tiles(tile_size: u32, tile: [u8; 3], grout: [u8; 3], seed: u32) -> RgbaImage {
+    let mut img = ImageBuffer::new(tile_size, tile_size);
+    let size = (tile_size / 2).max(2);
+    for y in 0..tile_size {
+        for x in 0..tile_size {
+            let color = if x % size == size - 1 || y % size == size - 1 {
+                grout
+            } else {
+                generate_noise_color(tile, 10, x, y, seed)
+            };
+            img.put_pixel(x, y, Rgba([color[0], color[1], color[2], 255]));
+        }
+    }
+    img
+}
+
+/// Smooth stone with a darker rim, for polished and cut blocks.
+fn generate_polished(tile_size: u32, base: [u8; 3], seed: u32) -> RgbaImage {
+    let mut img = ImageBuffer::new(tile_size, tile_size);
+    let edge = tile_size - 1;
+    for y in 0..tile_size {
+        for x in 0..tile_size {
+            let color = if x == 0 || y == 0 || x == edge || y == edge {
+                scale(base, 0.8)
+            } else {
+                generate_noise_color(base, 6, x, y, seed)
+            };
+            img.put_pixel(x, y, Rgba([color[0], color[1], color[2], 255]));
+        }
+    }
+    img
+}
+
+/// Horizontal strata of slightly different shades.
+fn generate_layered(tile_size: u32, base: [u8; 3], seed: u32) -> RgbaImage {
+    let mut img = ImageBuffer::new(tile_size, tile_size);
+    let band = (tile_size / 8).max(1);
+    for y in 0..tile_size {
+        let shade = 0.85 + (jitter(0, y / band, seed) % 30) as f32 / 100.0;
+        for x in 0..tile_size {
+            let color = generate_noise_color(scale(base, shade), 8, x, y, seed + 1);
+            img.put_pixel(x, y, Rgba([color[0], color[1], color[2], 255]));
+        }
+    }
+    img
+}
+
+/// The side of a pillar: vertical fluting with darker edges.
+fn generate_pillar(tile_size: u32, base: [u8; 3], seed: u32) -> RgbaImage {
+    let mut img = ImageBuffer::new(tile_size, tile_size);
+    let flute = (tile_size / 4).max(2);
+    for y in 0..tile_size {
+        for x in 0..tile_size {
+            let color = if x == 0 || x == tile_size - 1 {
+                scale(base, 0.75)
+            } else if x % flute == 0 {
+                scale(base, 0.9)
+            } else {
+                generate_noise_color(base, 6, x, y, seed)
+            };
+            img.put_pixel(x, y, Rgba([color[0], color[1], color[2], 255]));
+        }
+    }
+    img
+}
+
+/// The end of a pillar: concentric square rings.
+fn generate_rings(tile_size: u32, base: [u8; 3], seed: u32) -> RgbaImage {
+    let mut img = ImageBuffer::new(tile_size, tile_size);
+    let ring = (tile_size / 8).max(1);
+    for y in 0..tile_size {
+        for x in 0..tile_size {
+            let depth = x.min(y).min(tile_size - 1 - x).min(tile_size - 1 - y);
+            let shade = if (depth / ring) % 2 == 0 { 0.85 } else { 1.0 };
+            let color = generate_noise_color(scale(base, shade), 6, x, y, seed);
+            img.put_pixel(x, y, Rgba([color[0], color[1], color[2], 255]));
+        }
+    }
+    img
+}
+
+/// A polished block with a carved square in the middle.
+fn generate_chiseled(tile_size: u32, base: [u8; 3], seed: u32) -> RgbaImage {
+    let mut img = generate_polished(tile_size, base, seed);
+    let (inner, outer) = (tile_size / 4, tile_size - 1 - tile_size / 4);
+    let groove = scale(base, 0.65);
+    for i in inner..=outer {
+        for (x, y) in [(i, inner), (i, outer), (inner, i), (outer, i)] {
+            img.put_pixel(x, y, Rgba([groove[0], groove[1], groove[2], 255]));
+        }
+    }
+    img
+}
+
+/// Patches of moss over an existing texture.
+fn generate_mossy(mut img: RgbaImage, seed: u32) -> RgbaImage {
+    let patch = (img.width() / 4).max(1);
+    for y in 0..img.height() {
+        for x in 0..img.width() {
+            let clump = jitter(x / patch, y / patch, seed) % 3 == 0;
+            if clump && jitter(x, y, seed + 1) % 4 != 0 {
+                let color = generate_noise_color([75, 105, 45], 20, x, y, seed + 2);
+                img.put_pixel(x, y, Rgba([color[0], color[1], color[2], 255]));
+            }
+        }
+    }
+    img
+}
+
+/// A crack wandering down an existing texture.
+fn generate_cracked(mut img: RgbaImage, seed: u32) -> RgbaImage {
+    let size = img.width();
+    let mut x = size / 3 + jitter(0, 0, seed) % (size / 3).max(1);
+    for y in 0..img.height() {
+        let pixel = *img.get_pixel(x, y);
+        let dark = Rgba([pixel[0] / 2, pixel[1] / 2, pixel[2] / 2, 255]);
+        img.put_pixel(x, y, dark);
+        x = match jitter(x, y, seed + 1) % 3 {
+            0 => x.saturating_sub(1),
+            1 => (x + 1).min(size - 1),
+            _ => x,
+        };
+    }
+    img
+}
+
+/// A solid block of a refined material, lit from the top left.
+fn generate_metal_block(tile_size: u32, base: [u8; 3], seed: u32) -> RgbaImage {
+    let mut img = ImageBuffer::new(tile_size, tile_size);
+    let edge = tile_size - 1;
+    for y in 0..tile_size {
+        for x in 0..tile_size {
+            let color = if x == 0 || y == 0 {
+                scale(base, 1.2)
+            } else if x == edge || y == edge {
+                scale(base, 0.7)
+            } else {
+                generate_noise_color(base, 8, x, y, seed)
+            };
+            img.put_pixel(x, y, Rgba([color[0], color[1], color[2], 255]));
+        }
+    }
+    img
+}
+
+/// Transparent except for cracks spreading from the middle, more of them and
+/// longer at each stage. Every stage extends the one before, so cracks grow
+/// rather than jump around.
+fn generate_destroy_stage(tile_size: u32, stage: u8) -> RgbaImage {
+    let mut img = ImageBuffer::from_pixel(tile_size, tile_size, Rgba([0, 0, 0, 0]));
+    let cracks = 2 + stage as u32 / 2;
+    let length = tile_size * (stage as u32 + 1) / DESTROY_STAGES as u32;
+    let center = tile_size as i32 / 2;
+    let directions = [(1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1), (0, -1), (1, -1)];
+    for crack in 0..cracks {
+        let (mut x, mut y) = (center, center);
+        let heading = jitter(crack, 0, 214) as usize % directions.len();
+        for step in 0..length {
+            // Mostly straight, veering to a neighbouring direction now and then.
+            let turn = match jitter(crack, step, 215) % 4 {
+                0 => directions.len() - 1,
+                1 => 1,
+                _ => 0,
+            };
+            let (dx, dy) = directions[(heading + turn) % directions.len()];
+            x = (x + dx).clamp(0, tile_size as i32 - 1);
+            y = (y + dy).clamp(0, tile_size as i32 - 1);
+            img.put_pixel(x as u32, y as u32, Rgba([20, 20, 20, 210]));
+        }
+    }
+    img
+}
+
+fn generate_wool(tile_size: u32, base: [u8; 3]) -> RgbaImage {
+    let mut img = ImageBuffer::new(tile_size, tile_size);
+    for y in 0..tile_size {
+        for x in 0..tile_size {
+            // A loose weave of alternating diagonal threads.
+            let shade = if (x + y) % 4 < 2 { 1.0 } else { 0.9 };
+            let color = generate_noise_color(scale(base, shade), 12, x, y, 212);
+            img.put_pixel(x, y, Rgba([color[0], color[1], color[2], 255]));
+        }
+    }
+    img
+}
+
+fn generate_terracotta(tile_size: u32, base: [u8; 3], seed: u32) -> RgbaImage {
+    let mut img = ImageBuffer::new(tile_size, tile_size);
+    for y in 0..tile_size {
+        for x in 0..tile_size {
+            let shade = if jitter(x / 2, y / 2, seed + 1) % 7 == 0 { 0.92 } else { 1.0 };
+            let color = generate_noise_color(scale(base, shade), 6, x, y, seed);
+            img.put_pixel(x, y, Rgba([color[0], color[1], color[2], 255]));
+        }
+    }
+    img
+}
+
+fn generate_stained_glass(tile_size: u32, base: [u8; 3]) -> RgbaImage {
+    let mut img = ImageBuffer::new(tile_size, tile_size);
+    let edge = tile_size - 1;
+    for y in 0..tile_size {
+        for x in 0..tile_size {
+            let frame = x == 0 || y == 0 || x == edge || y == edge;
+            let color = if frame { scale(base, 0.8) } else { base };
+            let alpha = if frame { 220 } else { 130 };
+            img.put_pixel(x, y, Rgba([color[0], color[1], color[2], alpha]));
+        }
+    }
+    img
+}
+
+fn generate_ice(tile_size: u32, base: [u8; 3], alpha: u8, seed: u32) -> RgbaImage {
+    let mut img = ImageBuffer::new(tile_size, tile_size);
+    for y in 0..tile_size {
+        for x in 0..tile_size {
+            // Pale streaks running diagonally, like trapped air.
+            let streak = (x + tile_size - y) % (tile_size / 2).max(2) == 0;
+            let color = if streak {
+                blend(base, [255, 255, 255], 0.5)
+            } else {
+                generate_noise_color(base, 8, x, y, seed)
+            };
+            img.put_pixel(x, y, Rgba([color[0], color[1], color[2], alpha]));
+        }
+    }
+    img
+}
+
+fn generate_bookshelf(tile_size: u32) -> RgbaImage {
+    let mut img = generate_planks(tile_size, Wood::Oak.planks_color());
+    let shelf = (tile_size / 2).max(2);
+    let spine = (tile_size / 8).max(1);
+    let covers = [[140, 40, 40], [45, 70, 130], [60, 110, 50], [150, 120, 60]];
+    for y in 0..tile_size {
+        let row = y % shelf;
+        // Planks show above and below each row of books.
+        if row < spine || row == shelf - 1 {
+            continue;
+        }
+        for x in spine..tile_size - spine {
+            let book = jitter(x / spine, y / shelf, 213) as usize % covers.len();
+            let color = scale(covers[book], if x % spine == 0 { 0.7 } else { 1.0 });
+            img.put_pixel(x, y, Rgba([color[0], color[1], color[2], 255]));
+        }
+    }
+    img
+}
+
+fn generate_crafting_table_top(tile_size: u32) -> RgbaImage {
+    let mut img = generate_planks(tile_size, Wood::Oak.planks_color());
+    let third = (tile_size / 3).max(1);
+    let line = [90, 62, 38];
+    for i in 0..tile_size {
+        for j in [third, tile_size - 1 - third] {
+            img.put_pixel(i, j, Rgba([line[0], line[1], line[2], 255]));
+            img.put_pixel(j, i, Rgba([line[0], line[1], line[2], 255]));
+        }
+    }
+    img
+}
+
+fn generate_crafting_table_side(tile_size: u32) -> RgbaImage {
+    let mut img = generate_planks(tile_size, Wood::Oak.planks_color());
+    // Tools hung on the side: a saw blade and a hammer head.
+    let top = tile_size / 4;
+    for y in top..top + (tile_size / 4).max(1) {
+        for x in tile_size / 8..tile_size / 2 {
+            img.put_pixel(x, y, Rgba([170, 170, 175, 255]));
+        }
+        for x in tile_size * 5 / 8..tile_size * 7 / 8 {
+            img.put_pixel(x, y, Rgba([80, 80, 85, 255]));
+        }
+    }
+    img
+}
+
+fn scale(color: [u8; 3], factor: f32) -> [u8; 3] {
+    color.map(|c| (c as f32 * factor).round().clamp(0.0, 255.0) as u8)
+}
+
+fn blend(from: [u8; 3], to: [u8; 3], t: f32) -> [u8; 3] {
+    [0, 1, 2].map(|i| (from[i] as f32 * (1.0 - t) + to[i] as f32 * t).round() as u8)
+}
+
 fn jitter(x: u32, y: u32, seed: u32) -> u32 {
     let mut v = x.wrapping_mul(374761393)
         ^ y.wrapping_mul(668265263)
@@ -2,7 +2,9 @@
 use anyhow::Result;
 use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::net::TcpListener;
 use tokio::sync::{broadcast, Mutex};
 use tokio_util::codec::{Framed, LengthDelimitedCodec};
@@ -10,11 +12,103 @@ use futures::{SinkExt, StreamExt};
 use bytes::Bytes;
 
 use crate::net::protocol::{ClientMessage, PlayerSnapshot, ServerMessage};
+use crate::world::game_mode::GameMode;
+use crate::world::region::write_atomic;
+use crate::world::{RetentionPolicy, World, CHUNK_SIZE};
 
+pub const DEFAULT_CONFIG: &str = "server_config.json";
+pub const DEFAULT_PLAYER_DATA: &str = "server_players.json";
+
+const TICK_INTERVAL: Duration = Duration::from_millis(50);
+const CHUNK_UNLOAD_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Settings for the dedicated server; fields missing from the config file keep their defaults.
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(default)]
 pub struct ServerConfig {
     pub bind: String,
     pub port: u16,
+    /// Mode given to players when they join.
+    pub default_game_mode: GameMode,
+    /// Let players switch their own game mode; otherwise requests are refused.
+    pub allow_game_mode_changes: bool,
+    /// File keeping each player's game mode between connections.
+    pub player_data: PathBuf,
+    /// World the server keeps loaded around its players. Without one the server
+    /// only relays player state.
+    pub world_dir: Option<PathBuf>,
+    /// Chunks loaded around each player.
+    pub view_distance: u32,
+    /// Chunks further than this from every player are saved and unloaded.
+    pub chunk_retention_radius: u32,
+    /// Soft cap on loaded chunk memory in MiB; 0 means unlimited.
+    pub chunk_memory_limit_mb: u32,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            bind: "0.0.0.0".to_string(),
+            port: 25565,
+            default_game_mode: GameMode::default(),
+            allow_game_mode_changes: false,
+            player_data: PathBuf::from(DEFAULT_PLAYER_DATA),
+            world_dir: None,
+            view_distance: 8,
+            chunk_retention_radius: 12,
+            chunk_memory_limit_mb: 0,
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Read the config file; a missing file gives the defaults.
+    pub fn load(path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => Ok(serde_json::from_str(&contents)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn chunk_retention_policy(&self) -> RetentionPolicy {
+        RetentionPolicy {
+            radius: self.chunk_retention_radius.max(self.view_distance + 1) as i32,
+            max_memory_bytes: match self.chunk_memory_limit_mb {
+                0 => None,
+                mb => Some(mb as usize * 1024 * 1024),
+            },
+        }
+    }
+}
+
+/// What the server remembers about players who have connected before, keyed by name.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default)]
+struct SavedPlayers {
+    game_modes: HashMap<String, GameMode>,
+}
+
+impl SavedPlayers {
+    fn load(path: &Path) -> Self {
+        let Ok(contents) = std::fs::read_to_string(path) else {
+            return Self::default();
+        };
+        serde_json::from_str(&contents).unwrap_or_else(|e| {
+            eprintln!("Failed to read {:?}: {}; starting without saved players", path, e);
+            Self::default()
+        })
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        write_atomic(path, serde_json::to_string_pretty(self)?.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Connected players by client id, plus what is remembered about everyone.
+struct Players {
+    online: HashMap<u64, PlayerSnapshot>,
+    saved: SavedPlayers,
 }
 
 pub async fn run_server(config: ServerConfig) -> Result<()> {
@@ -25,9 +119,23 @@ pub async fn run_server(config: ServerConfig) -> Result<()> {
     // broadcast channel for sending ServerMessage to connected clients
     let (tx, _rx) = broadcast::channel::<Vec<u8>>(128);
 
-    // simple in-memory player registry (id -> snapshot)
-    let players = Arc::new(Mutex::new(HashMap::<u64, PlayerSnapshot>::new()));
+    // player registry (id -> snapshot) and the players saved on disk
+    let players = Arc::new(Mutex::new(Players {
+        online: HashMap::new(),
+        saved: SavedPlayers::load(&config.player_data),
+    }));
     let mut next_id: u64 = 1;
+    let config = Arc::new(config);
+
+    if let Some(world_dir) = &config.world_dir {
+        let world = World::new(Some(world_dir.clone()), None)?;
+        println!("Serving world {:?}", world_dir);
+        let players = players.clone();
+        let config = config.clone();
+        std::thread::Builder::new()
+            .name("world".into())
+            .spawn(move || run_world(world, &players, &config))?;
+    }
 
     loop {
         let (socket, peer) = listener.accept().await?;
@@ -36,24 +144,63 @@ pub async fn run_server(config: ServerConfig) -> Result<()> {
         let tx = tx.clone();
         let mut rx = tx.subscribe();
         let players = players.clone();
+        let config = config.clone();
         let client_id = next_id;
         next_id += 1;
 
         tokio::spawn(async move {
-            if let Err(e) = handle_client(socket, peer, client_id, tx, rx, players).await {
+            if let Err(e) = handle_client(socket, peer, client_id, tx, rx, players, config).await {
                 eprintln!("client {} error: {}", peer, e);
             }
         });
     }
 }
 
+/// Keep the chunks around every connected player loaded and ticking, and save
+/// and unload the ones no player is near any more.
+fn run_world(mut world: World, players: &Mutex<Players>, config: &ServerConfig) {
+    let view = config.view_distance as i32;
+    let mut last_unload = Instant::now();
+    loop {
+        let centers: Vec<(i32, i32)> = players
+            .blocking_lock()
+            .online
+            .values()
+            .map(|p| {
+                let size = CHUNK_SIZE as f32;
+                ((p.x / size).floor() as i32, (p.z / size).floor() as i32)
+            })
+            .collect();
+
+        for (center_x, center_z) in &centers {
+            for x in center_x - view..=center_x + view {
+                for z in center_z - view..=center_z + view {
+                    world.request_chunk(x, z);
+                }
+            }
+        }
+        world.process_loaded_chunks();
+        world.tick();
+
+        if last_unload.elapsed() >= CHUNK_UNLOAD_INTERVAL {
+            let unloaded = world.unload_chunks(&centers, config.chunk_retention_policy());
+            if !unloaded.is_empty() {
+                println!("Unloaded {} chunks", unloaded.len());
+            }
+            last_unload = Instant::now();
+        }
+        std::thread::sleep(TICK_INTERVAL);
+    }
+}
+
 async fn handle_client(
     socket: tokio::net::TcpStream,
     peer: SocketAddr,
     client_id: u64,
     tx: broadcast::Sender<Vec<u8>>,
     mut rx: broadcast::Receiver<Vec<u8>>,
-    players: Arc<Mutex<HashMap<u64, PlayerSnapshot>>>,
+    players: Arc<Mutex<Players>>,
+    config: Arc<ServerConfig>,
 ) -> Result<()> {
     let mut framed = Framed::new(socket, LengthDelimitedCodec::new());
 
@@ -77,23 +224,65 @@ async fn handle_client(
         match msg {
             ClientMessage::Connect { name } => {
                 println!("{} connected as '{}' (id={})", peer, name, client_id);
-                let snapshot = PlayerSnapshot { id: client_id, name, x: 0.0, y: 0.0, z: 0.0, yaw: 0.0, pitch: 0.0 };
-                players.lock().await.insert(client_id, snapshot.clone());
+                let game_mode = players
+                    .lock()
+                    .await
+                    .saved
+                    .game_modes
+                    .get(&name)
+                    .copied()
+                    .unwrap_or(config.default_game_mode);
+                let snapshot = PlayerSnapshot { id: client_id, name, x: 0.0, y: 0.0, z: 0.0, yaw: 0.0, pitch: 0.0, game_mode };
+                players.lock().await.online.insert(client_id, snapshot.clone());
+                let _ = tx.send(bincode::serialize(&ServerMessage::GameMode { id: client_id, mode: game_mode })?);
 
                 // broadcast a world state immediately
-                let state = ServerMessage::WorldState { players: players.lock().await.values().cloned().collect() };
+                let state = ServerMessage::WorldState { players: players.lock().await.online.values().cloned().collect() };
                 let bytes = bincode::serialize(&state)?;
                 let _ = tx.send(bytes);
             }
             ClientMessage::Position { x, y, z, yaw, pitch } => {
-                if let Some(p) = players.lock().await.get_mut(&client_id) {
+                if let Some(p) = players.lock().await.online.get_mut(&client_id) {
                     p.x = x; p.y = y; p.z = z; p.yaw = yaw; p.pitch = pitch;
                 }
                 // broadcast updated world state
-                let state = ServerMessage::WorldState { players: players.lock().await.values().cloned().collect() };
+                let state = ServerMessage::WorldState { players: players.lock().await.online.values().cloned().collect() };
                 let bytes = bincode::serialize(&state)?;
                 let _ = tx.send(bytes);
             }
+            ClientMessage::SetGameMode { mode } => {
+                // The server has the final say; a refused request is answered with
+                // the mode the player already has.
+                let mode = {
+                    let mut players = players.lock().await;
+                    let Players { online, saved } = &mut *players;
+                    let Some(p) = online.get_mut(&client_id) else { continue };
+                    if config.allow_game_mode_changes {
+                        p.game_mode = mode;
+                        saved.game_modes.insert(p.name.clone(), mode);
+                        if let Err(e) = saved.save(&config.player_data) {
+                            eprintln!("Failed to save {:?}: {}", config.player_data, e);
+                        }
+                    } else {
+                        println!("Refused game mode change to {} for {}", mode.name(), p.name);
+                    }
+                    p.game_mode
+                };
+                let _ = tx.send(bincode::serialize(&ServerMessage::GameMode { id: client_id, mode })?);
+            }
+            ClientMessage::BreakBlock { x, y, z } => {
+                // Block breaking is vetoed here for players whose mode can't change the world.
+                let allowed = match players.lock().await.online.get(&client_id) {
+                    Some(p) => p.game_mode.can_modify_world(),
+                    None => false,
+                };
+                let reply = if allowed {
+                    ServerMessage::BlockBroken { x, y, z }
+                } else {
+                    ServerMessage::BreakRefused { id: client_id, x, y, z }
+                };
+                let _ = tx.send(bincode::serialize(&reply)?);
+            }
         }
     }
 
@@ -101,6 +290,6 @@ async fn handle_client(
     send_task.abort();
 
     println!("Client {} disconnected", peer);
-    players.lock().await.remove(&client_id);
+    players.lock().await.online.remove(&client_id);
     Ok(())
 }
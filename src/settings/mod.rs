@@ -151,6 +151,10 @@ pub struct GameSettings {
     pub autosave_interval_secs: u32,
     pub fps_cap_playing: u32,
     pub fps_cap_menu: u32,
+    /// Chunks further than this from the player are unloaded. Never below render distance + 1.
+    pub chunk_retention_radius: u32,
+    /// Soft cap on loaded chunk memory in MiB; 0 means unlimited.
+    pub chunk_memory_limit_mb: u32,
 }
 
 impl Default for GameSettings {
@@ -165,6 +169,8 @@ impl Default for GameSettings {
             autosave_interval_secs: Self::default_autosave_interval_secs(),
             fps_cap_playing: 0,
             fps_cap_menu: 60,
+            chunk_retention_radius: 12,
+            chunk_memory_limit_mb: 0,
         }
     }
 }
@@ -199,6 +205,19 @@ impl GameSettings {
     fn default_autosave_interval_secs() -> u32 {
         10
     }
+
+    pub fn chunk_retention_policy(&self) -> crate::world::RetentionPolicy {
+        let radius = self
+            .chunk_retention_radius
+            .max(self.graphics.render_distance + 1);
+        crate::world::RetentionPolicy {
+            radius: radius as i32,
+            max_memory_bytes: match self.chunk_memory_limit_mb {
+                0 => None,
+                mb => Some(mb as usize * 1024 * 1024),
+            },
+        }
+    }
 }
 
 // Add dirs dependency requirement
@@ -1,9 +1,347 @@
 #![allow(dead_code)]
+use crate::crafting::{CraftingGrid, RecipeRegistry, INVENTORY_GRID_SIZE};
+use crate::inventory::{self, merge_into_slot, ClickButton, Inventory, ItemStack, HOTBAR_SIZE};
+use crate::item::{ItemCategory, ItemRegistry};
+use crate::world::block_entity::{BlockEntity, SmeltingRecipes};
 use crate::settings::{GameSettings, QualityPreset, TextureQuality};
 use egui::{self, FontDefinitions};
 use egui_winit::State;
 use winit::window::Window;
 
+/// Number keys that swap the hovered slot with a hotbar slot.
+const HOTBAR_KEYS: [egui::Key; HOTBAR_SIZE] = [
+    egui::Key::Num1,
+    egui::Key::Num2,
+    egui::Key::Num3,
+    egui::Key::Num4,
+    egui::Key::Num5,
+    egui::Key::Num6,
+    egui::Key::Num7,
+    egui::Key::Num8,
+    egui::Key::Num9,
+];
+
+/// A slot in an open inventory window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlotId {
+    Inventory(usize),
+    Grid(usize),
+    Chest(usize),
+    FurnaceInput,
+    FurnaceFuel,
+    FurnaceOutput,
+}
+
+/// What the inventory windows remember between frames.
+#[derive(Debug, Default)]
+pub struct InventoryView {
+    /// Stack picked up with the mouse. It goes back into the inventory when the window closes.
+    pub cursor: Option<ItemStack>,
+    /// Button held down over a slot with a stack on the cursor, and the slots
+    /// dragged across since.
+    drag: Option<(ClickButton, Vec<SlotId>)>,
+    /// Recipe book search text.
+    pub search: String,
+    pub message: Option<String>,
+    /// Tab and search text of the creative item picker.
+    pub picker_category: ItemCategory,
+    pub picker_search: String,
+}
+
+impl InventoryView {
+    /// Forget a drag in progress, as when the window closes under it.
+    pub fn cancel_drag(&mut self) {
+        self.drag = None;
+    }
+}
+
+/// Slot interactions asked for this frame, carried out by `apply_slot_actions`.
+#[derive(Debug)]
+enum SlotAction {
+    Click(SlotId, ClickButton),
+    QuickMove(SlotId),
+    Distribute(Vec<SlotId>, ClickButton),
+    SwapHotbar(SlotId, usize),
+    Throw(ClickButton),
+}
+
+fn stack_label(stack: &Option<ItemStack>) -> String {
+    stack
+        .as_ref()
+        .map(|stack| format!("{} x{}", stack.id, stack.count))
+        .unwrap_or_default()
+}
+
+/// Draw a slot button, noting it in `hovered` when the pointer is over it.
+fn slot_button(
+    ui: &mut egui::Ui,
+    id: SlotId,
+    stack: &Option<ItemStack>,
+    size: egui::Vec2,
+    view: &InventoryView,
+    hovered: &mut Option<SlotId>,
+) {
+    let dragged = view.drag.as_ref().is_some_and(|(_, slots)| slots.contains(&id));
+    let response = ui.add(egui::Button::new(stack_label(stack)).min_size(size).selected(dragged));
+    if response.contains_pointer() {
+        *hovered = Some(id);
+    }
+}
+
+/// Show the stack on the cursor next to the pointer.
+fn draw_cursor_stack(ctx: &egui::Context, view: &InventoryView) {
+    let (Some(stack), Some(pos)) = (&view.cursor, ctx.pointer_latest_pos()) else {
+        return;
+    };
+    egui::Area::new(egui::Id::new("cursor_stack"))
+        .order(egui::Order::Tooltip)
+        .interactable(false)
+        .fixed_pos(pos + egui::vec2(12.0, 12.0))
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.label(format!("{} x{}", stack.id, stack.count));
+            });
+        });
+}
+
+/// Turn this frame's pointer and number-key input into slot actions. `hovered`
+/// is the slot under the pointer, if any.
+///
+/// Pressing over a slot with nothing on the cursor clicks it straight away.
+/// With a stack on the cursor, pressing starts a drag; letting go over the same
+/// slot clicks it and letting go after crossing more slots spreads the stack
+/// over them. Shift-left-click quick-moves and pressing outside every window
+/// throws the cursor's stack.
+fn slot_actions(ctx: &egui::Context, view: &mut InventoryView, hovered: Option<SlotId>) -> Vec<SlotAction> {
+    let (pressed, left_down, right_down, shift, number) = ctx.input(|i| {
+        let pressed = if i.pointer.button_pressed(egui::PointerButton::Primary) {
+            Some(ClickButton::Left)
+        } else if i.pointer.button_pressed(egui::PointerButton::Secondary) {
+            Some(ClickButton::Right)
+        } else {
+            None
+        };
+        (
+            pressed,
+            i.pointer.button_down(egui::PointerButton::Primary),
+            i.pointer.button_down(egui::PointerButton::Secondary),
+            i.modifiers.shift,
+            HOTBAR_KEYS.iter().position(|key| i.key_pressed(*key)),
+        )
+    });
+
+    let mut actions = Vec::new();
+    if let Some((button, slots)) = &mut view.drag {
+        if let Some(slot) = hovered.filter(|slot| *slot != SlotId::FurnaceOutput) {
+            if !slots.contains(&slot) {
+                slots.push(slot);
+            }
+        }
+        let still_down = match button {
+            ClickButton::Left => left_down,
+            ClickButton::Right => right_down,
+        };
+        if !still_down {
+            if let Some((button, mut slots)) = view.drag.take() {
+                actions.push(if slots.len() == 1 {
+                    SlotAction::Click(slots.remove(0), button)
+                } else {
+                    SlotAction::Distribute(slots, button)
+                });
+            }
+        }
+    } else if let Some(button) = pressed {
+        match hovered {
+            Some(slot) if shift && button == ClickButton::Left => actions.push(SlotAction::QuickMove(slot)),
+            Some(slot) if view.cursor.is_some() && slot != SlotId::FurnaceOutput => {
+                view.drag = Some((button, vec![slot]));
+            }
+            Some(slot) => actions.push(SlotAction::Click(slot, button)),
+            None if view.cursor.is_some() && !ctx.is_pointer_over_area() => actions.push(SlotAction::Throw(button)),
+            None => {}
+        }
+    }
+    if let (Some(slot), Some(number)) = (hovered, number) {
+        actions.push(SlotAction::SwapHotbar(slot, number));
+    }
+    actions
+}
+
+/// The slot `id` names outside the player's inventory.
+fn container_slot<'a>(
+    id: SlotId,
+    grid: Option<&'a mut CraftingGrid>,
+    entity: Option<&'a mut BlockEntity>,
+) -> Option<&'a mut Option<ItemStack>> {
+    match (id, entity) {
+        (SlotId::Inventory(_), _) => None,
+        (SlotId::Grid(idx), _) => grid?.slots_mut().nth(idx),
+        (SlotId::Chest(idx), Some(BlockEntity::Chest(chest))) => chest.inventory.slots.get_mut(idx),
+        (SlotId::FurnaceInput, Some(BlockEntity::Furnace(furnace))) => Some(&mut furnace.input),
+        (SlotId::FurnaceFuel, Some(BlockEntity::Furnace(furnace))) => Some(&mut furnace.fuel),
+        (SlotId::FurnaceOutput, Some(BlockEntity::Furnace(furnace))) => Some(&mut furnace.output),
+        _ => None,
+    }
+}
+
+/// Carry out slot actions on the player's inventory and whichever crafting grid
+/// or block entity the window shows. Returns the stacks thrown out of the window.
+fn apply_slot_actions(
+    actions: Vec<SlotAction>,
+    view: &mut InventoryView,
+    inventory: &mut Inventory,
+    mut grid: Option<&mut CraftingGrid>,
+    mut entity: Option<&mut BlockEntity>,
+) -> Vec<ItemStack> {
+    let mut thrown = Vec::new();
+    for action in actions {
+        match action {
+            SlotAction::Click(SlotId::Inventory(idx), button) => inventory.click(idx, &mut view.cursor, button),
+            SlotAction::Click(SlotId::FurnaceOutput, _) => {
+                if let Some(slot) = container_slot(SlotId::FurnaceOutput, None, entity.as_deref_mut()) {
+                    inventory::click_output_slot(slot, &mut view.cursor);
+                }
+            }
+            SlotAction::Click(id, button) => {
+                if let Some(slot) = container_slot(id, grid.as_deref_mut(), entity.as_deref_mut()) {
+                    inventory::click_slot(slot, &mut view.cursor, button);
+                }
+            }
+            SlotAction::QuickMove(SlotId::Inventory(idx)) => match entity.as_deref_mut() {
+                Some(BlockEntity::Chest(chest)) => {
+                    inventory.quick_move_to(idx, &mut chest.inventory);
+                }
+                Some(BlockEntity::Furnace(furnace)) => {
+                    // Smeltable items go in to cook, anything else that burns goes in as fuel.
+                    let smelting = SmeltingRecipes::global();
+                    if let Some(stack) = inventory.slots.get_mut(idx).and_then(Option::take) {
+                        let target = if smelting.recipe_for(&stack.id).is_some() {
+                            Some(&mut furnace.input)
+                        } else if smelting.burn_time(&stack.id).is_some() {
+                            Some(&mut furnace.fuel)
+                        } else {
+                            None
+                        };
+                        inventory.slots[idx] = match target {
+                            Some(target) => merge_into_slot(target, stack),
+                            None => Some(stack),
+                        };
+                    }
+                }
+                None => {
+                    inventory.quick_move(idx);
+                }
+            },
+            SlotAction::QuickMove(id) => {
+                if let Some(slot) = container_slot(id, grid.as_deref_mut(), entity.as_deref_mut()) {
+                    if let Some(stack) = slot.take() {
+                        *slot = inventory.add_item(stack).err();
+                    }
+                }
+            }
+            SlotAction::Distribute(ids, button) => {
+                let mut slots: Vec<&mut Option<ItemStack>> = Vec::new();
+                let mut entity_slots: Vec<(SlotId, &mut Option<ItemStack>)> = Vec::new();
+                match entity.as_deref_mut() {
+                    Some(BlockEntity::Chest(chest)) => entity_slots
+                        .extend(chest.inventory.slots.iter_mut().enumerate().map(|(i, s)| (SlotId::Chest(i), s))),
+                    Some(BlockEntity::Furnace(furnace)) => {
+                        entity_slots.push((SlotId::FurnaceInput, &mut furnace.input));
+                        entity_slots.push((SlotId::FurnaceFuel, &mut furnace.fuel));
+                    }
+                    None => {}
+                }
+                let grid_slots = grid
+                    .as_deref_mut()
+                    .into_iter()
+                    .flat_map(|grid| grid.slots_mut())
+                    .enumerate()
+                    .map(|(i, s)| (SlotId::Grid(i), s));
+                let inventory_slots =
+                    inventory.slots.iter_mut().enumerate().map(|(i, s)| (SlotId::Inventory(i), s));
+                for (id, slot) in entity_slots.into_iter().chain(grid_slots).chain(inventory_slots) {
+                    if ids.contains(&id) {
+                        slots.push(slot);
+                    }
+                }
+                inventory::distribute(&mut slots, &mut view.cursor, button);
+            }
+            SlotAction::SwapHotbar(SlotId::Inventory(idx), number) => inventory.swap_with_hotbar(idx, number),
+            // The output only gives; everything else swaps with the hotbar slot.
+            SlotAction::SwapHotbar(SlotId::FurnaceOutput, _) => {}
+            SlotAction::SwapHotbar(id, number) => {
+                let slot = container_slot(id, grid.as_deref_mut(), entity.as_deref_mut());
+                if let (Some(slot), Some(hotbar)) = (slot, inventory.slots.get_mut(number)) {
+                    std::mem::swap(slot, hotbar);
+                }
+            }
+            SlotAction::Throw(button) => thrown.extend(inventory::drop_from_cursor(&mut view.cursor, button)),
+        }
+    }
+    thrown
+}
+
+/// Draw the creative item picker. Clicking an item puts a full stack of it on the
+/// cursor, moving whatever the cursor held into the inventory; shift-clicking puts
+/// one straight into the inventory. Returns what the inventory had no room for.
+fn item_picker(ui: &mut egui::Ui, inventory: &mut Inventory, view: &mut InventoryView) -> Option<ItemStack> {
+    ui.horizontal_wrapped(|ui| {
+        for category in ItemCategory::ALL {
+            ui.selectable_value(&mut view.picker_category, category, category.name());
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.label("Search");
+        ui.text_edit_singleline(&mut view.picker_search);
+    });
+    // A search looks through every tab.
+    let query = view.picker_search.trim().to_lowercase();
+    let items = ItemRegistry::global().iter().filter(|item| {
+        if query.is_empty() {
+            item.category == view.picker_category
+        } else {
+            item.display_name.to_lowercase().contains(&query) || item.name.contains(&query)
+        }
+    });
+    let mut picked = None;
+    egui::ScrollArea::vertical().max_height(160.0).show(ui, |ui| {
+        ui.horizontal_wrapped(|ui| {
+            for item in items {
+                if ui.add(egui::Button::new(&item.display_name).min_size(egui::vec2(48.0, 32.0))).clicked() {
+                    picked = Some(ItemStack { id: item.name.clone(), count: item.max_stack });
+                }
+            }
+        });
+    });
+    if let Some(stack) = picked {
+        if ui.input(|i| i.modifiers.shift) {
+            // Nothing is lost if the inventory is full; the picker has more.
+            let _ = inventory.add_item(stack);
+        } else if let Some(held) = view.cursor.replace(stack) {
+            return inventory.add_item(held).err();
+        }
+    }
+    None
+}
+
+/// Draw the player's inventory as a grid of slots.
+fn inventory_grid(
+    ui: &mut egui::Ui,
+    grid_id: &str,
+    inventory: &Inventory,
+    view: &InventoryView,
+    hovered: &mut Option<SlotId>,
+) {
+    egui::Grid::new(grid_id).num_columns(9).show(ui, |ui| {
+        for (idx, slot) in inventory.slots.iter().enumerate() {
+            slot_button(ui, SlotId::Inventory(idx), slot, egui::vec2(48.0, 48.0), view, hovered);
+            if idx % 9 == 8 {
+                ui.end_row();
+            }
+        }
+    });
+}
+
 pub struct Gui {
     pub egui_ctx: egui::Context,
     pub state: egui_winit::State,
@@ -49,60 +387,165 @@ impl Gui {
 
     }
 
-    /// Draw inventory/hotbar. `selected` is the hotbar index and `open` toggles the full inventory window.
-        pub fn draw_inventory(
-            &self,
-            ui_ctx: &egui::Context,
-            inventory: &mut crate::inventory::Inventory,
-            selected: &mut usize,
-            open: &mut bool,
-        ) {
-            // Draw hotbar at bottom center
-            egui::TopBottomPanel::bottom("hotbar_panel").show(ui_ctx, |ui| {
-                ui.horizontal_centered(|ui| {
-                    for i in 0..9usize.min(inventory.size) {
-                        let mut label = "Empty".to_string();
-                        if let Some(slot) = &inventory.slots[i] {
-                            label = format!("{} x{}", slot.id, slot.count);
-                        }
-                        let btn = ui.add(egui::Button::new(label).min_size(egui::vec2(48.0, 48.0)));
-                        if btn.clicked() {
-                            *selected = i;
-                        }
-                    }
-                    if ui.button("Inv").clicked() {
-                        *open = !*open;
-                    }
-                });
-            });
-
+    /// Draw the inventory window, with the crafting grid and recipe book, and in creative
+    /// mode the item picker. `open` is cleared when the window is closed. Returns stacks
+    /// thrown out of the window, and crafted items that didn't fit in the inventory.
+    pub fn draw_inventory(
+        &self,
+        ui_ctx: &egui::Context,
+        inventory: &mut Inventory,
+        open: &mut bool,
+        crafting: &mut CraftingGrid,
+        recipes: &RecipeRegistry,
+        view: &mut InventoryView,
+        creative: bool,
+    ) -> Vec<ItemStack> {
+            let mut overflow = Vec::new();
             if *open {
-                egui::Window::new("Inventory").show(ui_ctx, |ui| {
-                    ui.label("Inventory");
-                    egui::Grid::new("inv_grid").num_columns(9).show(ui, |ui| {
-                        for r in 0..(inventory.size / 9) {
-                            for c in 0..9 {
-                                let idx = r * 9 + c;
-                                if idx >= inventory.size {
-                                    ui.label("");
-                                    continue;
+                let mut hovered = None;
+                let title = if crafting.size() > INVENTORY_GRID_SIZE { "Crafting Table" } else { "Inventory" };
+                egui::Window::new(title).show(ui_ctx, |ui| {
+                    ui.label("Crafting");
+                    ui.horizontal(|ui| {
+                        let size = crafting.size();
+                        egui::Grid::new("crafting_grid").num_columns(size).show(ui, |ui| {
+                            for y in 0..size {
+                                for x in 0..size {
+                                    let stack = crafting.get(x, y).cloned();
+                                    let id = SlotId::Grid(y * size + x);
+                                    slot_button(ui, id, &stack, egui::vec2(48.0, 48.0), view, &mut hovered);
                                 }
-                                let mut label = "".to_string();
-                                if let Some(slot) = &inventory.slots[idx] {
-                                    label = format!("{} x{}", slot.id, slot.count);
+                                ui.end_row();
+                            }
+                        });
+                        ui.label("=>");
+                        let result = recipes.find(crafting).map(|recipe| recipe.result.clone());
+                        let output = ui.add_enabled(
+                            result.is_some(),
+                            egui::Button::new(stack_label(&result)).min_size(egui::vec2(64.0, 48.0)),
+                        );
+                        if output.clicked() {
+                            if ui.input(|i| i.modifiers.shift) {
+                                // Shift-click crafts straight into the inventory.
+                                if let Some(stack) = recipes.craft(crafting) {
+                                    if let Err(left) = inventory.add_item(stack) {
+                                        overflow.push(left);
+                                    }
                                 }
-                                if ui.button(label).clicked() {
-                                    // Simple pickup logic: remove stack and put in selected hotbar if empty
-                                    if let Some(stack) = inventory.remove_at(idx, u16::MAX) {
-                                        let _ = inventory.add_item(stack);
+                            } else {
+                                // The result joins the cursor's stack, if it fits there.
+                                let fits = match (&view.cursor, &result) {
+                                    (None, _) => true,
+                                    (Some(held), Some(result)) => {
+                                        held.id == result.id && held.count + result.count <= held.max_stack_size()
                                     }
+                                    (Some(_), None) => false,
+                                };
+                                if let Some(stack) = fits.then(|| recipes.craft(crafting)).flatten() {
+                                    overflow.extend(merge_into_slot(&mut view.cursor, stack));
                                 }
                             }
-                            ui.end_row();
                         }
                     });
+
+                    ui.collapsing("Recipe book", |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label("Search");
+                            ui.text_edit_singleline(&mut view.search);
+                        });
+                        let size = crafting.size();
+                        egui::ScrollArea::vertical().max_height(160.0).show(ui, |ui| {
+                            for recipe in recipes.search(&view.search).filter(|r| r.fits(size)) {
+                                let label = format!("{} x{}", recipe.result.id, recipe.result.count);
+                                if ui.button(label).clicked() {
+                                    view.message = (!recipe.fill_grid(crafting, inventory))
+                                        .then(|| format!("Missing ingredients for {}", recipe.result.id));
+                                }
+                            }
+                        });
+                        if let Some(message) = &view.message {
+                            ui.label(message);
+                        }
+                    });
+
+                    if creative {
+                        let picker = ui.collapsing("Creative items", |ui| item_picker(ui, inventory, view));
+                        overflow.extend(picker.body_returned.flatten());
+                    }
+
+                    ui.separator();
+                    ui.label("Inventory");
+                    inventory_grid(ui, "inv_grid", inventory, view, &mut hovered);
                 });
+                let actions = slot_actions(ui_ctx, view, hovered);
+                overflow.extend(apply_slot_actions(actions, view, inventory, Some(crafting), None));
+                draw_cursor_stack(ui_ctx, view);
+            }
+            overflow
+    }
+
+    /// Draw the window of an opened chest or furnace next to the player's inventory.
+    /// Returns stacks thrown out of the window.
+    pub fn draw_block_entity(
+        &self,
+        ui_ctx: &egui::Context,
+        entity: &mut BlockEntity,
+        inventory: &mut Inventory,
+        open: &mut bool,
+        view: &mut InventoryView,
+    ) -> Vec<ItemStack> {
+        let title = match entity {
+            BlockEntity::Chest(_) => "Chest",
+            BlockEntity::Furnace(_) => "Furnace",
+        };
+        let mut hovered = None;
+        egui::Window::new(title).open(open).show(ui_ctx, |ui| {
+            match &*entity {
+                BlockEntity::Chest(chest) => {
+                    egui::Grid::new("chest_grid").num_columns(9).show(ui, |ui| {
+                        for (idx, slot) in chest.inventory.slots.iter().enumerate() {
+                            slot_button(ui, SlotId::Chest(idx), slot, egui::vec2(48.0, 48.0), view, &mut hovered);
+                            if idx % 9 == 8 {
+                                ui.end_row();
+                            }
+                        }
+                    });
+                }
+                BlockEntity::Furnace(furnace) => {
+                    egui::Grid::new("furnace_grid").num_columns(2).show(ui, |ui| {
+                        for (name, id, slot) in [
+                            ("Input", SlotId::FurnaceInput, &furnace.input),
+                            ("Fuel", SlotId::FurnaceFuel, &furnace.fuel),
+                            ("Output", SlotId::FurnaceOutput, &furnace.output),
+                        ] {
+                            ui.label(name);
+                            slot_button(ui, id, slot, egui::vec2(96.0, 32.0), view, &mut hovered);
+                            ui.end_row();
+                        }
+                    });
+                    let burn = if furnace.burn_duration > 0 {
+                        furnace.burn_time as f32 / furnace.burn_duration as f32
+                    } else {
+                        0.0
+                    };
+                    let cook = if furnace.cook_time > 0 {
+                        furnace.cook_progress as f32 / furnace.cook_time as f32
+                    } else {
+                        0.0
+                    };
+                    ui.add(egui::ProgressBar::new(burn).text("Fuel"));
+                    ui.add(egui::ProgressBar::new(cook).text("Smelting"));
+                }
             }
+
+            ui.separator();
+            ui.label("Inventory");
+            inventory_grid(ui, "container_inv_grid", inventory, view, &mut hovered);
+        });
+        let actions = slot_actions(ui_ctx, view, hovered);
+        let thrown = apply_slot_actions(actions, view, inventory, None, Some(entity));
+        draw_cursor_stack(ui_ctx, view);
+        thrown
     }
 
     /// Draw the main in-window menu. Returns whether the user requested to open settings.
@@ -236,6 +679,21 @@ impl Gui {
                     egui::Slider::new(&mut settings.autosave_interval_secs, 5..=300)
                         .text("Autosave Interval (seconds)"),
                 );
+                ui.add(
+                    egui::Slider::new(&mut settings.chunk_retention_radius, 2..=64)
+                        .text("Chunk Retention Radius"),
+                );
+                ui.add(
+                    egui::Slider::new(&mut settings.chunk_memory_limit_mb, 0..=4096)
+                        .text("Chunk Memory Limit")
+                        .custom_formatter(|value, _| {
+                            if value < 0.5 {
+                                "Unlimited".to_string()
+                            } else {
+                                format!("{:.0} MiB", value)
+                            }
+                        }),
+                );
                 ui.add(
                     egui::Slider::new(&mut settings.fps_cap_playing, 0..=240)
                         .integer()
@@ -0,0 +1,217 @@
+#![allow(dead_code)]
+/// Biomes and the climate noise that places them.
+///
+/// A biome is picked per column from three low-frequency noise fields derived
+/// from the world seed: continentalness (ocean vs. land), temperature and
+/// humidity. The biome decides the terrain height curve, the surface and filler
+/// blocks, and the tint applied to grass and leaves.
+use super::block::BlockType;
+use noise::{NoiseFn, Perlin};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+#[repr(u8)]
+pub enum Biome {
+    Ocean = 0,
+    Beach = 1,
+    #[default]
+    Plains = 2,
+    Desert = 3,
+    Badlands = 4,
+    Savanna = 5,
+    Jungle = 6,
+    Forest = 7,
+    BirchForest = 8,
+    DarkForest = 9,
+    Swamp = 10,
+    Taiga = 11,
+    Mountains = 12,
+}
+
+/// Terrain shape for a biome: the average surface height and how far it may stray from it.
+#[derive(Debug, Clone, Copy)]
+pub struct HeightParams {
+    pub base: f64,
+    pub amplitude: f64,
+}
+
+impl Biome {
+    pub const ALL: [Biome; 13] = [
+        Biome::Ocean,
+        Biome::Beach,
+        Biome::Plains,
+        Biome::Desert,
+        Biome::Badlands,
+        Biome::Savanna,
+        Biome::Jungle,
+        Biome::Forest,
+        Biome::BirchForest,
+        Biome::DarkForest,
+        Biome::Swamp,
+        Biome::Taiga,
+        Biome::Mountains,
+    ];
+
+    pub fn from_id(id: u8) -> Self {
+        Self::ALL.get(id as usize).copied().unwrap_or_default()
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Biome::Ocean => "Ocean",
+            Biome::Beach => "Beach",
+            Biome::Plains => "Plains",
+            Biome::Desert => "Desert",
+            Biome::Badlands => "Badlands",
+            Biome::Savanna => "Savanna",
+            Biome::Jungle => "Jungle",
+            Biome::Forest => "Forest",
+            Biome::BirchForest => "Birch Forest",
+            Biome::DarkForest => "Dark Forest",
+            Biome::Swamp => "Swamp",
+            Biome::Taiga => "Taiga",
+            Biome::Mountains => "Mountains",
+        }
+    }
+
+    pub fn height_params(self) -> HeightParams {
+        let (base, amplitude) = match self {
+            Biome::Ocean => (46.0, 10.0),
+            Biome::Beach => (64.0, 2.0),
+            Biome::Plains => (68.0, 6.0),
+            Biome::Desert => (68.0, 8.0),
+            Biome::Badlands => (76.0, 18.0),
+            Biome::Savanna => (70.0, 8.0),
+            Biome::Jungle => (72.0, 16.0),
+            Biome::Forest => (70.0, 10.0),
+            Biome::BirchForest => (70.0, 10.0),
+            Biome::DarkForest => (69.0, 8.0),
+            Biome::Swamp => (62.0, 3.0),
+            Biome::Taiga => (72.0, 14.0),
+            Biome::Mountains => (96.0, 48.0),
+        };
+        HeightParams { base, amplitude }
+    }
+
+    /// Topmost block of a column that ends above sea level.
+    pub fn surface_block(self) -> BlockType {
+        match self {
+            Biome::Ocean | Biome::Beach | Biome::Desert => BlockType::Sand,
+            Biome::Badlands => BlockType::RedSand,
+            Biome::Taiga => BlockType::Podzol,
+            _ => BlockType::GrassBlock,
+        }
+    }
+
+    /// Topmost block of a column that ends under water.
+    pub fn underwater_surface_block(self) -> BlockType {
+        match self {
+            Biome::Swamp => BlockType::Mud,
+            Biome::Taiga | Biome::Mountains => BlockType::Gravel,
+            Biome::Badlands => BlockType::RedSand,
+            _ => BlockType::Sand,
+        }
+    }
+
+    /// Blocks between the surface and the stone layer.
+    pub fn filler_block(self) -> BlockType {
+        match self {
+            Biome::Ocean | Biome::Beach | Biome::Desert => BlockType::Sandstone,
+            Biome::Badlands => BlockType::RedSandstone,
+            Biome::Swamp => BlockType::Mud,
+            _ => BlockType::Dirt,
+        }
+    }
+
+    pub fn grass_tint(self) -> [f32; 3] {
+        match self {
+            Biome::Desert | Biome::Badlands | Biome::Savanna => [0.56, 0.62, 0.31],
+            Biome::Jungle => [0.26, 0.72, 0.16],
+            Biome::Forest => [0.33, 0.64, 0.24],
+            Biome::BirchForest => [0.42, 0.66, 0.30],
+            Biome::DarkForest => [0.27, 0.52, 0.20],
+            Biome::Swamp => [0.38, 0.48, 0.26],
+            Biome::Taiga => [0.36, 0.56, 0.36],
+            Biome::Mountains => [0.40, 0.58, 0.40],
+            _ => [0.35, 0.65, 0.25],
+        }
+    }
+
+    pub fn foliage_tint(self) -> [f32; 3] {
+        match self {
+            Biome::Desert | Biome::Badlands | Biome::Savanna => [0.50, 0.60, 0.22],
+            Biome::Jungle => [0.19, 0.70, 0.10],
+            Biome::BirchForest => [0.42, 0.62, 0.30],
+            Biome::DarkForest => [0.16, 0.46, 0.14],
+            Biome::Swamp => [0.32, 0.44, 0.20],
+            Biome::Taiga => [0.24, 0.45, 0.30],
+            Biome::Mountains => [0.30, 0.50, 0.32],
+            _ => [0.20, 0.60, 0.20],
+        }
+    }
+}
+
+/// Samples the climate noise fields and maps them to biomes.
+pub struct BiomeSource {
+    continentalness: Perlin,
+    temperature: Perlin,
+    humidity: Perlin,
+}
+
+impl BiomeSource {
+    pub fn new(seed: u32) -> Self {
+        Self {
+            continentalness: Perlin::new(seed.wrapping_add(1)),
+            temperature: Perlin::new(seed.wrapping_add(2)),
+            humidity: Perlin::new(seed.wrapping_add(3)),
+        }
+    }
+
+    fn sample(noise: &Perlin, x: f64, z: f64, scale: f64) -> f64 {
+        let mut value = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = scale;
+        let mut max_value = 0.0;
+        for _ in 0..3 {
+            value += noise.get([x * frequency, z * frequency]) * amplitude;
+            max_value += amplitude;
+            amplitude *= 0.5;
+            frequency *= 2.0;
+        }
+        (value / max_value + 1.0) / 2.0
+    }
+
+    pub fn biome_at(&self, world_x: i32, world_z: i32) -> Biome {
+        let (x, z) = (world_x as f64, world_z as f64);
+        let continental = Self::sample(&self.continentalness, x, z, 0.0015);
+        if continental < 0.40 {
+            return Biome::Ocean;
+        }
+        if continental < 0.43 {
+            return Biome::Beach;
+        }
+
+        let temperature = Self::sample(&self.temperature, x, z, 0.0025);
+        let humidity = Self::sample(&self.humidity, x, z, 0.0025);
+        if temperature > 0.58 {
+            match humidity {
+                h if h < 0.38 => Biome::Desert,
+                h if h < 0.46 => Biome::Badlands,
+                h if h < 0.56 => Biome::Savanna,
+                _ => Biome::Jungle,
+            }
+        } else if temperature > 0.42 {
+            match humidity {
+                h if h < 0.42 => Biome::Plains,
+                h if h < 0.52 => Biome::Forest,
+                h if h < 0.58 => Biome::BirchForest,
+                h if h < 0.64 => Biome::DarkForest,
+                _ => Biome::Swamp,
+            }
+        } else if humidity < 0.45 {
+            Biome::Mountains
+        } else {
+            Biome::Taiga
+        }
+    }
+}
@@ -1,11 +1,36 @@
 #![allow(dead_code)]
-/// Block types matching the JavaScript implementation
-/// Covers all vanilla Minecraft blocks for 1.21.10 compatibility
+/// Block types matching the JavaScript implementation, covering the vanilla 1.21
+/// building blocks. Everything known about a block lives in its definition in
+/// `data/blocks.json`; see `registry`.
+use super::registry::{BlockDefinition, BlockRegistry};
+use super::shape::BlockShape;
+use super::state::Property;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
-#[repr(u16)]
-pub enum BlockType {
+/// The first release saved blocks as their variant's position in the list, and
+/// declared exactly this many, in the order they still have.
+pub const LEGACY_BLOCK_COUNT: usize = 67;
+
+/// Declares `BlockType` and `BlockType::ALL` from a single list of variants, so
+/// adding a block only takes a line here and its definition in `data/blocks.json`.
+/// New blocks go at the end; see `LEGACY_BLOCK_COUNT`.
+macro_rules! block_types {
+    ($($name:ident = $id:literal,)*) => {
+        /// Saved chunks store blocks by id (see `by_id`); data files name them by variant.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+        #[repr(u16)]
+        pub enum BlockType {
+            $($name = $id,)*
+        }
+
+        impl BlockType {
+            /// Every block type, in declaration order.
+            pub const ALL: &'static [BlockType] = &[$(BlockType::$name,)*];
+        }
+    };
+}
+
+block_types! {
     // Air and Technical
     Air = 0,
     CaveAir = 1,
@@ -90,6 +115,248 @@ pub enum BlockType {
     CraftingTable = 239,
     Furnace = 240,
     Chest = 241,
+
+    // Slabs
+    StoneSlab = 107,
+    OakSlab = 137,
+
+    // Wood: remaining species, stairs, slabs, fences and leaves
+    OakStairs = 242,
+    OakFence = 243,
+    SpruceStairs = 244,
+    SpruceSlab = 245,
+    SpruceFence = 246,
+    SpruceLeaves = 247,
+    BirchStairs = 248,
+    BirchSlab = 249,
+    BirchFence = 250,
+    BirchLeaves = 251,
+    JunglePlanks = 252,
+    JungleStairs = 253,
+    JungleSlab = 254,
+    JungleFence = 255,
+    JungleLeaves = 256,
+    AcaciaPlanks = 257,
+    AcaciaStairs = 258,
+    AcaciaSlab = 259,
+    AcaciaFence = 260,
+    AcaciaLeaves = 261,
+    DarkOakPlanks = 262,
+    DarkOakStairs = 263,
+    DarkOakSlab = 264,
+    DarkOakFence = 265,
+    DarkOakLeaves = 266,
+    MangroveLog = 267,
+    MangrovePlanks = 268,
+    MangroveStairs = 269,
+    MangroveSlab = 270,
+    MangroveFence = 271,
+    MangroveLeaves = 272,
+    CherryLog = 273,
+    CherryPlanks = 274,
+    CherryStairs = 275,
+    CherrySlab = 276,
+    CherryFence = 277,
+    CherryLeaves = 278,
+    CrimsonStem = 279,
+    CrimsonPlanks = 280,
+    CrimsonStairs = 281,
+    CrimsonSlab = 282,
+    CrimsonFence = 283,
+    WarpedStem = 284,
+    WarpedPlanks = 285,
+    WarpedStairs = 286,
+    WarpedSlab = 287,
+    WarpedFence = 288,
+
+    // Wool
+    WhiteWool = 289,
+    OrangeWool = 290,
+    MagentaWool = 291,
+    LightBlueWool = 292,
+    YellowWool = 293,
+    LimeWool = 294,
+    PinkWool = 295,
+    GrayWool = 296,
+    LightGrayWool = 297,
+    CyanWool = 298,
+    PurpleWool = 299,
+    BlueWool = 300,
+    BrownWool = 301,
+    GreenWool = 302,
+    RedWool = 303,
+    BlackWool = 304,
+
+    // Concrete
+    WhiteConcrete = 305,
+    OrangeConcrete = 306,
+    MagentaConcrete = 307,
+    LightBlueConcrete = 308,
+    YellowConcrete = 309,
+    LimeConcrete = 310,
+    PinkConcrete = 311,
+    GrayConcrete = 312,
+    LightGrayConcrete = 313,
+    CyanConcrete = 314,
+    PurpleConcrete = 315,
+    BlueConcrete = 316,
+    BrownConcrete = 317,
+    GreenConcrete = 318,
+    RedConcrete = 319,
+    BlackConcrete = 320,
+
+    // Terracotta
+    Terracotta = 321,
+    WhiteTerracotta = 322,
+    OrangeTerracotta = 323,
+    MagentaTerracotta = 324,
+    LightBlueTerracotta = 325,
+    YellowTerracotta = 326,
+    LimeTerracotta = 327,
+    PinkTerracotta = 328,
+    GrayTerracotta = 329,
+    LightGrayTerracotta = 330,
+    CyanTerracotta = 331,
+    PurpleTerracotta = 332,
+    BlueTerracotta = 333,
+    BrownTerracotta = 334,
+    GreenTerracotta = 335,
+    RedTerracotta = 336,
+    BlackTerracotta = 337,
+
+    // Stained glass and panes
+    OrangeStainedGlass = 338,
+    MagentaStainedGlass = 339,
+    LightBlueStainedGlass = 340,
+    YellowStainedGlass = 341,
+    LimeStainedGlass = 342,
+    PinkStainedGlass = 343,
+    GrayStainedGlass = 344,
+    LightGrayStainedGlass = 345,
+    CyanStainedGlass = 346,
+    PurpleStainedGlass = 347,
+    BlueStainedGlass = 348,
+    BrownStainedGlass = 349,
+    GreenStainedGlass = 350,
+    RedStainedGlass = 351,
+    BlackStainedGlass = 352,
+    GlassPane = 353,
+    WhiteStainedGlassPane = 354,
+    OrangeStainedGlassPane = 355,
+    MagentaStainedGlassPane = 356,
+    LightBlueStainedGlassPane = 357,
+    YellowStainedGlassPane = 358,
+    LimeStainedGlassPane = 359,
+    PinkStainedGlassPane = 360,
+    GrayStainedGlassPane = 361,
+    LightGrayStainedGlassPane = 362,
+    CyanStainedGlassPane = 363,
+    PurpleStainedGlassPane = 364,
+    BlueStainedGlassPane = 365,
+    BrownStainedGlassPane = 366,
+    GreenStainedGlassPane = 367,
+    RedStainedGlassPane = 368,
+    BlackStainedGlassPane = 369,
+
+    // Stone variants
+    PolishedGranite = 370,
+    PolishedDiorite = 371,
+    PolishedAndesite = 372,
+    CobbledDeepslate = 373,
+    PolishedDeepslate = 374,
+    DeepslateBricks = 375,
+    DeepslateTiles = 376,
+    MossyStoneBricks = 377,
+    CrackedStoneBricks = 378,
+    ChiseledStoneBricks = 379,
+    CutSandstone = 380,
+    ChiseledSandstone = 381,
+    SmoothSandstone = 382,
+    CutRedSandstone = 383,
+    ChiseledRedSandstone = 384,
+    SmoothRedSandstone = 385,
+    MudBricks = 386,
+    PackedMud = 387,
+
+    // More Nether blocks
+    SoulSoil = 388,
+    Glowstone = 389,
+    Basalt = 390,
+    PolishedBasalt = 391,
+    SmoothBasalt = 392,
+    Blackstone = 393,
+    PolishedBlackstone = 394,
+    PolishedBlackstoneBricks = 395,
+    GildedBlackstone = 396,
+    NetherQuartzOre = 397,
+    NetherGoldOre = 398,
+    QuartzBlock = 399,
+    QuartzPillar = 400,
+    MagmaBlock = 401,
+    CrimsonNylium = 402,
+    WarpedNylium = 403,
+    NetherWartBlock = 404,
+    WarpedWartBlock = 405,
+    Shroomlight = 406,
+    RedNetherBricks = 407,
+    CryingObsidian = 408,
+    AncientDebris = 409,
+    NetherBrickFence = 410,
+
+    // End
+    EndStone = 411,
+    EndStoneBricks = 412,
+    PurpurBlock = 413,
+    PurpurPillar = 414,
+
+    // Other building blocks
+    Bookshelf = 415,
+    SnowBlock = 416,
+    Ice = 417,
+    PackedIce = 418,
+    Prismarine = 419,
+    PrismarineBricks = 420,
+    DarkPrismarine = 421,
+    SeaLantern = 422,
+    CoalBlock = 423,
+    IronBlock = 424,
+    CopperBlock = 425,
+    GoldBlock = 426,
+    RedstoneBlock = 427,
+    EmeraldBlock = 428,
+    LapisBlock = 429,
+    DiamondBlock = 430,
+
+    // Stairs and slabs
+    CobblestoneStairs = 431,
+    CobblestoneSlab = 432,
+    StoneStairs = 433,
+    StoneBrickStairs = 434,
+    StoneBrickSlab = 435,
+    MossyCobblestoneStairs = 436,
+    MossyCobblestoneSlab = 437,
+    SandstoneStairs = 438,
+    SandstoneSlab = 439,
+    RedSandstoneStairs = 440,
+    RedSandstoneSlab = 441,
+    BrickStairs = 442,
+    BrickSlab = 443,
+    DeepslateBrickStairs = 444,
+    DeepslateBrickSlab = 445,
+    NetherBrickStairs = 446,
+    NetherBrickSlab = 447,
+    BlackstoneStairs = 448,
+    BlackstoneSlab = 449,
+    QuartzStairs = 450,
+    QuartzSlab = 451,
+    PurpurStairs = 452,
+    PurpurSlab = 453,
+    PrismarineStairs = 454,
+    PrismarineSlab = 455,
+    EndStoneBrickStairs = 456,
+    EndStoneBrickSlab = 457,
+    MudBrickStairs = 458,
+    MudBrickSlab = 459,
 }
 
 impl Default for BlockType {
@@ -99,184 +366,87 @@ impl Default for BlockType {
 }
 
 impl BlockType {
+    pub fn definition(self) -> &'static BlockDefinition {
+        BlockRegistry::global().get(self)
+    }
+
+    /// Namespaced name, e.g. `minecraft:stone`.
+    pub fn name(self) -> &'static str {
+        &self.definition().name
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        BlockRegistry::global().by_name(name)
+    }
+
+    /// Construct a BlockType from its numeric id; `None` for ids no block has.
+    pub fn from_id(id: u16) -> Option<Self> {
+        BlockRegistry::global().by_id(id)
+    }
+
+    /// The block a first-release save meant by variant position `position`.
+    pub fn from_legacy_position(position: u32) -> Option<Self> {
+        BlockType::ALL[..LEGACY_BLOCK_COUNT].get(position as usize).copied()
+    }
+
     pub fn is_transparent(self) -> bool {
-        matches!(
-            self,
-            BlockType::Air
-                | BlockType::CaveAir
-                | BlockType::VoidAir
-                | BlockType::Glass
-                | BlockType::WhiteStainedGlass
-                | BlockType::Water
-                | BlockType::Lava
-                | BlockType::OakLeaves
-        )
+        self.definition().transparent
     }
 
     pub fn is_solid(self) -> bool {
-        !matches!(
-            self,
-            BlockType::Air | BlockType::CaveAir | BlockType::VoidAir | BlockType::Water
-        )
+        self.definition().solid
     }
 
     pub fn is_liquid(self) -> bool {
         matches!(self, BlockType::Water | BlockType::Lava)
     }
 
+    /// Solid and filling the whole block space, so it hides the faces of its neighbours.
+    pub fn is_full_cube(self) -> bool {
+        self.definition().is_full_cube()
+    }
+
+    pub fn shape(self) -> BlockShape {
+        self.definition().shape
+    }
+
+    /// The state properties this block has; see `state::BlockState`.
+    pub fn properties(self) -> &'static [Property] {
+        &self.definition().properties
+    }
+
+    /// Block light given off by the block itself.
+    pub fn light_emission(self) -> u8 {
+        self.definition().light_emission
+    }
+
+    /// How much light is lost passing through the block; 15 blocks it entirely.
+    pub fn light_opacity(self) -> u8 {
+        self.definition().light_opacity()
+    }
+
     pub fn hardness(self) -> f32 {
-        match self {
-            BlockType::Air | BlockType::CaveAir | BlockType::VoidAir => 0.0,
-            BlockType::Bedrock => -1.0, // Unbreakable
-            BlockType::GrassBlock => 0.6,
-            BlockType::Dirt | BlockType::CoarseDirt | BlockType::Podzol => 0.5,
-            BlockType::Sand | BlockType::RedSand => 0.5,
-            BlockType::Gravel => 0.6,
-            BlockType::Stone => 1.5,
-            BlockType::Granite | BlockType::Diorite | BlockType::Andesite => 1.5,
-            BlockType::Deepslate => 3.0,
-            BlockType::CoalOre | BlockType::IronOre | BlockType::GoldOre => 3.0,
-            BlockType::DiamondOre | BlockType::EmeraldOre => 3.0,
-            BlockType::Cobblestone | BlockType::MossyCobblestone => 2.0,
-            BlockType::StoneBricks => 1.5,
-            BlockType::Sandstone | BlockType::RedSandstone => 0.8,
-            BlockType::Bricks => 2.0,
-            BlockType::OakLog
-            | BlockType::SpruceLog
-            | BlockType::BirchLog
-            | BlockType::JungleLog
-            | BlockType::AcaciaLog
-            | BlockType::DarkOakLog => 2.0,
-            BlockType::OakPlanks | BlockType::SprucePlanks | BlockType::BirchPlanks => 2.0,
-            BlockType::OakLeaves => 0.2,
-            BlockType::Glass | BlockType::WhiteStainedGlass => 0.3,
-            BlockType::Netherrack => 0.4,
-            BlockType::NetherBricks => 2.0,
-            BlockType::SoulSand => 0.5,
-            BlockType::Obsidian => 50.0,
-            BlockType::CraftingTable => 2.5,
-            BlockType::Furnace => 3.5,
-            BlockType::Chest => 2.5,
-            _ => 1.0,
-        }
+        self.definition().hardness
     }
 
     pub fn get_color(self) -> [f32; 3] {
-        match self {
-            BlockType::Air | BlockType::CaveAir | BlockType::VoidAir => [0.0, 0.0, 0.0],
-            BlockType::GrassBlock => [0.35, 0.65, 0.25],
-            BlockType::Dirt | BlockType::CoarseDirt => [0.55, 0.35, 0.2],
-            BlockType::Sand => [0.9, 0.85, 0.6],
-            BlockType::RedSand => [0.8, 0.5, 0.3],
-            BlockType::Stone => [0.5, 0.5, 0.5],
-            BlockType::Granite => [0.6, 0.4, 0.35],
-            BlockType::Diorite => [0.85, 0.85, 0.85],
-            BlockType::Andesite => [0.55, 0.55, 0.55],
-            BlockType::Deepslate => [0.3, 0.3, 0.35],
-            BlockType::CoalOre => [0.4, 0.4, 0.4],
-            BlockType::IronOre => [0.65, 0.6, 0.55],
-            BlockType::GoldOre => [0.9, 0.8, 0.3],
-            BlockType::DiamondOre => [0.4, 0.7, 0.8],
-            BlockType::EmeraldOre => [0.3, 0.8, 0.4],
-            BlockType::Cobblestone => [0.45, 0.45, 0.45],
-            BlockType::StoneBricks => [0.5, 0.5, 0.5],
-            BlockType::Sandstone => [0.85, 0.8, 0.6],
-            BlockType::RedSandstone => [0.75, 0.45, 0.3],
-            BlockType::Bricks => [0.6, 0.3, 0.2],
-            BlockType::OakLog => [0.4, 0.3, 0.2],
-            BlockType::SpruceLog => [0.3, 0.25, 0.2],
-            BlockType::BirchLog => [0.85, 0.85, 0.8],
-            BlockType::OakPlanks => [0.65, 0.5, 0.3],
-            BlockType::SprucePlanks => [0.45, 0.35, 0.25],
-            BlockType::BirchPlanks => [0.75, 0.7, 0.55],
-            BlockType::OakLeaves => [0.2, 0.6, 0.2],
-            BlockType::Glass => [0.85, 0.95, 1.0],
-            BlockType::WhiteStainedGlass => [0.95, 0.95, 1.0],
-            BlockType::Netherrack => [0.6, 0.25, 0.25],
-            BlockType::NetherBricks => [0.3, 0.15, 0.2],
-            BlockType::SoulSand => [0.35, 0.3, 0.25],
-            BlockType::Obsidian => [0.05, 0.05, 0.15],
-            BlockType::Bedrock => [0.2, 0.2, 0.2],
-            BlockType::Water => [0.1, 0.3, 0.8],
-            BlockType::Lava => [0.9, 0.4, 0.1],
-            BlockType::CraftingTable => [0.6, 0.45, 0.3],
-            BlockType::Furnace => [0.4, 0.4, 0.4],
-            BlockType::Chest => [0.55, 0.4, 0.25],
-            _ => [0.7, 0.7, 0.7],
-        }
+        self.definition().color
     }
+}
 
-        /// Construct a BlockType from its numeric id. Unknown ids map to Air.
-        pub fn from_id(id: u16) -> Self {
-            match id {
-                0 => BlockType::Air,
-                1 => BlockType::CaveAir,
-                2 => BlockType::VoidAir,
-                3 => BlockType::GrassBlock,
-                4 => BlockType::Dirt,
-                5 => BlockType::CoarseDirt,
-                6 => BlockType::Podzol,
-                7 => BlockType::Mycelium,
-                8 => BlockType::RootedDirt,
-                9 => BlockType::Mud,
-                10 => BlockType::Clay,
-                11 => BlockType::Sand,
-                12 => BlockType::RedSand,
-                13 => BlockType::Gravel,
-                14 => BlockType::Stone,
-                15 => BlockType::Granite,
-                16 => BlockType::Diorite,
-                17 => BlockType::Andesite,
-                18 => BlockType::Deepslate,
-                19 => BlockType::Calcite,
-                20 => BlockType::Tuff,
-                21 => BlockType::DripstoneBlock,
-                81 => BlockType::CoalOre,
-                82 => BlockType::DeepslateCoalOre,
-                83 => BlockType::IronOre,
-                84 => BlockType::DeepslateIronOre,
-                85 => BlockType::CopperOre,
-                86 => BlockType::DeepslateCopperOre,
-                87 => BlockType::GoldOre,
-                88 => BlockType::DeepslateGoldOre,
-                89 => BlockType::RedstoneOre,
-                90 => BlockType::DeepslateRedstoneOre,
-                91 => BlockType::EmeraldOre,
-                92 => BlockType::DeepslateEmeraldOre,
-                93 => BlockType::LapisOre,
-                94 => BlockType::DeepslateLapisOre,
-                95 => BlockType::DiamondOre,
-                96 => BlockType::DeepslateDiamondOre,
-                100 => BlockType::Cobblestone,
-                101 => BlockType::MossyCobblestone,
-                102 => BlockType::StoneBricks,
-                106 => BlockType::SmoothStone,
-                109 => BlockType::Sandstone,
-                112 => BlockType::RedSandstone,
-                115 => BlockType::Bricks,
-                118 => BlockType::OakLog,
-                119 => BlockType::SpruceLog,
-                120 => BlockType::BirchLog,
-                121 => BlockType::JungleLog,
-                122 => BlockType::AcaciaLog,
-                123 => BlockType::DarkOakLog,
-                126 => BlockType::OakLeaves,
-                134 => BlockType::OakPlanks,
-                135 => BlockType::SprucePlanks,
-                136 => BlockType::BirchPlanks,
-                182 => BlockType::Glass,
-                183 => BlockType::WhiteStainedGlass,
-                216 => BlockType::Netherrack,
-                217 => BlockType::NetherBricks,
-                222 => BlockType::SoulSand,
-                235 => BlockType::Obsidian,
-                236 => BlockType::Bedrock,
-                237 => BlockType::Water,
-                238 => BlockType::Lava,
-                239 => BlockType::CraftingTable,
-                240 => BlockType::Furnace,
-                241 => BlockType::Chest,
-                _ => BlockType::Air,
-            }
-        }
+/// Serde helpers for saving a block as its numeric id, which stays the same when
+/// variants are added anywhere in the list. Use with `#[serde(with = "...")]`.
+pub mod by_id {
+    use super::BlockType;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(block: &BlockType, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u16(*block as u16)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BlockType, D::Error> {
+        let id = u16::deserialize(deserializer)?;
+        BlockType::from_id(id).ok_or_else(|| D::Error::custom(format!("unknown block id {}", id)))
+    }
 }
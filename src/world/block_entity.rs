@@ -0,0 +1,208 @@
+#![allow(dead_code)]
+/// State attached to single blocks: chest contents and furnaces.
+///
+/// A block entity lives in its chunk, keyed by the block's position in the
+/// chunk, and is saved along with the chunk's blocks. `World::set_state_at`
+/// gives a block its entity when the block is placed and throws the entity away
+/// when the block is replaced, so code that wants a chest's contents must take
+/// them before breaking it. Furnaces smelt one game tick at a time, going by
+/// the recipes and fuels in `data/smelting.json`.
+use super::block::BlockType;
+use crate::inventory::{Inventory, ItemStack};
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+pub const CHEST_SIZE: usize = 27;
+
+pub const SMELTING_PATH: &str = "data/smelting.json";
+const DEFAULT_SMELTING: &str = include_str!("../../data/smelting.json");
+
+static SMELTING: OnceLock<SmeltingRecipes> = OnceLock::new();
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum BlockEntity {
+    Chest(Chest),
+    Furnace(Furnace),
+}
+
+impl BlockEntity {
+    /// The entity a freshly placed `block` starts with, if it has one.
+    pub fn for_block(block: BlockType) -> Option<Self> {
+        match block {
+            BlockType::Chest => Some(BlockEntity::Chest(Chest::default())),
+            BlockType::Furnace => Some(BlockEntity::Furnace(Furnace::default())),
+            _ => None,
+        }
+    }
+
+    /// Every item held, for dropping when the block is broken.
+    pub fn into_contents(self) -> Vec<ItemStack> {
+        match self {
+            BlockEntity::Chest(chest) => chest.inventory.slots.into_iter().flatten().collect(),
+            BlockEntity::Furnace(furnace) => [furnace.input, furnace.fuel, furnace.output]
+                .into_iter()
+                .flatten()
+                .collect(),
+        }
+    }
+
+    /// Advance by one game tick. Returns whether anything changed.
+    pub fn tick(&mut self) -> bool {
+        match self {
+            BlockEntity::Chest(_) => false,
+            BlockEntity::Furnace(furnace) => furnace.tick(SmeltingRecipes::global()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Chest {
+    pub inventory: Inventory,
+}
+
+impl Default for Chest {
+    fn default() -> Self {
+        Self {
+            inventory: Inventory::new(CHEST_SIZE),
+        }
+    }
+}
+
+/// Durations are in game ticks.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Furnace {
+    pub input: Option<ItemStack>,
+    pub fuel: Option<ItemStack>,
+    pub output: Option<ItemStack>,
+    /// Ticks the current piece of fuel keeps burning.
+    pub burn_time: u32,
+    /// What `burn_time` started at, for drawing the flame.
+    pub burn_duration: u32,
+    /// Ticks the current input item has been cooking.
+    pub cook_progress: u32,
+    /// Ticks the current input item needs.
+    pub cook_time: u32,
+}
+
+impl Furnace {
+    pub fn is_burning(&self) -> bool {
+        self.burn_time > 0
+    }
+
+    /// The recipe for the input item, if the output slot has room for its result.
+    fn ready_recipe<'a>(&self, recipes: &'a SmeltingRecipes) -> Option<&'a SmeltingRecipe> {
+        let recipe = recipes.recipe_for(&self.input.as_ref()?.id)?;
+        let fits = match &self.output {
+            None => true,
+            Some(output) => {
+                output.id == recipe.result.id && output.count + recipe.result.count <= output.max_stack_size()
+            }
+        };
+        fits.then_some(recipe)
+    }
+
+    fn tick(&mut self, recipes: &SmeltingRecipes) -> bool {
+        let was_burning = self.is_burning();
+        let progress_before = self.cook_progress;
+        if self.burn_time > 0 {
+            self.burn_time -= 1;
+        }
+
+        let recipe = self.ready_recipe(recipes);
+        if self.burn_time == 0 && recipe.is_some() {
+            let fuel_ticks = self.fuel.as_ref().and_then(|fuel| recipes.burn_time(&fuel.id));
+            if let Some(ticks) = fuel_ticks {
+                take_one(&mut self.fuel);
+                self.burn_time = ticks;
+                self.burn_duration = ticks;
+            }
+        }
+
+        match recipe {
+            Some(recipe) if self.is_burning() => {
+                self.cook_time = recipe.cook_time;
+                self.cook_progress += 1;
+                if self.cook_progress >= recipe.cook_time {
+                    self.cook_progress = 0;
+                    take_one(&mut self.input);
+                    match &mut self.output {
+                        Some(output) => output.count += recipe.result.count,
+                        None => self.output = Some(recipe.result.clone()),
+                    }
+                }
+            }
+            // Without heat, or with nothing to cook, progress cools back down.
+            _ => self.cook_progress = self.cook_progress.saturating_sub(2),
+        }
+
+        was_burning || self.is_burning() || progress_before != self.cook_progress
+    }
+}
+
+fn take_one(slot: &mut Option<ItemStack>) {
+    if let Some(stack) = slot {
+        stack.count = stack.count.saturating_sub(1);
+        if stack.count == 0 {
+            *slot = None;
+        }
+    }
+}
+
+fn default_cook_time() -> u32 {
+    200
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SmeltingRecipe {
+    pub input: String,
+    pub result: ItemStack,
+    #[serde(default = "default_cook_time")]
+    pub cook_time: u32,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SmeltingRecipes {
+    recipes: Vec<SmeltingRecipe>,
+    /// Ticks one of each fuel item burns for.
+    fuels: HashMap<String, u32>,
+}
+
+impl SmeltingRecipes {
+    pub fn from_json(contents: &str) -> anyhow::Result<Self> {
+        Ok(serde_json::from_str(contents)?)
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("reading {}", path.display()))?;
+        Self::from_json(&contents).with_context(|| format!("loading {}", path.display()))
+    }
+
+    /// `SMELTING_PATH` relative to the working directory if present, otherwise the built-in recipes.
+    pub fn load_or_default() -> Self {
+        let path = Path::new(SMELTING_PATH);
+        if path.exists() {
+            match Self::load(path) {
+                Ok(recipes) => return recipes,
+                Err(err) => log::warn!("Failed to load {}: {:#}; using built-in smelting recipes", SMELTING_PATH, err),
+            }
+        }
+        Self::from_json(DEFAULT_SMELTING).expect("Built-in smelting recipes are invalid")
+    }
+
+    /// The recipes shared by every furnace, loaded on first use.
+    pub fn global() -> &'static Self {
+        SMELTING.get_or_init(Self::load_or_default)
+    }
+
+    pub fn recipe_for(&self, input: &str) -> Option<&SmeltingRecipe> {
+        self.recipes.iter().find(|recipe| recipe.input == input)
+    }
+
+    pub fn burn_time(&self, fuel: &str) -> Option<u32> {
+        self.fuels.get(fuel).copied()
+    }
+}
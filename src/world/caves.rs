@@ -0,0 +1,220 @@
+#![allow(dead_code)]
+/// Cave and ravine carving.
+///
+/// Caves come from 3D noise: large "cheese" caverns where one low-frequency
+/// field is high, and winding "spaghetti" tunnels where two fields are both
+/// close to zero. Ravines are traced by a carver seeded from the chunk they
+/// start in; every chunk replays the carvers of its neighbours within
+/// `RAVINE_REACH`, so a ravine crossing a border is cut identically on both
+/// sides. Carved blocks become `CaveAir`, or water and lava below the
+/// configured levels.
+use super::block::BlockType;
+use super::chunk::{Chunk, CHUNK_SIZE, WORLD_HEIGHT};
+use super::generator::{chunk_rng, SEA_LEVEL};
+use noise::{NoiseFn, Perlin};
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::f64::consts::{PI, TAU};
+
+/// How many chunks away a ravine may start and still reach this chunk.
+const RAVINE_REACH: i32 = 5;
+const RAVINE_CHANCE: f64 = 0.02;
+const RAVINE_SALT: u64 = 0;
+/// Caverns are suppressed this many blocks below the surface so they rarely break through.
+const CAVERN_ROOF: f64 = 8.0;
+/// Solid blocks kept between open caves and the floor of an ocean or lake.
+const SEABED_ROOF: usize = 4;
+
+#[derive(Debug, Clone, Copy)]
+pub struct CaveConfig {
+    /// Carved space at or below this height fills with lava.
+    pub lava_level: usize,
+    /// Carved space below this height floods with water where the aquifer noise is high.
+    pub aquifer_level: usize,
+}
+
+impl Default for CaveConfig {
+    fn default() -> Self {
+        Self {
+            lava_level: 10,
+            aquifer_level: 40,
+        }
+    }
+}
+
+pub struct CaveCarver {
+    cheese: Perlin,
+    spaghetti_a: Perlin,
+    spaghetti_b: Perlin,
+    aquifer: Perlin,
+    seed: u32,
+    config: CaveConfig,
+}
+
+impl CaveCarver {
+    pub fn new(seed: u32, config: CaveConfig) -> Self {
+        Self {
+            cheese: Perlin::new(seed.wrapping_add(4)),
+            spaghetti_a: Perlin::new(seed.wrapping_add(5)),
+            spaghetti_b: Perlin::new(seed.wrapping_add(6)),
+            aquifer: Perlin::new(seed.wrapping_add(7)),
+            seed,
+            config,
+        }
+    }
+
+    pub fn config(&self) -> &CaveConfig {
+        &self.config
+    }
+
+    /// Carve caves and ravines into a chunk whose terrain is already in place.
+    /// `height_map` holds the surface height of each column, indexed `[x][z]`.
+    pub fn carve_chunk(&self, chunk: &mut Chunk, height_map: &[[usize; CHUNK_SIZE]; CHUNK_SIZE]) {
+        let chunk_world_x = chunk.x * CHUNK_SIZE as i32;
+        let chunk_world_z = chunk.z * CHUNK_SIZE as i32;
+
+        for x in 0..CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE {
+                let world_x = chunk_world_x + x as i32;
+                let world_z = chunk_world_z + z as i32;
+                let surface = height_map[x][z];
+                let top = Self::carve_limit(surface);
+
+                for y in 1..top {
+                    if self.is_cave(world_x, y, world_z, surface) {
+                        self.carve_block(chunk, x, y, z);
+                    }
+                }
+            }
+        }
+
+        self.carve_ravines(chunk, height_map);
+    }
+
+    /// Highest y (exclusive) that may be carved in a column with the given surface.
+    fn carve_limit(surface: usize) -> usize {
+        if surface <= SEA_LEVEL {
+            surface.saturating_sub(SEABED_ROOF)
+        } else {
+            surface.min(WORLD_HEIGHT)
+        }
+    }
+
+    fn is_cave(&self, world_x: i32, y: usize, world_z: i32, surface: usize) -> bool {
+        let (x, y_f, z) = (world_x as f64, y as f64, world_z as f64);
+
+        let depth = surface as f64 - y_f;
+        let cheese_threshold = 0.45 + (CAVERN_ROOF - depth).max(0.0) * 0.05;
+        if self.cheese.get([x / 64.0, y_f / 40.0, z / 64.0]) > cheese_threshold {
+            return true;
+        }
+
+        let a = self.spaghetti_a.get([x / 48.0, y_f / 32.0, z / 48.0]);
+        if a.abs() >= 0.05 {
+            return false;
+        }
+        let b = self.spaghetti_b.get([x / 48.0, y_f / 32.0, z / 48.0]);
+        b.abs() < 0.05
+    }
+
+    /// What carved space at this position is filled with.
+    fn cave_fill(&self, world_x: i32, y: usize, world_z: i32) -> BlockType {
+        if y <= self.config.lava_level {
+            BlockType::Lava
+        } else if y < self.config.aquifer_level
+            && self
+                .aquifer
+                .get([world_x as f64 / 96.0, y as f64 / 64.0, world_z as f64 / 96.0])
+                > 0.3
+        {
+            BlockType::Water
+        } else {
+            BlockType::CaveAir
+        }
+    }
+
+    fn carve_block(&self, chunk: &mut Chunk, x: usize, y: usize, z: usize) {
+        let existing = chunk.get_block(x, y, z);
+        if existing == BlockType::Bedrock || existing.is_liquid() || !existing.is_solid() {
+            return;
+        }
+        let world_x = chunk.x * CHUNK_SIZE as i32 + x as i32;
+        let world_z = chunk.z * CHUNK_SIZE as i32 + z as i32;
+        let fill = self.cave_fill(world_x, y, world_z);
+        chunk.set_block(x, y, z, fill);
+    }
+
+    fn carve_ravines(&self, chunk: &mut Chunk, height_map: &[[usize; CHUNK_SIZE]; CHUNK_SIZE]) {
+        for offset_x in -RAVINE_REACH..=RAVINE_REACH {
+            for offset_z in -RAVINE_REACH..=RAVINE_REACH {
+                let start_x = chunk.x + offset_x;
+                let start_z = chunk.z + offset_z;
+                let mut rng = chunk_rng(self.seed, start_x, start_z, RAVINE_SALT);
+                if rng.gen_bool(RAVINE_CHANCE) {
+                    self.trace_ravine(&mut rng, start_x, start_z, chunk, height_map);
+                }
+            }
+        }
+    }
+
+    /// Walk a ravine path from its start chunk and carve the parts that fall inside `chunk`.
+    /// The random stream is consumed the same way whichever chunk is being carved.
+    fn trace_ravine(
+        &self,
+        rng: &mut StdRng,
+        start_chunk_x: i32,
+        start_chunk_z: i32,
+        chunk: &mut Chunk,
+        height_map: &[[usize; CHUNK_SIZE]; CHUNK_SIZE],
+    ) {
+        let mut x = (start_chunk_x * CHUNK_SIZE as i32) as f64 + rng.gen_range(0.0..CHUNK_SIZE as f64);
+        let mut z = (start_chunk_z * CHUNK_SIZE as i32) as f64 + rng.gen_range(0.0..CHUNK_SIZE as f64);
+        let mut y = rng.gen_range(20.0..56.0);
+        let mut yaw: f64 = rng.gen_range(0.0..TAU);
+        let mut pitch: f64 = rng.gen_range(-0.1..0.1);
+        let length = rng.gen_range(40..72);
+        let width = rng.gen_range(1.5..3.0);
+
+        let min_x = (chunk.x * CHUNK_SIZE as i32) as f64;
+        let min_z = (chunk.z * CHUNK_SIZE as i32) as f64;
+        let max_x = min_x + CHUNK_SIZE as f64;
+        let max_z = min_z + CHUNK_SIZE as f64;
+
+        for step in 0..length {
+            x += yaw.cos();
+            z += yaw.sin();
+            y += pitch;
+            yaw += rng.gen_range(-0.08..0.08);
+            pitch = pitch * 0.7 + rng.gen_range(-0.05..0.05);
+
+            // Narrow at both ends, widest in the middle, and much taller than wide.
+            let radius = 1.0 + width * (step as f64 / length as f64 * PI).sin();
+            let half_height = radius * 3.0;
+
+            if x + radius < min_x || x - radius >= max_x || z + radius < min_z || z - radius >= max_z {
+                continue;
+            }
+
+            let from_x = ((x - radius).floor() - min_x).max(0.0) as usize;
+            let to_x = ((x + radius).ceil() - min_x).min(CHUNK_SIZE as f64) as usize;
+            let from_z = ((z - radius).floor() - min_z).max(0.0) as usize;
+            let to_z = ((z + radius).ceil() - min_z).min(CHUNK_SIZE as f64) as usize;
+            let from_y = (y - half_height).floor().max(1.0) as usize;
+            let to_y = (y + half_height).ceil().max(1.0) as usize;
+
+            for local_x in from_x..to_x {
+                let dx = (min_x + local_x as f64 + 0.5 - x) / radius;
+                for local_z in from_z..to_z {
+                    let dz = (min_z + local_z as f64 + 0.5 - z) / radius;
+                    let top = Self::carve_limit(height_map[local_x][local_z]).min(to_y);
+                    for block_y in from_y..top {
+                        let dy = (block_y as f64 + 0.5 - y) / half_height;
+                        if dx * dx + dy * dy + dz * dz < 1.0 {
+                            self.carve_block(chunk, local_x, block_y, local_z);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,18 +1,50 @@
 #![allow(dead_code)]
+use super::biome::Biome;
 use super::block::BlockType;
-use noise::{NoiseFn, Perlin};
+use super::block_entity::BlockEntity;
+use super::decoration::PendingBlock;
+use super::fluid::{self, Fluid, TickQueue};
+use super::generator::{create_generator, WorldGenerator, DEFAULT_GENERATOR};
+use super::light::{self, BorderLight, LightKind, LightMap, WorldLight};
+use super::meta::{unix_now, WorldMeta};
+use super::provider::ChunkProvider;
+use super::region::{write_atomic, RegionStorage};
+use super::section::{ChunkSection, SECTION_SIZE};
+use super::shape::{self, BlockBox};
+use super::state::{BlockState, Facing};
+use bincode::Options;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 pub const CHUNK_SIZE: usize = 16;
 pub const WORLD_HEIGHT: usize = 256;
 
+pub const SECTION_COUNT: usize = WORLD_HEIGHT / SECTION_SIZE;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Chunk {
     pub x: i32,
     pub z: i32,
-    pub blocks: Vec<BlockType>,
+    /// Vertical 16-block sections from the bottom up; `None` means all air.
+    sections: Vec<Option<ChunkSection>>,
+    /// Biome id per column, indexed `x + z * CHUNK_SIZE`.
+    biomes: Vec<u8>,
+    /// Keyed by position within the chunk; see `block_entity`.
+    block_entities: HashMap<(u8, u8, u8), BlockEntity>,
+    /// Not saved; recomputed by `light::light_chunk` when the chunk is loaded.
+    #[serde(skip)]
+    light: LightMap,
+}
+
+/// On-disk layout of one-file-per-chunk worlds, converted by the version 0
+/// migration in `meta`. Blocks are variant positions; see `LEGACY_BLOCK_COUNT`.
+#[derive(Deserialize)]
+struct LegacyChunk {
+    x: i32,
+    z: i32,
+    blocks: Vec<u32>,
 }
 
 impl Chunk {
@@ -20,245 +52,443 @@ impl Chunk {
         Self {
             x,
             z,
-            blocks: vec![BlockType::Air; CHUNK_SIZE * WORLD_HEIGHT * CHUNK_SIZE],
+            sections: vec![None; SECTION_COUNT],
+            biomes: vec![Biome::default() as u8; CHUNK_SIZE * CHUNK_SIZE],
+            block_entities: HashMap::new(),
+            light: LightMap::default(),
         }
     }
 
     pub fn get_block(&self, x: usize, y: usize, z: usize) -> BlockType {
+        self.get_state(x, y, z).block
+    }
+
+    pub fn get_state(&self, x: usize, y: usize, z: usize) -> BlockState {
         if x >= CHUNK_SIZE || y >= WORLD_HEIGHT || z >= CHUNK_SIZE {
-            return BlockType::Air;
+            return BlockState::default();
+        }
+        match &self.sections[y / SECTION_SIZE] {
+            Some(section) => section.get(x, y % SECTION_SIZE, z),
+            None => BlockState::default(),
         }
-        self.blocks[x + y * CHUNK_SIZE + z * CHUNK_SIZE * WORLD_HEIGHT]
     }
 
+    /// Set a block in its default state.
     pub fn set_block(&mut self, x: usize, y: usize, z: usize, block: BlockType) {
+        self.set_state(x, y, z, BlockState::new(block));
+    }
+
+    pub fn set_state(&mut self, x: usize, y: usize, z: usize, state: BlockState) {
+        if x >= CHUNK_SIZE || y >= WORLD_HEIGHT || z >= CHUNK_SIZE {
+            return;
+        }
+        let slot = &mut self.sections[y / SECTION_SIZE];
+        match slot {
+            Some(section) => {
+                section.set(x, y % SECTION_SIZE, z, state);
+                if section.is_empty() {
+                    *slot = None;
+                }
+            }
+            None if state.block == BlockType::Air => {}
+            None => {
+                let mut section = ChunkSection::new();
+                section.set(x, y % SECTION_SIZE, z, state);
+                *slot = Some(section);
+            }
+        }
+    }
+
+    /// Level of a fluid block; 0 for sources and everything else. See `fluid`.
+    pub fn fluid_level(&self, x: usize, y: usize, z: usize) -> u8 {
+        self.get_state(x, y, z).level()
+    }
+
+    pub fn biome(&self, x: usize, z: usize) -> Biome {
+        if x >= CHUNK_SIZE || z >= CHUNK_SIZE {
+            return Biome::default();
+        }
+        Biome::from_id(self.biomes[x + z * CHUNK_SIZE])
+    }
+
+    pub fn set_biome(&mut self, x: usize, z: usize, biome: Biome) {
+        if x >= CHUNK_SIZE || z >= CHUNK_SIZE {
+            return;
+        }
+        self.biomes[x + z * CHUNK_SIZE] = biome as u8;
+    }
+
+    pub fn block_entity(&self, x: usize, y: usize, z: usize) -> Option<&BlockEntity> {
+        self.block_entities.get(&(x as u8, y as u8, z as u8))
+    }
+
+    pub fn block_entity_mut(&mut self, x: usize, y: usize, z: usize) -> Option<&mut BlockEntity> {
+        self.block_entities.get_mut(&(x as u8, y as u8, z as u8))
+    }
+
+    /// Attach `entity` to the block at a position, or remove what is there with `None`.
+    /// Returns the entity that was there before.
+    pub fn set_block_entity(&mut self, x: usize, y: usize, z: usize, entity: Option<BlockEntity>) -> Option<BlockEntity> {
+        if x >= CHUNK_SIZE || y >= WORLD_HEIGHT || z >= CHUNK_SIZE {
+            return None;
+        }
+        let key = (x as u8, y as u8, z as u8);
+        match entity {
+            Some(entity) => self.block_entities.insert(key, entity),
+            None => self.block_entities.remove(&key),
+        }
+    }
+
+    /// Advance every block entity by one game tick. Returns whether any changed.
+    pub fn tick_block_entities(&mut self) -> bool {
+        let mut changed = false;
+        for entity in self.block_entities.values_mut() {
+            changed |= entity.tick();
+        }
+        changed
+    }
+
+    pub fn light(&self, kind: LightKind, x: usize, y: usize, z: usize) -> u8 {
+        if x >= CHUNK_SIZE || y >= WORLD_HEIGHT || z >= CHUNK_SIZE {
+            return 0;
+        }
+        self.light.get(kind, x, y, z)
+    }
+
+    pub fn set_light(&mut self, kind: LightKind, x: usize, y: usize, z: usize, level: u8) {
         if x >= CHUNK_SIZE || y >= WORLD_HEIGHT || z >= CHUNK_SIZE {
             return;
         }
-        self.blocks[x + y * CHUNK_SIZE + z * CHUNK_SIZE * WORLD_HEIGHT] = block;
+        self.light.set(kind, x, y, z, level);
+    }
+
+    /// False until `light::light_chunk` has run on this chunk.
+    pub fn is_lit(&self) -> bool {
+        !self.light.is_empty()
+    }
+
+    /// Make the whole chunk dark, ready to be lit from scratch.
+    pub fn reset_light(&mut self) {
+        self.light = LightMap::dark();
+    }
+
+    /// True if the 16-high section at `index` holds nothing but air.
+    pub fn is_section_empty(&self, index: usize) -> bool {
+        self.sections.get(index).map_or(true, |s| s.is_none())
+    }
+
+    /// Approximate memory used by this chunk in bytes.
+    pub fn memory_usage(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.sections.capacity() * std::mem::size_of::<Option<ChunkSection>>()
+            + self.sections.iter().flatten().map(|s| s.heap_size()).sum::<usize>()
+            + self.light.heap_size()
+            + self.block_entities.len() * std::mem::size_of::<((u8, u8, u8), BlockEntity)>()
+    }
+
+    fn is_valid(&self) -> bool {
+        self.sections.len() == SECTION_COUNT
+            && self.biomes.len() == CHUNK_SIZE * CHUNK_SIZE
+            && self.sections.iter().flatten().all(|s| s.is_valid())
+    }
+
+    pub fn encode(&self) -> bincode::Result<Vec<u8>> {
+        bincode::serialize(self)
+    }
+
+    /// Decode a chunk file from a version 0 world.
+    pub fn decode_legacy(data: &[u8]) -> Option<Self> {
+        let legacy: LegacyChunk = bincode::deserialize(data).ok()?;
+        if legacy.blocks.len() != CHUNK_SIZE * WORLD_HEIGHT * CHUNK_SIZE {
+            return None;
+        }
+        let mut chunk = Chunk::new(legacy.x, legacy.z);
+        for (index, position) in legacy.blocks.into_iter().enumerate() {
+            let x = index % CHUNK_SIZE;
+            let y = (index / CHUNK_SIZE) % WORLD_HEIGHT;
+            let z = index / (CHUNK_SIZE * WORLD_HEIGHT);
+            chunk.set_block(x, y, z, BlockType::from_legacy_position(position)?);
+        }
+        Some(chunk)
+    }
+
+    /// Decode a chunk record. Chunks from older world formats are converted by the
+    /// `meta` migrations before a world is opened, so anything else is damage.
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        let chunk: Chunk = bincode::DefaultOptions::new()
+            .with_fixint_encoding()
+            .deserialize(data)
+            .ok()?;
+        chunk.is_valid().then_some(chunk)
     }
 }
 
+/// Which chunks `World::unload_chunks` keeps in memory.
+#[derive(Debug, Clone, Copy)]
+pub struct RetentionPolicy {
+    /// Chunks further than this many chunks from every center are unloaded.
+    pub radius: i32,
+    /// Upper bound on estimated chunk memory; the furthest chunks are evicted first.
+    pub max_memory_bytes: Option<usize>,
+}
+
+/// Chunks this close to a center are never evicted by the memory cap, so the
+/// player always has terrain to stand on.
+const MIN_RETAINED_RADIUS: i32 = 1;
+
+/// How long a chunk whose record couldn't be read is left alone before trying again.
+const LOAD_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Gap left between a moving box and the block it stops against.
+const COLLISION_EPSILON: f32 = 0.001;
+
 pub struct World {
     chunks: HashMap<(i32, i32), Chunk>,
-    noise: Perlin,
-    seed: u32,
+    generator: Arc<dyn WorldGenerator>,
+    meta: WorldMeta,
     world_dir: Option<std::path::PathBuf>,
+    regions: Option<Arc<Mutex<RegionStorage>>>,
+    provider: ChunkProvider,
     dirty_chunks: HashSet<(i32, i32)>,
+    /// Decoration blocks waiting for their chunk to be loaded, keyed by chunk.
+    pending_blocks: HashMap<(i32, i32), Vec<PendingBlock>>,
+    pending_blocks_dirty: bool,
+    /// Loaded chunks whose light changed since the last `take_relit_chunks`.
+    relit_chunks: HashSet<(i32, i32)>,
+    /// Game ticks run since the world was opened.
+    tick_count: u64,
+    fluid_ticks: TickQueue,
+    /// Chunks whose blocks were changed by the current `tick`.
+    ticked_chunks: HashSet<(i32, i32)>,
+    /// Chunks whose region record failed to read, and when to try them again.
+    /// They are neither generated nor saved in the meantime.
+    failed_loads: HashMap<(i32, i32), Instant>,
 }
 
+const PENDING_BLOCKS_FILE: &str = "pending_blocks.bin";
+
 impl World {
-    pub fn new(world_dir: Option<std::path::PathBuf>, seed: Option<u32>) -> Self {
-        // If a world dir is provided, try to read seed from metadata; otherwise use provided seed or random
-        let mut used_seed = seed.unwrap_or_else(|| rand::random());
-        if let Some(ref dir) = world_dir {
-            let mut meta_path = dir.clone();
-            meta_path.push("world_meta.json");
-            if let Ok(contents) = std::fs::read_to_string(&meta_path) {
-                if let Ok(meta) = serde_json::from_str::<serde_json::Value>(&contents) {
-                    if let Some(s) = meta.get("seed").and_then(|v| v.as_u64()) {
-                        used_seed = s as u32;
-                    }
-                }
-            } else {
-                // Save metadata with chosen seed
-                let meta = serde_json::json!({ "seed": used_seed });
-                let _ = std::fs::create_dir_all(dir);
-                let _ = std::fs::write(&meta_path, serde_json::to_string_pretty(&meta).unwrap());
-            }
-        }
+    pub fn new(world_dir: Option<std::path::PathBuf>, seed: Option<u32>) -> anyhow::Result<Self> {
+        Self::with_generator(world_dir, seed, DEFAULT_GENERATOR, serde_json::Value::Null)
+    }
 
-        Self {
+    /// Open a world, or create it with the given seed and generator if it has no metadata
+    /// yet. An existing world keeps the seed and generator recorded in its metadata.
+    /// Fails if the metadata can't be read or migrated, or names an unknown generator.
+    pub fn with_generator(
+        world_dir: Option<std::path::PathBuf>,
+        seed: Option<u32>,
+        generator_name: &str,
+        generator_options: serde_json::Value,
+    ) -> anyhow::Result<Self> {
+        let existing = match world_dir {
+            Some(ref dir) => WorldMeta::load(dir)?,
+            None => None,
+        };
+        let is_new = existing.is_none();
+        let meta = existing.unwrap_or_else(|| {
+            WorldMeta::new(
+                seed.unwrap_or_else(rand::random),
+                generator_name,
+                generator_options,
+            )
+        });
+
+        let generator = create_generator(&meta.generator, meta.seed, &meta.generator_options)?;
+
+        let regions = world_dir
+            .as_ref()
+            .map(|dir| Arc::new(Mutex::new(RegionStorage::new(dir.join("regions")))));
+
+        let pending_blocks = world_dir
+            .as_ref()
+            .and_then(|dir| std::fs::read(dir.join(PENDING_BLOCKS_FILE)).ok())
+            .and_then(|data| bincode::deserialize(&data).ok())
+            .unwrap_or_default();
+
+        let provider = ChunkProvider::new(
+            generator.clone(),
+            regions.clone(),
+            ChunkProvider::default_worker_count(),
+        );
+
+        let mut world = Self {
             chunks: HashMap::new(),
-            noise: Perlin::new(used_seed),
-            seed: used_seed,
+            generator,
+            meta,
             world_dir,
+            regions,
+            provider,
             dirty_chunks: HashSet::new(),
+            pending_blocks,
+            pending_blocks_dirty: false,
+            relit_chunks: HashSet::new(),
+            tick_count: 0,
+            fluid_ticks: TickQueue::default(),
+            ticked_chunks: HashSet::new(),
+            failed_loads: HashMap::new(),
+        };
+        if is_new {
+            world.save_meta();
         }
+        Ok(world)
     }
 
-    pub fn get_chunk(&mut self, chunk_x: i32, chunk_z: i32) -> &Chunk {
-        if !self.chunks.contains_key(&(chunk_x, chunk_z)) {
-            let chunk = self
-                .load_chunk_from_disk(chunk_x, chunk_z)
-                .unwrap_or_else(|| {
-                    let generated = self.generate_chunk(chunk_x, chunk_z);
-                    self.dirty_chunks.insert((chunk_x, chunk_z));
-                    generated
-                });
-            self.chunks.insert((chunk_x, chunk_z), chunk);
-        }
-        self.chunks.get(&(chunk_x, chunk_z)).unwrap()
+    /// Load or generate a chunk synchronously on the calling thread.
+    ///
+    /// Per-frame code should prefer `request_chunk`/`loaded_chunk`, which never block.
+    /// `None` if the chunk's record couldn't be read; it is tried again after a delay.
+    pub fn get_chunk(&mut self, chunk_x: i32, chunk_z: i32) -> Option<&Chunk> {
+        self.ensure_chunk_loaded(chunk_x, chunk_z);
+        self.chunks.get(&(chunk_x, chunk_z))
     }
 
-    pub fn get_chunk_mut(&mut self, chunk_x: i32, chunk_z: i32) -> &mut Chunk {
-        if !self.chunks.contains_key(&(chunk_x, chunk_z)) {
-            let chunk = self
-                .load_chunk_from_disk(chunk_x, chunk_z)
-                .unwrap_or_else(|| {
-                    let generated = self.generate_chunk(chunk_x, chunk_z);
-                    self.dirty_chunks.insert((chunk_x, chunk_z));
-                    generated
-                });
-            self.chunks.insert((chunk_x, chunk_z), chunk);
-        }
-        self.chunks.get_mut(&(chunk_x, chunk_z)).unwrap()
-    }
-
-    fn generate_chunk(&self, chunk_x: i32, chunk_z: i32) -> Chunk {
-        let mut chunk = Chunk::new(chunk_x, chunk_z);
-        let chunk_world_x = chunk_x * CHUNK_SIZE as i32;
-        let chunk_world_z = chunk_z * CHUNK_SIZE as i32;
-
-        // Generate heightmap
-        let mut height_map = [[0usize; CHUNK_SIZE]; CHUNK_SIZE];
-        for x in 0..CHUNK_SIZE {
-            for z in 0..CHUNK_SIZE {
-                let world_x = chunk_world_x + x as i32;
-                let world_z = chunk_world_z + z as i32;
-
-                // Base terrain height (40-120)
-                let base_height =
-                    40.0 + self.sample_noise(world_x as f64, world_z as f64, 6, 0.5, 0.005) * 80.0;
-
-                // Add hills and valleys
-                let hill_noise =
-                    self.sample_noise(world_x as f64, world_z as f64, 3, 0.7, 0.02) * 20.0;
-                let valley_noise =
-                    self.sample_noise(world_x as f64, world_z as f64, 2, 0.8, 0.01) * 15.0;
-
-                height_map[x][z] = (base_height + hill_noise - valley_noise)
-                    .max(1.0)
-                    .min(WORLD_HEIGHT as f64 - 1.0) as usize;
+    pub fn get_chunk_mut(&mut self, chunk_x: i32, chunk_z: i32) -> Option<&mut Chunk> {
+        self.ensure_chunk_loaded(chunk_x, chunk_z);
+        self.chunks.get_mut(&(chunk_x, chunk_z))
+    }
+
+    fn ensure_chunk_loaded(&mut self, chunk_x: i32, chunk_z: i32) {
+        let coords = (chunk_x, chunk_z);
+        if self.chunks.contains_key(&coords) || self.is_load_deferred(coords) {
+            return;
+        }
+        let loaded = match self.regions.as_deref() {
+            Some(regions) => Self::load_chunk_from_disk(regions, chunk_x, chunk_z),
+            None => Ok(None),
+        };
+        match loaded {
+            Ok(Some(chunk)) => {
+                self.insert_chunk(chunk, Vec::new());
             }
+            Ok(None) => {
+                let (chunk, spill) = self.generator.generate_chunk(chunk_x, chunk_z);
+                self.dirty_chunks.insert(coords);
+                self.insert_chunk(chunk, spill);
+            }
+            Err(_) => self.defer_load(coords),
         }
+    }
 
-        // Generate blocks
-        for x in 0..CHUNK_SIZE {
-            for z in 0..CHUNK_SIZE {
-                let height = height_map[x][z];
-
-                for y in 0..WORLD_HEIGHT {
-                    let block = if y == 0 {
-                        // Bedrock layer
-                        BlockType::Bedrock
-                    } else if y < height.saturating_sub(3) {
-                        // Deep stone
-                        if y < 16 {
-                            BlockType::Deepslate
-                        } else {
-                            BlockType::Stone
-                        }
-                    } else if y < height.saturating_sub(1) {
-                        // Dirt layer
-                        BlockType::Dirt
-                    } else if y < height {
-                        // Surface block
-                        if height > 90 {
-                            BlockType::Stone // Mountain peaks
-                        } else if height < 65 {
-                            BlockType::Sand // Beaches
-                        } else {
-                            BlockType::GrassBlock
-                        }
-                    } else if y < 63 {
-                        // Water below sea level
-                        BlockType::Water
-                    } else {
-                        BlockType::Air
-                    };
-
-                    chunk.set_block(x, y, z, block);
-                }
-
-                // Add ores
-                self.generate_ores(&mut chunk, x, z, height);
+    /// Whether a chunk failed to load recently enough that it shouldn't be retried yet.
+    fn is_load_deferred(&mut self, coords: (i32, i32)) -> bool {
+        match self.failed_loads.get(&coords) {
+            Some(retry_at) if Instant::now() < *retry_at => true,
+            Some(_) => {
+                self.failed_loads.remove(&coords);
+                false
             }
+            None => false,
         }
+    }
 
-        chunk
+    fn defer_load(&mut self, coords: (i32, i32)) {
+        self.failed_loads.insert(coords, Instant::now() + LOAD_RETRY_DELAY);
     }
 
-    fn generate_ores(&self, chunk: &mut Chunk, x: usize, z: usize, surface_height: usize) {
-        let chunk_world_x = chunk.x * CHUNK_SIZE as i32;
-        let chunk_world_z = chunk.z * CHUNK_SIZE as i32;
-        let world_x = chunk_world_x + x as i32;
-        let world_z = chunk_world_z + z as i32;
+    /// Add a chunk to the world, applying decoration queued for it and routing
+    /// `spill` to its neighbours, then light it together with its neighbours.
+    /// Returns the already-loaded neighbours that changed or need remeshing.
+    fn insert_chunk(&mut self, mut chunk: Chunk, spill: Vec<PendingBlock>) -> Vec<(i32, i32)> {
+        let coords = (chunk.x, chunk.z);
+        let mut needs_light = !chunk.is_lit();
+        if let Some(queued) = self.pending_blocks.remove(&coords) {
+            if queued.iter().fold(false, |changed, block| block.apply(&mut chunk) | changed) {
+                self.dirty_chunks.insert(coords);
+                needs_light = true;
+            }
+            self.pending_blocks_dirty = true;
+        }
+        if needs_light {
+            light::light_chunk(&mut chunk);
+        }
+        self.chunks.insert(coords, chunk);
 
-        for y in 1..surface_height.min(WORLD_HEIGHT - 1) {
-            let ore_noise =
-                self.noise
-                    .get([world_x as f64 * 0.1, y as f64 * 0.1, world_z as f64 * 0.1]);
+        let mut world_light = WorldLight::new(&mut self.chunks);
+        light::stitch_chunk(&mut world_light, coords.0, coords.1);
+        self.relit_chunks.extend(world_light.changed);
+        // Border faces of the neighbours are shaded with this chunk's light.
+        let mut changed: Vec<(i32, i32)> = [(-1, 0), (1, 0), (0, -1), (0, 1)]
+            .iter()
+            .map(|(dx, dz)| (coords.0 + dx, coords.1 + dz))
+            .filter(|neighbour| self.chunks.contains_key(neighbour))
+            .collect();
 
-            // Coal (common, all heights)
-            if ore_noise > 0.6 && y > 0 && y < 128 {
-                chunk.set_block(
-                    x,
-                    y,
-                    z,
-                    if y < 16 {
-                        BlockType::DeepslateCoalOre
-                    } else {
-                        BlockType::CoalOre
-                    },
-                );
-            }
-            // Iron (medium depth)
-            else if ore_noise > 0.7 && y > 0 && y < 64 {
-                chunk.set_block(
-                    x,
-                    y,
-                    z,
-                    if y < 16 {
-                        BlockType::DeepslateIronOre
-                    } else {
-                        BlockType::IronOre
-                    },
-                );
-            }
-            // Gold (deeper)
-            else if ore_noise > 0.75 && y > 0 && y < 32 {
-                chunk.set_block(
-                    x,
-                    y,
-                    z,
-                    if y < 16 {
-                        BlockType::DeepslateGoldOre
-                    } else {
-                        BlockType::GoldOre
-                    },
-                );
-            }
-            // Diamond (very deep, rare)
-            else if ore_noise > 0.8 && y > 1 && y < 20 {
-                chunk.set_block(
-                    x,
-                    y,
-                    z,
-                    if y < 16 {
-                        BlockType::DeepslateDiamondOre
-                    } else {
-                        BlockType::DiamondOre
-                    },
-                );
+        for block in spill {
+            let target = block.chunk_coords();
+            match self.chunks.get_mut(&target) {
+                Some(neighbour) => {
+                    if block.apply(neighbour) {
+                        self.dirty_chunks.insert(target);
+                        let mut world_light = WorldLight::new(&mut self.chunks);
+                        light::update_block(&mut world_light, block.x, block.y, block.z);
+                        self.relit_chunks.extend(world_light.changed);
+                        if !changed.contains(&target) {
+                            changed.push(target);
+                        }
+                    }
+                }
+                None => {
+                    self.pending_blocks.entry(target).or_default().push(block);
+                    self.pending_blocks_dirty = true;
+                }
             }
         }
+        changed
     }
 
-    fn sample_noise(&self, x: f64, z: f64, octaves: i32, persistence: f64, scale: f64) -> f64 {
-        let mut value = 0.0;
-        let mut amplitude = 1.0;
-        let mut frequency = scale;
-        let mut max_value = 0.0;
+    /// The chunk if it is already in memory; never loads or generates.
+    pub fn loaded_chunk(&self, chunk_x: i32, chunk_z: i32) -> Option<&Chunk> {
+        self.chunks.get(&(chunk_x, chunk_z))
+    }
 
-        for _ in 0..octaves {
-            value += self.noise.get([x * frequency, z * frequency]) * amplitude;
-            max_value += amplitude;
-            amplitude *= persistence;
-            frequency *= 2.0;
+    /// Ask the background workers for a chunk. No-op if it is loaded or already queued.
+    pub fn request_chunk(&mut self, chunk_x: i32, chunk_z: i32) {
+        let coords = (chunk_x, chunk_z);
+        if !self.chunks.contains_key(&coords) && !self.is_load_deferred(coords) {
+            self.provider.request(chunk_x, chunk_z);
         }
+    }
+
+    pub fn is_chunk_pending(&self, chunk_x: i32, chunk_z: i32) -> bool {
+        self.provider.is_pending(chunk_x, chunk_z)
+    }
+
+    /// Re-prioritise queued requests around the player and drop those beyond `radius` chunks.
+    pub fn set_load_center(&mut self, chunk_x: i32, chunk_z: i32, radius: i32) {
+        self.provider.set_center(chunk_x, chunk_z, radius);
+    }
 
-        (value / max_value + 1.0) / 2.0 // Normalize to 0-1
+    /// Move chunks finished by the background workers into the world.
+    /// Returns the coordinates of the chunks that were added, followed by loaded
+    /// chunks that received decoration from them and need remeshing.
+    pub fn process_loaded_chunks(&mut self) -> Vec<(i32, i32)> {
+        let mut added = Vec::new();
+        let mut changed = Vec::new();
+        while let Some(result) = self.provider.try_recv() {
+            let loaded = match result {
+                Ok(loaded) => loaded,
+                Err(coords) => {
+                    self.defer_load(coords);
+                    continue;
+                }
+            };
+            let coords = (loaded.chunk.x, loaded.chunk.z);
+            // A synchronous `get_chunk` may have beaten the worker to it.
+            if self.chunks.contains_key(&coords) {
+                continue;
+            }
+            if loaded.generated {
+                self.dirty_chunks.insert(coords);
+            }
+            changed.extend(self.insert_chunk(loaded.chunk, loaded.spill));
+            added.push(coords);
+        }
+        changed.retain(|coords| !added.contains(coords));
+        changed.sort_unstable();
+        changed.dedup();
+        added.extend(changed);
+        added
     }
 
     pub fn get_loaded_chunks(&self) -> Vec<(i32, i32)> {
@@ -270,37 +500,52 @@ impl World {
     }
 
     pub fn has_dirty_chunks(&self) -> bool {
-        !self.dirty_chunks.is_empty()
+        !self.dirty_chunks.is_empty() || self.pending_blocks_dirty
     }
 
     pub fn seed(&self) -> u32 {
-        self.seed
+        self.meta.seed
+    }
+
+    pub fn generator_name(&self) -> &str {
+        &self.meta.generator
+    }
+
+    pub fn meta(&self) -> &WorldMeta {
+        &self.meta
+    }
+
+    /// Changes are written by the next `save_meta`.
+    pub fn meta_mut(&mut self) -> &mut WorldMeta {
+        &mut self.meta
     }
 
-    pub fn save_meta(&self) {
+    /// Write `world_meta.json`, stamping the last-played time.
+    pub fn save_meta(&mut self) {
         if let Some(ref dir) = self.world_dir {
-            let mut meta_path = dir.clone();
-            meta_path.push("world_meta.json");
-            let meta = serde_json::json!({ "seed": self.seed });
-            let _ = std::fs::create_dir_all(dir);
-            let _ = std::fs::write(&meta_path, serde_json::to_string_pretty(&meta).unwrap());
+            self.meta.last_played = unix_now();
+            if let Err(err) = self.meta.save(dir) {
+                log::warn!("Failed to save world metadata: {}", err);
+            }
         }
     }
 
     pub fn save_dirty_chunks(&mut self) {
+        self.save_pending_blocks();
         if self.dirty_chunks.is_empty() {
             return;
         }
 
-        if self.world_dir.is_none() {
+        let Some(regions) = self.regions.clone() else {
             self.dirty_chunks.clear();
             return;
-        }
+        };
+        let mut regions = regions.lock().unwrap();
 
         let to_save: Vec<(i32, i32)> = self.dirty_chunks.drain().collect();
         for (chunk_x, chunk_z) in to_save {
             if let Some(chunk) = self.chunks.get(&(chunk_x, chunk_z)) {
-                if let Err(err) = self.save_chunk_to_disk(chunk) {
+                if let Err(err) = Self::save_chunk_to_disk(&mut regions, chunk) {
                     log::warn!(
                         "Failed to save chunk ({}, {}): {}",
                         chunk_x, chunk_z, err
@@ -310,6 +555,120 @@ impl World {
                 }
             }
         }
+        if let Err(err) = regions.sync() {
+            log::warn!("Failed to sync region files: {}", err);
+        }
+    }
+
+    /// Persist decoration still waiting for unloaded chunks, so it survives a restart.
+    fn save_pending_blocks(&mut self) {
+        if !self.pending_blocks_dirty {
+            return;
+        }
+        let Some(ref dir) = self.world_dir else {
+            self.pending_blocks_dirty = false;
+            return;
+        };
+        let path = dir.join(PENDING_BLOCKS_FILE);
+        let result = if self.pending_blocks.is_empty() {
+            match std::fs::remove_file(&path) {
+                Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+                _ => Ok(()),
+            }
+        } else {
+            bincode::serialize(&self.pending_blocks)
+                .map_err(anyhow::Error::from)
+                .and_then(|data| {
+                    std::fs::create_dir_all(dir)?;
+                    write_atomic(&path, &data)?;
+                    Ok(())
+                })
+        };
+        match result {
+            Ok(()) => self.pending_blocks_dirty = false,
+            Err(err) => log::warn!("Failed to save pending decoration: {}", err),
+        }
+    }
+
+    pub fn memory_usage(&self) -> usize {
+        self.chunks.values().map(|chunk| chunk.memory_usage()).sum()
+    }
+
+    /// Drop chunks that no center needs any more, saving dirty ones first.
+    ///
+    /// `centers` are chunk coordinates of everything keeping the world loaded: the
+    /// local player, or every connected player on a server. Returns the unloaded chunks.
+    pub fn unload_chunks(&mut self, centers: &[(i32, i32)], policy: RetentionPolicy) -> Vec<(i32, i32)> {
+        let distance_to_center = |(x, z): (i32, i32)| {
+            centers
+                .iter()
+                .map(|(cx, cz)| (x - cx).abs().max((z - cz).abs()))
+                .min()
+                .unwrap_or(i32::MAX)
+        };
+
+        let mut by_distance: Vec<((i32, i32), i32)> = self
+            .chunks
+            .keys()
+            .map(|coords| (*coords, distance_to_center(*coords)))
+            .collect();
+        // Furthest first, so the memory cap below evicts from the outside in.
+        by_distance.sort_by(|a, b| b.1.cmp(&a.1));
+
+        let mut to_unload: Vec<(i32, i32)> = by_distance
+            .iter()
+            .filter(|(_, distance)| *distance > policy.radius)
+            .map(|(coords, _)| *coords)
+            .collect();
+
+        if let Some(limit) = policy.max_memory_bytes {
+            let mut usage: usize = by_distance
+                .iter()
+                .filter(|(_, distance)| *distance <= policy.radius)
+                .map(|(coords, _)| self.chunks[coords].memory_usage())
+                .sum();
+            for (coords, distance) in by_distance.iter().filter(|(_, d)| *d <= policy.radius) {
+                if usage <= limit || *distance <= MIN_RETAINED_RADIUS {
+                    break;
+                }
+                usage -= self.chunks[coords].memory_usage();
+                to_unload.push(*coords);
+            }
+        }
+
+        to_unload
+            .into_iter()
+            .filter(|(x, z)| self.unload_chunk(*x, *z))
+            .collect()
+    }
+
+    /// Save the chunk if dirty and drop it from memory. Returns false if the save
+    /// failed, in which case the chunk stays loaded.
+    pub fn unload_chunk(&mut self, chunk_x: i32, chunk_z: i32) -> bool {
+        let coords = (chunk_x, chunk_z);
+        let Some(chunk) = self.chunks.get(&coords) else {
+            return false;
+        };
+
+        if self.dirty_chunks.contains(&coords) {
+            if let Some(regions) = &self.regions {
+                let mut regions = regions.lock().unwrap();
+                if let Err(err) = Self::save_chunk_to_disk(&mut regions, chunk)
+                    .and_then(|_| regions.sync().map_err(anyhow::Error::from))
+                {
+                    log::warn!(
+                        "Failed to save chunk ({}, {}) before unloading: {}",
+                        chunk_x, chunk_z, err
+                    );
+                    return false;
+                }
+            }
+            self.dirty_chunks.remove(&coords);
+        }
+
+        self.chunks.remove(&coords);
+        self.relit_chunks.remove(&coords);
+        true
     }
 
     fn split_world_coord(coord: i32) -> (i32, usize) {
@@ -326,16 +685,132 @@ impl World {
 
         let (chunk_x, local_x) = Self::split_world_coord(world_x);
         let (chunk_z, local_z) = Self::split_world_coord(world_z);
-        let chunk = self.get_chunk(chunk_x, chunk_z);
-        chunk.get_block(local_x, world_y as usize, local_z)
+        match self.get_chunk(chunk_x, chunk_z) {
+            Some(chunk) => chunk.get_block(local_x, world_y as usize, local_z),
+            None => BlockType::Air,
+        }
+    }
+
+    /// Block at a world position if its chunk is loaded; `None` for unloaded chunks.
+    pub fn get_loaded_block(&self, world_x: i32, world_y: i32, world_z: i32) -> Option<BlockType> {
+        self.get_loaded_state(world_x, world_y, world_z)
+            .map(|state| state.block)
+    }
+
+    pub fn get_state_at(&mut self, world_x: i32, world_y: i32, world_z: i32) -> BlockState {
+        if world_y < 0 || world_y >= WORLD_HEIGHT as i32 {
+            return BlockState::default();
+        }
+
+        let (chunk_x, local_x) = Self::split_world_coord(world_x);
+        let (chunk_z, local_z) = Self::split_world_coord(world_z);
+        match self.get_chunk(chunk_x, chunk_z) {
+            Some(chunk) => chunk.get_state(local_x, world_y as usize, local_z),
+            None => BlockState::default(),
+        }
+    }
+
+    /// Block state at a world position if its chunk is loaded; `None` for unloaded chunks.
+    pub fn get_loaded_state(&self, world_x: i32, world_y: i32, world_z: i32) -> Option<BlockState> {
+        if world_y < 0 || world_y >= WORLD_HEIGHT as i32 {
+            return Some(BlockState::default());
+        }
+
+        let (chunk_x, local_x) = Self::split_world_coord(world_x);
+        let (chunk_z, local_z) = Self::split_world_coord(world_z);
+        self.chunks
+            .get(&(chunk_x, chunk_z))
+            .map(|chunk| chunk.get_state(local_x, world_y as usize, local_z))
+    }
+
+    /// World-space boxes that things collide with in the block at `x, y, z`.
+    /// Unloaded chunks are solid so nothing falls into them.
+    pub fn collision_boxes(&self, x: i32, y: i32, z: i32) -> Vec<BlockBox> {
+        let Some(state) = self.get_loaded_state(x, y, z) else {
+            return vec![BlockBox::FULL.offset(x, y, z)];
+        };
+        if !state.block.is_solid() {
+            return Vec::new();
+        }
+        let shape = state.block.shape();
+        let connected = |facing: Facing| {
+            let (dx, dy, dz) = facing.offset();
+            self.get_loaded_block(x + dx, y + dy, z + dz)
+                .is_some_and(|neighbour| shape.connects_to(neighbour))
+        };
+        shape::block_boxes(state, connected)
+            .into_iter()
+            .map(|block_box| block_box.offset(x, y, z))
+            .collect()
+    }
+
+    /// How far `body` can move by `delta` along `axis` before it runs into a block,
+    /// stopping just short of it. Boxes `body` already overlaps are ignored so
+    /// whatever is stuck inside a block can move out of it.
+    pub fn sweep_box(&self, body: BlockBox, axis: usize, delta: f32) -> f32 {
+        let mut swept = body;
+        if delta > 0.0 {
+            swept.max[axis] += delta;
+        } else {
+            swept.min[axis] += delta;
+        }
+
+        let mut allowed = delta;
+        let cells = |a: usize| swept.min[a].floor() as i32..=swept.max[a].floor() as i32;
+        for x in cells(0) {
+            for y in cells(1) {
+                for z in cells(2) {
+                    for block_box in self.collision_boxes(x, y, z) {
+                        let beside = (0..3).filter(|a| *a != axis).all(|a| {
+                            block_box.min[a] < body.max[a] && block_box.max[a] > body.min[a]
+                        });
+                        if !beside {
+                            continue;
+                        }
+                        if delta > 0.0 && block_box.min[axis] >= body.max[axis] {
+                            let gap = block_box.min[axis] - body.max[axis] - COLLISION_EPSILON;
+                            allowed = allowed.min(gap.max(0.0));
+                        } else if delta < 0.0 && block_box.max[axis] <= body.min[axis] {
+                            let gap = block_box.max[axis] - body.min[axis] + COLLISION_EPSILON;
+                            allowed = allowed.max(gap.min(0.0));
+                        }
+                    }
+                }
+            }
+        }
+        allowed
+    }
+
+    /// Biome of a column if its chunk is loaded.
+    pub fn biome_at(&self, world_x: i32, world_z: i32) -> Option<Biome> {
+        let (chunk_x, local_x) = Self::split_world_coord(world_x);
+        let (chunk_z, local_z) = Self::split_world_coord(world_z);
+        self.chunks
+            .get(&(chunk_x, chunk_z))
+            .map(|chunk| chunk.biome(local_x, local_z))
     }
 
+    /// Set a block in its default state, unless that block is already there.
     pub fn set_block_at(
         &mut self,
         world_x: i32,
         world_y: i32,
         world_z: i32,
         block: BlockType,
+    ) -> Option<(i32, i32)> {
+        if self.get_state_at(world_x, world_y, world_z).block == block {
+            return None;
+        }
+        self.set_state_at(world_x, world_y, world_z, BlockState::new(block))
+    }
+
+    /// Set a block state, returning the chunk that changed, if any.
+    pub fn set_state_at(
+        &mut self,
+        world_x: i32,
+        world_y: i32,
+        world_z: i32,
+        state: BlockState,
     ) -> Option<(i32, i32)> {
         if world_y < 0 || world_y >= WORLD_HEIGHT as i32 {
             return None;
@@ -343,43 +818,237 @@ impl World {
 
         let (chunk_x, local_x) = Self::split_world_coord(world_x);
         let (chunk_z, local_z) = Self::split_world_coord(world_z);
-        let chunk = self.get_chunk_mut(chunk_x, chunk_z);
-        let current = chunk.get_block(local_x, world_y as usize, local_z);
-        if current == block {
+        let chunk = self.get_chunk_mut(chunk_x, chunk_z)?;
+        let current = chunk.get_state(local_x, world_y as usize, local_z);
+        if current == state {
             return None;
         }
-        chunk.set_block(local_x, world_y as usize, local_z, block);
+        chunk.set_state(local_x, world_y as usize, local_z, state);
+        if current.block != state.block {
+            chunk.set_block_entity(local_x, world_y as usize, local_z, BlockEntity::for_block(state.block));
+        }
         self.dirty_chunks.insert((chunk_x, chunk_z));
+
+        if current.block != state.block {
+            let mut world_light = WorldLight::new(&mut self.chunks);
+            light::update_block(&mut world_light, world_x, world_y, world_z);
+            self.relit_chunks.extend(world_light.changed);
+        }
+        self.schedule_fluid_updates(world_x, world_y, world_z);
         Some((chunk_x, chunk_z))
     }
 
-    fn chunk_directory(&self) -> Option<PathBuf> {
-        let mut dir = self.world_dir.clone()?;
-        dir.push("chunks");
-        Some(dir)
+    /// Advance the world by one game tick, running the block updates that are due.
+    /// Returns the chunks whose blocks changed, including neighbours of changed
+    /// border blocks, so their meshes can be rebuilt.
+    pub fn tick(&mut self) -> Vec<(i32, i32)> {
+        self.tick_count += 1;
+        for pos in self.fluid_ticks.take_due(self.tick_count) {
+            fluid::update(self, pos);
+        }
+        for (coords, chunk) in self.chunks.iter_mut() {
+            if chunk.tick_block_entities() {
+                self.dirty_chunks.insert(*coords);
+            }
+        }
+        self.ticked_chunks.drain().collect()
     }
 
-    fn chunk_path(&self, chunk_x: i32, chunk_z: i32) -> Option<PathBuf> {
-        let mut dir = self.chunk_directory()?;
-        dir.push(format!("chunk_{}_{}.bin", chunk_x, chunk_z));
-        Some(dir)
+    /// Block entity at a world position if its chunk is loaded.
+    pub fn block_entity(&self, world_x: i32, world_y: i32, world_z: i32) -> Option<&BlockEntity> {
+        if world_y < 0 || world_y >= WORLD_HEIGHT as i32 {
+            return None;
+        }
+        let (chunk_x, local_x) = Self::split_world_coord(world_x);
+        let (chunk_z, local_z) = Self::split_world_coord(world_z);
+        self.chunks
+            .get(&(chunk_x, chunk_z))?
+            .block_entity(local_x, world_y as usize, local_z)
+    }
+
+    /// Block entity at a world position for changing; its chunk is marked for saving.
+    pub fn block_entity_mut(&mut self, world_x: i32, world_y: i32, world_z: i32) -> Option<&mut BlockEntity> {
+        if world_y < 0 || world_y >= WORLD_HEIGHT as i32 {
+            return None;
+        }
+        let (chunk_x, local_x) = Self::split_world_coord(world_x);
+        let (chunk_z, local_z) = Self::split_world_coord(world_z);
+        let chunk = self.chunks.get_mut(&(chunk_x, chunk_z))?;
+        let entity = chunk.block_entity_mut(local_x, world_y as usize, local_z)?;
+        self.dirty_chunks.insert((chunk_x, chunk_z));
+        Some(entity)
+    }
+
+    /// Detach the block entity at a world position, e.g. to drop its contents
+    /// before the block is broken.
+    pub fn take_block_entity(&mut self, world_x: i32, world_y: i32, world_z: i32) -> Option<BlockEntity> {
+        if world_y < 0 || world_y >= WORLD_HEIGHT as i32 {
+            return None;
+        }
+        let (chunk_x, local_x) = Self::split_world_coord(world_x);
+        let (chunk_z, local_z) = Self::split_world_coord(world_z);
+        let entity = self
+            .chunks
+            .get_mut(&(chunk_x, chunk_z))?
+            .set_block_entity(local_x, world_y as usize, local_z, None)?;
+        self.dirty_chunks.insert((chunk_x, chunk_z));
+        Some(entity)
     }
 
-    fn load_chunk_from_disk(&self, chunk_x: i32, chunk_z: i32) -> Option<Chunk> {
-        let path = self.chunk_path(chunk_x, chunk_z)?;
-        let data = std::fs::read(path).ok()?;
-        bincode::deserialize(&data).ok()
+    pub fn tick_count(&self) -> u64 {
+        self.tick_count
+    }
+
+    /// Number of block updates waiting to run.
+    pub fn scheduled_tick_count(&self) -> usize {
+        self.fluid_ticks.len()
+    }
+
+    /// Stored fluid level at a world position if its chunk is loaded; see `fluid`.
+    pub fn fluid_level_at(&self, world_x: i32, world_y: i32, world_z: i32) -> Option<u8> {
+        if world_y < 0 || world_y >= WORLD_HEIGHT as i32 {
+            return None;
+        }
+        let (chunk_x, local_x) = Self::split_world_coord(world_x);
+        let (chunk_z, local_z) = Self::split_world_coord(world_z);
+        self.chunks
+            .get(&(chunk_x, chunk_z))
+            .map(|chunk| chunk.fluid_level(local_x, world_y as usize, local_z))
     }
 
-    fn save_chunk_to_disk(&self, chunk: &Chunk) -> anyhow::Result<()> {
-        let Some(dir) = self.chunk_directory() else {
-            return Ok(());
+    /// Place a block with a fluid level in a loaded chunk on behalf of the fluid
+    /// simulation, updating light and waking up neighbouring fluid.
+    pub(super) fn set_fluid_at(&mut self, world_x: i32, world_y: i32, world_z: i32, block: BlockType, level: u8) {
+        if world_y < 0 || world_y >= WORLD_HEIGHT as i32 {
+            return;
+        }
+        let (chunk_x, local_x) = Self::split_world_coord(world_x);
+        let (chunk_z, local_z) = Self::split_world_coord(world_z);
+        let Some(chunk) = self.chunks.get_mut(&(chunk_x, chunk_z)) else {
+            return;
         };
-        std::fs::create_dir_all(&dir)?;
-        let mut path = dir;
-        path.push(format!("chunk_{}_{}.bin", chunk.x, chunk.z));
-        let data = bincode::serialize(chunk)?;
-        std::fs::write(path, data)?;
+        let y = world_y as usize;
+        let state = BlockState::new(block).with_level(level);
+        let current = chunk.get_state(local_x, y, local_z);
+        if current == state {
+            return;
+        }
+        let block_changed = current.block != block;
+        chunk.set_state(local_x, y, local_z, state);
+        self.dirty_chunks.insert((chunk_x, chunk_z));
+
+        self.ticked_chunks.insert((chunk_x, chunk_z));
+        if local_x == 0 {
+            self.ticked_chunks.insert((chunk_x - 1, chunk_z));
+        }
+        if local_x == CHUNK_SIZE - 1 {
+            self.ticked_chunks.insert((chunk_x + 1, chunk_z));
+        }
+        if local_z == 0 {
+            self.ticked_chunks.insert((chunk_x, chunk_z - 1));
+        }
+        if local_z == CHUNK_SIZE - 1 {
+            self.ticked_chunks.insert((chunk_x, chunk_z + 1));
+        }
+
+        if block_changed {
+            let mut world_light = WorldLight::new(&mut self.chunks);
+            light::update_block(&mut world_light, world_x, world_y, world_z);
+            self.relit_chunks.extend(world_light.changed);
+        }
+        self.schedule_fluid_updates(world_x, world_y, world_z);
+    }
+
+    /// Schedule an update for any fluid at or next to a block that just changed.
+    fn schedule_fluid_updates(&mut self, world_x: i32, world_y: i32, world_z: i32) {
+        const AROUND: [(i32, i32, i32); 7] = [
+            (0, 0, 0),
+            (1, 0, 0),
+            (-1, 0, 0),
+            (0, 1, 0),
+            (0, -1, 0),
+            (0, 0, 1),
+            (0, 0, -1),
+        ];
+        for (dx, dy, dz) in AROUND {
+            let pos = (world_x + dx, world_y + dy, world_z + dz);
+            let fluid = self
+                .get_loaded_state(pos.0, pos.1, pos.2)
+                .and_then(Fluid::of_state);
+            if let Some(fluid) = fluid {
+                self.fluid_ticks.schedule(self.tick_count + fluid.tick_delay(), pos);
+            }
+        }
+    }
+
+    /// Light at a world position; unloaded chunks read as dark.
+    pub fn light_at(&self, kind: LightKind, world_x: i32, world_y: i32, world_z: i32) -> u8 {
+        if world_y < 0 || world_y >= WORLD_HEIGHT as i32 {
+            return 0;
+        }
+        let (chunk_x, local_x) = Self::split_world_coord(world_x);
+        let (chunk_z, local_z) = Self::split_world_coord(world_z);
+        self.chunks
+            .get(&(chunk_x, chunk_z))
+            .map_or(0, |chunk| chunk.light(kind, local_x, world_y as usize, local_z))
+    }
+
+    /// Light just outside a loaded chunk's sides, for meshing a copy of the chunk.
+    pub fn border_light(&self, chunk_x: i32, chunk_z: i32) -> BorderLight {
+        BorderLight::collect(&self.chunks, chunk_x, chunk_z)
+    }
+
+    /// Chunks whose light changed since the last call, beyond the one a block was
+    /// set in. Their meshes are stale.
+    pub fn take_relit_chunks(&mut self) -> Vec<(i32, i32)> {
+        self.relit_chunks.drain().collect()
+    }
+
+    /// Read a chunk from its region file. `Ok(None)` means there is nothing usable
+    /// on disk and the chunk may be generated: it was never saved, or its record
+    /// was corrupt and has been quarantined. `Err` means the record could not be
+    /// read (or set aside), so generating the chunk would overwrite it.
+    pub(super) fn load_chunk_from_disk(
+        regions: &Mutex<RegionStorage>,
+        chunk_x: i32,
+        chunk_z: i32,
+    ) -> std::io::Result<Option<Chunk>> {
+        let data = regions.lock().unwrap().read_chunk(chunk_x, chunk_z);
+        let reason = match data {
+            Ok(None) => return Ok(None),
+            Ok(Some(data)) => match Chunk::decode(&data) {
+                Some(chunk) => return Ok(Some(chunk)),
+                None => "undecodable".to_string(),
+            },
+            Err(err) if err.kind() == std::io::ErrorKind::InvalidData => err.to_string(),
+            Err(err) => {
+                log::warn!("Failed to read chunk ({}, {}): {}", chunk_x, chunk_z, err);
+                return Err(err);
+            }
+        };
+
+        // Damaged on disk: set it aside so regenerating the chunk doesn't destroy it.
+        match regions.lock().unwrap().quarantine_chunk(chunk_x, chunk_z) {
+            Ok(path) => {
+                log::warn!(
+                    "Chunk ({}, {}) is corrupt ({}); moved to {:?} and regenerating",
+                    chunk_x, chunk_z, reason, path
+                );
+                Ok(None)
+            }
+            Err(err) => {
+                log::warn!(
+                    "Chunk ({}, {}) is corrupt ({}) and could not be quarantined: {}",
+                    chunk_x, chunk_z, reason, err
+                );
+                Err(err)
+            }
+        }
+    }
+
+    fn save_chunk_to_disk(regions: &mut RegionStorage, chunk: &Chunk) -> anyhow::Result<()> {
+        let data = chunk.encode()?;
+        regions.write_chunk(chunk.x, chunk.z, &data)?;
         Ok(())
     }
 }
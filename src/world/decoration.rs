@@ -0,0 +1,315 @@
+#![allow(dead_code)]
+/// Tree decoration that runs after terrain and caves.
+///
+/// Trees are rooted in the chunk being generated, but their canopies may hang
+/// over into neighbouring chunks. Blocks that fall outside the chunk are handed
+/// back as `PendingBlock`s for the world to apply when the neighbour is (or
+/// already was) loaded. Placement only ever upgrades a block along a fixed
+/// ranking (air < leaves < logs, ties broken by block id) and never touches
+/// terrain, so the final result is the same whichever chunk generates first.
+use super::biome::Biome;
+use super::block::BlockType;
+use super::chunk::{Chunk, CHUNK_SIZE, WORLD_HEIGHT};
+use super::generator::{chunk_rng, SEA_LEVEL};
+use rand::rngs::StdRng;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+const DECORATION_SALT: u64 = 0x7EE5;
+/// Trunks closer than this (in blocks, either axis) to an earlier trunk in the same chunk are skipped.
+const MIN_TREE_SPACING: i32 = 3;
+
+/// A decoration block destined for a chunk other than the one that generated it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingBlock {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+    #[serde(with = "super::block::by_id")]
+    pub block: BlockType,
+}
+
+impl PendingBlock {
+    pub fn chunk_coords(&self) -> (i32, i32) {
+        (
+            self.x.div_euclid(CHUNK_SIZE as i32),
+            self.z.div_euclid(CHUNK_SIZE as i32),
+        )
+    }
+
+    /// Place the block into its chunk. Returns true if the chunk changed.
+    pub fn apply(&self, chunk: &mut Chunk) -> bool {
+        let x = self.x.rem_euclid(CHUNK_SIZE as i32) as usize;
+        let z = self.z.rem_euclid(CHUNK_SIZE as i32) as usize;
+        place_decoration(chunk, x, self.y as usize, z, self.block)
+    }
+}
+
+/// Where a block sits in the decoration ranking, or `None` if decoration may not replace it.
+fn decoration_rank(block: BlockType) -> Option<(u8, u16)> {
+    let tier = match block {
+        BlockType::Air | BlockType::CaveAir => 0,
+        BlockType::OakLeaves => 1,
+        BlockType::OakLog
+        | BlockType::SpruceLog
+        | BlockType::BirchLog
+        | BlockType::JungleLog
+        | BlockType::AcaciaLog
+        | BlockType::DarkOakLog => 2,
+        _ => return None,
+    };
+    Some((tier, block as u16))
+}
+
+/// Set a decoration block if it outranks what is already there. Returns true if it was placed.
+pub fn place_decoration(chunk: &mut Chunk, x: usize, y: usize, z: usize, block: BlockType) -> bool {
+    if y >= WORLD_HEIGHT {
+        return false;
+    }
+    let existing = chunk.get_block(x, y, z);
+    match (decoration_rank(existing), decoration_rank(block)) {
+        (Some(current), Some(new)) if new > current => {
+            chunk.set_block(x, y, z, block);
+            true
+        }
+        _ => false,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TreeKind {
+    Oak,
+    Birch,
+    Spruce,
+    Jungle,
+    Acacia,
+    DarkOak,
+}
+
+impl TreeKind {
+    fn log(self) -> BlockType {
+        match self {
+            TreeKind::Oak => BlockType::OakLog,
+            TreeKind::Birch => BlockType::BirchLog,
+            TreeKind::Spruce => BlockType::SpruceLog,
+            TreeKind::Jungle => BlockType::JungleLog,
+            TreeKind::Acacia => BlockType::AcaciaLog,
+            TreeKind::DarkOak => BlockType::DarkOakLog,
+        }
+    }
+}
+
+/// Average trees attempted per chunk for a biome; the fractional part is a chance of one more.
+fn tree_density(biome: Biome) -> f64 {
+    match biome {
+        Biome::Forest | Biome::BirchForest | Biome::Taiga => 8.0,
+        Biome::DarkForest => 12.0,
+        Biome::Jungle => 14.0,
+        Biome::Swamp => 2.0,
+        Biome::Savanna | Biome::Mountains => 1.0,
+        Biome::Plains => 0.3,
+        Biome::Ocean | Biome::Beach | Biome::Desert | Biome::Badlands => 0.0,
+    }
+}
+
+fn pick_tree(biome: Biome, rng: &mut StdRng) -> Option<TreeKind> {
+    let kind = match biome {
+        Biome::Forest if rng.gen_bool(0.2) => TreeKind::Birch,
+        Biome::Forest | Biome::Plains | Biome::Swamp => TreeKind::Oak,
+        Biome::BirchForest => TreeKind::Birch,
+        Biome::DarkForest if rng.gen_bool(0.1) => TreeKind::Oak,
+        Biome::DarkForest => TreeKind::DarkOak,
+        Biome::Jungle => TreeKind::Jungle,
+        Biome::Savanna => TreeKind::Acacia,
+        Biome::Taiga | Biome::Mountains => TreeKind::Spruce,
+        Biome::Ocean | Biome::Beach | Biome::Desert | Biome::Badlands => return None,
+    };
+    Some(kind)
+}
+
+/// Collects decoration blocks, writing those inside the chunk and queueing the rest.
+struct Placer<'a> {
+    chunk: &'a mut Chunk,
+    origin_x: i32,
+    origin_z: i32,
+    spill: Vec<PendingBlock>,
+}
+
+impl Placer<'_> {
+    fn set(&mut self, x: i32, y: i32, z: i32, block: BlockType) {
+        if y < 0 || y >= WORLD_HEIGHT as i32 {
+            return;
+        }
+        let local_x = x - self.origin_x;
+        let local_z = z - self.origin_z;
+        let size = CHUNK_SIZE as i32;
+        if (0..size).contains(&local_x) && (0..size).contains(&local_z) {
+            place_decoration(self.chunk, local_x as usize, y as usize, local_z as usize, block);
+        } else {
+            self.spill.push(PendingBlock { x, y, z, block });
+        }
+    }
+
+    /// A square layer of leaves; `trim_corners` drops the four corners for a rounder canopy.
+    fn leaf_layer(&mut self, cx: i32, y: i32, cz: i32, radius: i32, trim_corners: bool) {
+        for dx in -radius..=radius {
+            for dz in -radius..=radius {
+                if trim_corners && radius > 0 && dx.abs() == radius && dz.abs() == radius {
+                    continue;
+                }
+                self.set(cx + dx, y, cz + dz, BlockType::OakLeaves);
+            }
+        }
+    }
+}
+
+/// Decorate a freshly generated chunk and return the blocks that belong to its neighbours.
+/// `height_map` holds the surface height of each column, indexed `[x][z]`.
+pub fn decorate_chunk(
+    seed: u32,
+    chunk: &mut Chunk,
+    height_map: &[[usize; CHUNK_SIZE]; CHUNK_SIZE],
+) -> Vec<PendingBlock> {
+    let mut rng = chunk_rng(seed, chunk.x, chunk.z, DECORATION_SALT);
+
+    // Density follows the biome at the chunk's center so the attempt count is stable.
+    let center = CHUNK_SIZE / 2;
+    let density = tree_density(chunk.biome(center, center));
+    let mut attempts = density.floor() as usize;
+    if rng.gen_bool(density.fract()) {
+        attempts += 1;
+    }
+
+    let mut trunks: Vec<(i32, i32)> = Vec::new();
+    let mut placer = Placer {
+        origin_x: chunk.x * CHUNK_SIZE as i32,
+        origin_z: chunk.z * CHUNK_SIZE as i32,
+        chunk,
+        spill: Vec::new(),
+    };
+
+    for _ in 0..attempts {
+        let x = rng.gen_range(0..CHUNK_SIZE);
+        let z = rng.gen_range(0..CHUNK_SIZE);
+        let Some(kind) = pick_tree(placer.chunk.biome(x, z), &mut rng) else {
+            continue;
+        };
+        // Shape parameters are drawn before any checks so each attempt uses the same amount
+        // of randomness whether or not it succeeds.
+        let shape: u32 = rng.gen();
+
+        let height = height_map[x][z];
+        if height <= SEA_LEVEL || height + 16 >= WORLD_HEIGHT {
+            continue;
+        }
+        let ground = placer.chunk.get_block(x, height - 1, z);
+        if !matches!(
+            ground,
+            BlockType::GrassBlock | BlockType::Podzol | BlockType::Dirt
+        ) || placer.chunk.get_block(x, height, z) != BlockType::Air
+        {
+            continue;
+        }
+        if kind == TreeKind::DarkOak && (x + 1 >= CHUNK_SIZE || z + 1 >= CHUNK_SIZE) {
+            continue;
+        }
+        if trunks.iter().any(|(tx, tz)| {
+            (tx - x as i32).abs() < MIN_TREE_SPACING && (tz - z as i32).abs() < MIN_TREE_SPACING
+        }) {
+            continue;
+        }
+        trunks.push((x as i32, z as i32));
+
+        placer.chunk.set_block(x, height - 1, z, BlockType::Dirt);
+        let world_x = placer.origin_x + x as i32;
+        let world_z = placer.origin_z + z as i32;
+        grow_tree(&mut placer, kind, world_x, height as i32, world_z, shape);
+    }
+
+    placer.spill
+}
+
+/// Place a tree whose trunk starts at `(x, y, z)`; `shape` varies its height and form.
+fn grow_tree(placer: &mut Placer, kind: TreeKind, x: i32, y: i32, z: i32, shape: u32) {
+    let log = kind.log();
+    match kind {
+        TreeKind::Oak | TreeKind::Birch => {
+            let trunk = 4 + (shape % 3) as i32 + if kind == TreeKind::Birch { 1 } else { 0 };
+            let top = y + trunk;
+            placer.leaf_layer(x, top - 2, z, 2, shape & 8 != 0);
+            placer.leaf_layer(x, top - 1, z, 2, true);
+            placer.leaf_layer(x, top, z, 1, false);
+            placer.leaf_layer(x, top + 1, z, 1, true);
+            for dy in 0..trunk {
+                placer.set(x, y + dy, z, log);
+            }
+        }
+        TreeKind::Spruce => {
+            let trunk = 6 + (shape % 4) as i32;
+            let top = y + trunk;
+            placer.set(x, top + 1, z, BlockType::OakLeaves);
+            placer.leaf_layer(x, top, z, 1, true);
+            // Alternate narrow and wide rings down the trunk, widening toward the base.
+            let mut radius = 1;
+            for level in (y + 2..top).rev() {
+                let ring = if (top - level) % 2 == 0 { 1 } else { radius };
+                placer.leaf_layer(x, level, z, ring, ring > 1);
+                if (top - level) % 2 == 1 && radius < 3 {
+                    radius += 1;
+                }
+            }
+            for dy in 0..trunk {
+                placer.set(x, y + dy, z, log);
+            }
+        }
+        TreeKind::Jungle => {
+            let trunk = 8 + (shape % 5) as i32;
+            let top = y + trunk;
+            placer.leaf_layer(x, top - 2, z, 3, true);
+            placer.leaf_layer(x, top - 1, z, 2, false);
+            placer.leaf_layer(x, top, z, 2, true);
+            placer.leaf_layer(x, top + 1, z, 1, true);
+            for dy in 0..trunk {
+                placer.set(x, y + dy, z, log);
+            }
+        }
+        TreeKind::Acacia => {
+            // Straight for a few blocks, then leaning diagonally toward one side.
+            let straight = 3 + (shape % 2) as i32;
+            let (lean_x, lean_z) = match (shape >> 4) % 4 {
+                0 => (1, 0),
+                1 => (-1, 0),
+                2 => (0, 1),
+                _ => (0, -1),
+            };
+            let (mut tip_x, mut tip_z) = (x, z);
+            let mut tip_y = y;
+            for dy in 0..straight {
+                placer.set(x, y + dy, z, log);
+                tip_y = y + dy;
+            }
+            for _ in 0..2 {
+                tip_x += lean_x;
+                tip_z += lean_z;
+                tip_y += 1;
+                placer.set(tip_x, tip_y, tip_z, log);
+            }
+            placer.leaf_layer(tip_x, tip_y + 1, tip_z, 2, true);
+            placer.leaf_layer(tip_x, tip_y + 2, tip_z, 1, false);
+        }
+        TreeKind::DarkOak => {
+            // 2x2 trunk under a wide, low canopy.
+            let trunk = 6 + (shape % 3) as i32;
+            let top = y + trunk;
+            let (cx, cz) = (x + 1, z + 1);
+            placer.leaf_layer(cx, top - 1, cz, 3, true);
+            placer.leaf_layer(cx, top, cz, 3, true);
+            placer.leaf_layer(cx, top + 1, cz, 2, true);
+            for dy in 0..trunk {
+                for (dx, dz) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    placer.set(x + dx, y + dy, z + dz, log);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,476 @@
+#![allow(dead_code)]
+/// Water and lava flow, driven by scheduled block ticks.
+///
+/// A fluid block at level 0 is a source, and so is any waterlogged block.
+/// Flowing fluid stores its distance from the nearest source (1 to
+/// `MAX_FLUID_LEVEL`) in its `level` property, or `FALLING` when it is fed from
+/// above. Whenever a fluid block or one of its neighbours
+/// changes, the fluid block is scheduled to update after its fluid's delay. An
+/// update first recalculates the level of flowing fluid from its neighbours
+/// (drying up if nothing feeds it any more), then flows down if it can and
+/// otherwise spreads sideways, preferring the directions that lead to the
+/// nearest drop. Lava touching water hardens into obsidian (source) or
+/// cobblestone (flowing), and lava flowing down into water turns it to stone.
+use super::block::BlockType;
+use super::chunk::World;
+use super::state::BlockState;
+use std::collections::{BTreeMap, HashSet, VecDeque};
+
+/// Flowing fluid this far from a source stops spreading.
+pub const MAX_FLUID_LEVEL: u8 = 7;
+/// Stored level of fluid falling from the block above.
+pub const FALLING: u8 = 8;
+
+const HORIZONTAL: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fluid {
+    Water,
+    Lava,
+}
+
+impl Fluid {
+    pub fn of(block: BlockType) -> Option<Self> {
+        match block {
+            BlockType::Water => Some(Fluid::Water),
+            BlockType::Lava => Some(Fluid::Lava),
+            _ => None,
+        }
+    }
+
+    /// The fluid in a block state, counting waterlogged blocks as water.
+    pub fn of_state(state: BlockState) -> Option<Self> {
+        if state.waterlogged() {
+            return Some(Fluid::Water);
+        }
+        Self::of(state.block)
+    }
+
+    pub fn block(self) -> BlockType {
+        match self {
+            Fluid::Water => BlockType::Water,
+            Fluid::Lava => BlockType::Lava,
+        }
+    }
+
+    /// Game ticks between an update being scheduled and running.
+    pub fn tick_delay(self) -> u64 {
+        match self {
+            Fluid::Water => 5,
+            Fluid::Lava => 30,
+        }
+    }
+
+    /// Levels lost per block of sideways flow.
+    fn level_drop(self) -> u8 {
+        match self {
+            Fluid::Water => 1,
+            Fluid::Lava => 2,
+        }
+    }
+
+    /// How far sideways flow looks for a way down.
+    fn slope_distance(self) -> u32 {
+        match self {
+            Fluid::Water => 4,
+            Fluid::Lava => 2,
+        }
+    }
+}
+
+/// Level that counts for spreading sideways; falling fluid spreads like a source.
+fn spread_level(level: u8) -> u8 {
+    if level == FALLING {
+        0
+    } else {
+        level
+    }
+}
+
+/// Surface height of a fluid block with the given level, from 0.0 to 1.0.
+pub fn fluid_height(level: u8) -> f32 {
+    if level == FALLING {
+        1.0
+    } else {
+        1.0 - level as f32 / (MAX_FLUID_LEVEL + 1) as f32
+    }
+}
+
+fn is_replaceable(block: BlockType) -> bool {
+    matches!(block, BlockType::Air | BlockType::CaveAir | BlockType::VoidAir)
+}
+
+/// Block updates waiting for a given game tick. A position is queued at most once.
+#[derive(Debug, Default)]
+pub struct TickQueue {
+    due: BTreeMap<u64, Vec<(i32, i32, i32)>>,
+    queued: HashSet<(i32, i32, i32)>,
+}
+
+impl TickQueue {
+    pub fn schedule(&mut self, tick: u64, pos: (i32, i32, i32)) {
+        if self.queued.insert(pos) {
+            self.due.entry(tick).or_default().push(pos);
+        }
+    }
+
+    /// Remove and return every update due at or before `tick`, oldest first.
+    pub fn take_due(&mut self, tick: u64) -> Vec<(i32, i32, i32)> {
+        let later = self.due.split_off(&(tick + 1));
+        let due: Vec<_> = std::mem::replace(&mut self.due, later)
+            .into_values()
+            .flatten()
+            .collect();
+        for pos in &due {
+            self.queued.remove(pos);
+        }
+        due
+    }
+
+    pub fn len(&self) -> usize {
+        self.queued.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queued.is_empty()
+    }
+}
+
+fn fluid_at(world: &World, pos: (i32, i32, i32)) -> Option<Fluid> {
+    world
+        .get_loaded_state(pos.0, pos.1, pos.2)
+        .and_then(Fluid::of_state)
+}
+
+/// Can `fluid` at `level` flow into this position?
+fn can_flow_into(world: &World, fluid: Fluid, pos: (i32, i32, i32), level: u8) -> bool {
+    let Some(state) = world.get_loaded_state(pos.0, pos.1, pos.2) else {
+        return false;
+    };
+    if is_replaceable(state.block) {
+        return true;
+    }
+    match Fluid::of_state(state) {
+        // Waterlogged blocks are sources, so this never replaces them.
+        Some(other) if other == fluid => {
+            let current = state.level();
+            current != 0 && current != FALLING && (level == FALLING || level < current)
+        }
+        // Lava pouring down into water is handled by `flow_into`.
+        Some(_) => fluid == Fluid::Lava && state.block == BlockType::Water,
+        None => false,
+    }
+}
+
+/// Whether flow can pass through a position: open space or flowing fluid of the same kind.
+fn is_open(world: &World, fluid: Fluid, pos: (i32, i32, i32)) -> bool {
+    match world.get_loaded_state(pos.0, pos.1, pos.2) {
+        Some(state) if Fluid::of_state(state) == Some(fluid) => state.level() != 0,
+        Some(state) => is_replaceable(state.block),
+        None => false,
+    }
+}
+
+/// Whether fluid above this position pours down rather than spreading sideways:
+/// the space below is open or already holds the same fluid.
+fn is_hole(world: &World, fluid: Fluid, pos: (i32, i32, i32)) -> bool {
+    if fluid_at(world, pos) == Some(fluid) {
+        return true;
+    }
+    can_flow_into(world, fluid, pos, FALLING)
+}
+
+fn flow_into(world: &mut World, fluid: Fluid, pos: (i32, i32, i32), level: u8) {
+    let target = world.get_loaded_block(pos.0, pos.1, pos.2);
+    if fluid == Fluid::Lava && target == Some(BlockType::Water) {
+        if level == FALLING {
+            world.set_fluid_at(pos.0, pos.1, pos.2, BlockType::Stone, 0);
+        }
+        return;
+    }
+    world.set_fluid_at(pos.0, pos.1, pos.2, fluid.block(), level);
+}
+
+/// The level a flowing block should have given its neighbours, or `None` if it dries up.
+fn fed_level(world: &World, fluid: Fluid, (x, y, z): (i32, i32, i32)) -> Option<u8> {
+    let same = |pos: (i32, i32, i32)| fluid_at(world, pos) == Some(fluid);
+    let level = |pos: (i32, i32, i32)| world.fluid_level_at(pos.0, pos.1, pos.2).unwrap_or(0);
+
+    if fluid == Fluid::Water {
+        let sources = HORIZONTAL
+            .iter()
+            .filter(|(dx, dz)| same((x + dx, y, z + dz)) && level((x + dx, y, z + dz)) == 0)
+            .count();
+        let below = (x, y - 1, z);
+        let supported = world
+            .get_loaded_block(below.0, below.1, below.2)
+            .map_or(false, |block| block.is_solid() && Fluid::of(block).is_none())
+            || (same(below) && level(below) == 0);
+        if sources >= 2 && supported {
+            return Some(0);
+        }
+    }
+
+    if same((x, y + 1, z)) {
+        return Some(FALLING);
+    }
+
+    HORIZONTAL
+        .iter()
+        .map(|(dx, dz)| (x + dx, y, z + dz))
+        .filter(|&pos| same(pos))
+        .map(|pos| spread_level(level(pos)) + fluid.level_drop())
+        .min()
+        .filter(|&next| next <= MAX_FLUID_LEVEL)
+}
+
+/// Sideways directions to spread in: those with the shortest way down, or all
+/// open directions if there is no drop within reach. Directions already covered
+/// by flowing fluid still count, so a spread that has started stays on course.
+fn spread_directions(world: &World, fluid: Fluid, (x, y, z): (i32, i32, i32)) -> Vec<(i32, i32)> {
+    let mut best = u32::MAX;
+    let mut directions = Vec::new();
+    for (dx, dz) in HORIZONTAL {
+        let start = (x + dx, y, z + dz);
+        if !is_open(world, fluid, start) {
+            continue;
+        }
+        let distance = distance_to_drop(world, fluid, start, (x, z)).unwrap_or(u32::MAX - 1);
+        if distance < best {
+            best = distance;
+            directions.clear();
+        }
+        if distance == best {
+            directions.push((dx, dz));
+        }
+    }
+    directions
+}
+
+/// Steps from `start` to the nearest block fluid could fall from, not passing back through `origin`.
+fn distance_to_drop(world: &World, fluid: Fluid, start: (i32, i32, i32), origin: (i32, i32)) -> Option<u32> {
+    let (_, y, _) = start;
+    let mut seen = HashSet::from([(origin.0, origin.1), (start.0, start.2)]);
+    let mut queue = VecDeque::from([((start.0, start.2), 0)]);
+    while let Some(((x, z), distance)) = queue.pop_front() {
+        if is_hole(world, fluid, (x, y - 1, z)) {
+            return Some(distance);
+        }
+        if distance >= fluid.slope_distance() {
+            continue;
+        }
+        for (dx, dz) in HORIZONTAL {
+            let next = (x + dx, z + dz);
+            if seen.insert(next) && is_open(world, fluid, (next.0, y, next.1)) {
+                queue.push_back((next, distance + 1));
+            }
+        }
+    }
+    None
+}
+
+/// Run a scheduled update for the fluid block at a position, if there still is one.
+pub(super) fn update(world: &mut World, (x, y, z): (i32, i32, i32)) {
+    let Some(fluid) = fluid_at(world, (x, y, z)) else {
+        return;
+    };
+    let mut level = world.fluid_level_at(x, y, z).unwrap_or(0);
+
+    if fluid == Fluid::Lava {
+        let touches_water = [(1, 0, 0), (-1, 0, 0), (0, 1, 0), (0, 0, 1), (0, 0, -1)]
+            .iter()
+            .any(|(dx, dy, dz)| fluid_at(world, (x + dx, y + dy, z + dz)) == Some(Fluid::Water));
+        if touches_water {
+            let hardened = if level == 0 {
+                BlockType::Obsidian
+            } else {
+                BlockType::Cobblestone
+            };
+            world.set_fluid_at(x, y, z, hardened, 0);
+            return;
+        }
+    }
+
+    if level != 0 {
+        match fed_level(world, fluid, (x, y, z)) {
+            None => {
+                world.set_fluid_at(x, y, z, BlockType::Air, 0);
+                return;
+            }
+            Some(fed) if fed != level => {
+                world.set_fluid_at(x, y, z, fluid.block(), fed);
+                level = fed;
+            }
+            Some(_) => {}
+        }
+    }
+
+    let below = (x, y - 1, z);
+    if is_hole(world, fluid, below) {
+        if can_flow_into(world, fluid, below, FALLING) {
+            flow_into(world, fluid, below, FALLING);
+        }
+        return;
+    }
+
+    let next = spread_level(level) + fluid.level_drop();
+    if next > MAX_FLUID_LEVEL {
+        return;
+    }
+    for (dx, dz) in spread_directions(world, fluid, (x, y, z)) {
+        let target = (x + dx, y, z + dz);
+        if can_flow_into(world, fluid, target, next) {
+            flow_into(world, fluid, target, next);
+        }
+    }
+}
+
+/// Direction fluid at a position is flowing in, for pushing entities along.
+/// Zero for still fluid and non-fluid blocks.
+pub fn flow_direction(world: &World, x: i32, y: i32, z: i32) -> [f32; 3] {
+    let Some(fluid) = fluid_at(world, (x, y, z)) else {
+        return [0.0; 3];
+    };
+    let height = fluid_height(world.fluid_level_at(x, y, z).unwrap_or(0));
+
+    let mut flow = [0.0f32; 3];
+    for (dx, dz) in HORIZONTAL {
+        let (nx, nz) = (x + dx, z + dz);
+        let neighbour = world.get_loaded_block(nx, y, nz);
+        let drop = match fluid_at(world, (nx, y, nz)) {
+            Some(other) if other == fluid => {
+                height - fluid_height(world.fluid_level_at(nx, y, nz).unwrap_or(0))
+            }
+            // Fluid is about to pour over an open edge.
+            _ if neighbour.map_or(false, is_replaceable) => height,
+            _ => 0.0,
+        };
+        flow[0] += dx as f32 * drop;
+        flow[2] += dz as f32 * drop;
+    }
+    if world.fluid_level_at(x, y, z) == Some(FALLING) {
+        flow[1] = -1.0;
+    }
+
+    let length = flow.iter().map(|c| c * c).sum::<f32>().sqrt();
+    if length > 0.0 {
+        flow.map(|c| c / length)
+    } else {
+        flow
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// First air layer above the default superflat ground.
+    const SURFACE: i32 = 4;
+
+    /// A superflat world with the chunks around the origin loaded.
+    fn flat_world() -> World {
+        let mut world = World::with_generator(None, Some(1), "flat", serde_json::Value::Null).unwrap();
+        for chunk_x in -1..=1 {
+            for chunk_z in -1..=1 {
+                world.get_chunk(chunk_x, chunk_z);
+            }
+        }
+        world
+    }
+
+    /// Tick until no updates are scheduled.
+    fn settle(world: &mut World) {
+        for _ in 0..2000 {
+            if world.scheduled_tick_count() == 0 {
+                return;
+            }
+            world.tick();
+        }
+        panic!("fluid never settled");
+    }
+
+    fn block(world: &World, x: i32, z: i32) -> Option<BlockType> {
+        world.get_loaded_block(x, SURFACE, z)
+    }
+
+    #[test]
+    fn water_spreads_to_max_level_and_stops() {
+        let mut world = flat_world();
+        world.set_block_at(8, SURFACE, 8, BlockType::Water);
+        settle(&mut world);
+
+        for distance in 1..=MAX_FLUID_LEVEL as i32 {
+            assert_eq!(block(&world, 8 + distance, 8), Some(BlockType::Water));
+            assert_eq!(world.fluid_level_at(8 + distance, SURFACE, 8), Some(distance as u8));
+        }
+        assert_eq!(world.fluid_level_at(10, SURFACE, 11), Some(5));
+        assert_eq!(block(&world, 9 + MAX_FLUID_LEVEL as i32, 8), Some(BlockType::Air));
+        assert_eq!(block(&world, 12, 12), Some(BlockType::Air));
+    }
+
+    #[test]
+    fn flowing_water_dries_up_without_its_source() {
+        let mut world = flat_world();
+        world.set_block_at(8, SURFACE, 8, BlockType::Water);
+        settle(&mut world);
+        world.set_block_at(8, SURFACE, 8, BlockType::Air);
+        settle(&mut world);
+
+        for x in 0..=16 {
+            for z in 0..=16 {
+                assert_eq!(block(&world, x, z), Some(BlockType::Air), "water left at {}, {}", x, z);
+            }
+        }
+    }
+
+    #[test]
+    fn lava_source_next_to_water_becomes_obsidian() {
+        let mut world = flat_world();
+        world.set_block_at(8, SURFACE, 8, BlockType::Lava);
+        world.set_block_at(9, SURFACE, 8, BlockType::Water);
+        settle(&mut world);
+
+        assert_eq!(block(&world, 8, 8), Some(BlockType::Obsidian));
+    }
+
+    #[test]
+    fn flowing_lava_next_to_water_becomes_cobblestone() {
+        let mut world = flat_world();
+        world.set_block_at(8, SURFACE, 8, BlockType::Lava);
+        settle(&mut world);
+        assert_eq!(world.fluid_level_at(8, SURFACE, 11), Some(6));
+
+        world.set_block_at(8, SURFACE, 12, BlockType::Water);
+        settle(&mut world);
+
+        assert_eq!(block(&world, 8, 11), Some(BlockType::Cobblestone));
+        assert_eq!(block(&world, 8, 8), Some(BlockType::Lava));
+    }
+
+    #[test]
+    fn lava_falling_into_water_becomes_stone() {
+        let mut world = flat_world();
+        world.set_block_at(8, SURFACE, 8, BlockType::Water);
+        settle(&mut world);
+        world.set_block_at(8, SURFACE + 2, 8, BlockType::Lava);
+        settle(&mut world);
+
+        assert_eq!(block(&world, 8, 8), Some(BlockType::Stone));
+    }
+
+    #[test]
+    fn take_due_returns_updates_in_tick_order_once() {
+        let mut queue = TickQueue::default();
+        queue.schedule(5, (1, 0, 0));
+        queue.schedule(3, (2, 0, 0));
+        queue.schedule(3, (1, 0, 0));
+        queue.schedule(7, (3, 0, 0));
+        assert_eq!(queue.len(), 3);
+
+        assert_eq!(queue.take_due(5), vec![(2, 0, 0), (1, 0, 0)]);
+        assert_eq!(queue.take_due(6), vec![]);
+        queue.schedule(8, (1, 0, 0));
+        assert_eq!(queue.take_due(10), vec![(3, 0, 0), (1, 0, 0)]);
+        assert!(queue.is_empty());
+    }
+}
@@ -0,0 +1,87 @@
+#![allow(dead_code)]
+/// Game modes and the rules each one plays by.
+///
+/// The mode belongs to the player: single player keeps it in the world's
+/// metadata, and a server keeps one per connected player and tells clients
+/// which one they have. Code that needs to know whether something is allowed
+/// asks the mode rather than matching on it, so the rules live in one place.
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GameMode {
+    #[default]
+    Survival,
+    Creative,
+    /// Survival rules, but the world can't be changed.
+    Adventure,
+    /// Invisible observer flying through blocks.
+    Spectator,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 4] = [
+        GameMode::Survival,
+        GameMode::Creative,
+        GameMode::Adventure,
+        GameMode::Spectator,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GameMode::Survival => "survival",
+            GameMode::Creative => "creative",
+            GameMode::Adventure => "adventure",
+            GameMode::Spectator => "spectator",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|mode| mode.name() == name)
+    }
+
+    /// The mode after this one, for cycling through them with a key.
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|mode| *mode == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// The player may toggle flight.
+    pub fn allows_flight(self) -> bool {
+        matches!(self, GameMode::Creative | GameMode::Spectator)
+    }
+
+    /// The player flies all the time and can't land.
+    pub fn always_flying(self) -> bool {
+        self == GameMode::Spectator
+    }
+
+    /// The player collides with blocks.
+    pub fn has_collision(self) -> bool {
+        self != GameMode::Spectator
+    }
+
+    /// Blocks can be broken and placed.
+    pub fn can_modify_world(self) -> bool {
+        matches!(self, GameMode::Survival | GameMode::Creative)
+    }
+
+    /// Blocks break on the first click, even unbreakable ones, instead of being mined.
+    pub fn breaks_instantly(self) -> bool {
+        self == GameMode::Creative
+    }
+
+    /// Placing a block uses one up from the inventory.
+    pub fn consumes_items(self) -> bool {
+        matches!(self, GameMode::Survival | GameMode::Adventure)
+    }
+
+    /// Dropped items go into the player's inventory when they walk over them.
+    pub fn picks_up_items(self) -> bool {
+        self != GameMode::Spectator
+    }
+
+    pub fn takes_damage(self) -> bool {
+        matches!(self, GameMode::Survival | GameMode::Adventure)
+    }
+}
@@ -0,0 +1,259 @@
+#![allow(dead_code)]
+use super::block::BlockType;
+use super::chunk::{Chunk, CHUNK_SIZE, WORLD_HEIGHT};
+use super::biome::{Biome, BiomeSource, HeightParams};
+use super::caves::{CaveCarver, CaveConfig};
+use super::decoration::{self, PendingBlock};
+use super::ores::OreConfig;
+use super::presets::{FlatGenerator, FlatOptions, VoidGenerator};
+use noise::{NoiseFn, Perlin};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::sync::Arc;
+
+pub const SEA_LEVEL: usize = 63;
+/// Columns above this height get bare stone instead of the biome's surface block.
+const BARE_PEAK_HEIGHT: usize = 120;
+/// Biome height parameters are sampled every `BLEND_STEP` blocks and averaged over
+/// `BLEND_RADIUS` blocks around each column.
+const BLEND_STEP: i32 = 4;
+const BLEND_RADIUS: i32 = 8;
+
+/// Random stream for one generation feature in one chunk, independent of generation order.
+pub(super) fn chunk_rng(seed: u32, chunk_x: i32, chunk_z: i32, salt: u64) -> StdRng {
+    StdRng::seed_from_u64(
+        (seed as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+            ^ (chunk_x as i64 as u64).wrapping_mul(0x85EB_CA77_C2B2_AE63)
+            ^ (chunk_z as i64 as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F)
+            ^ salt,
+    )
+}
+
+/// Produces the contents of chunks that have never been saved. `World` owns one,
+/// chosen by the `generator` name in `world_meta.json`, and shares it with the
+/// loader threads.
+pub trait WorldGenerator: Send + Sync {
+    /// Name stored in `world_meta.json`.
+    fn name(&self) -> &'static str;
+
+    /// Generate a chunk. Decoration blocks that land in neighbouring chunks are
+    /// returned alongside it for the world to apply.
+    fn generate_chunk(&self, chunk_x: i32, chunk_z: i32) -> (Chunk, Vec<PendingBlock>);
+}
+
+pub const DEFAULT_GENERATOR: &str = "noise";
+
+/// Generator names paired with the label shown when creating a world.
+pub const GENERATOR_PRESETS: &[(&str, &str)] = &[
+    ("noise", "Default"),
+    ("flat", "Superflat"),
+    ("void", "Void"),
+];
+
+/// Build the generator called `name`. `options` holds the generator-specific settings
+/// from `world_meta.json` and may be null.
+pub fn create_generator(
+    name: &str,
+    seed: u32,
+    options: &serde_json::Value,
+) -> anyhow::Result<Arc<dyn WorldGenerator>> {
+    let generator: Arc<dyn WorldGenerator> = match name {
+        "noise" => Arc::new(TerrainGenerator::with_config(
+            seed,
+            CaveConfig::default(),
+            OreConfig::load_or_default(),
+        )),
+        "flat" => {
+            let options = if options.is_null() {
+                FlatOptions::default()
+            } else {
+                serde_json::from_value(options.clone())?
+            };
+            Arc::new(FlatGenerator::new(options))
+        }
+        "void" => Arc::new(VoidGenerator),
+        other => anyhow::bail!("unknown world generator '{}'", other),
+    };
+    Ok(generator)
+}
+
+/// Seeded terrain generator. It holds no mutable state, so worker threads can share it.
+pub struct TerrainGenerator {
+    noise: Perlin,
+    biomes: BiomeSource,
+    caves: CaveCarver,
+    ores: OreConfig,
+    seed: u32,
+}
+
+impl TerrainGenerator {
+    pub fn new(seed: u32) -> Self {
+        Self::with_config(seed, CaveConfig::default(), OreConfig::default())
+    }
+
+    pub fn with_config(seed: u32, cave_config: CaveConfig, ore_config: OreConfig) -> Self {
+        Self {
+            noise: Perlin::new(seed),
+            biomes: BiomeSource::new(seed),
+            caves: CaveCarver::new(seed, cave_config),
+            ores: ore_config,
+            seed,
+        }
+    }
+
+    pub fn seed(&self) -> u32 {
+        self.seed
+    }
+
+    pub fn biome_at(&self, world_x: i32, world_z: i32) -> Biome {
+        self.biomes.biome_at(world_x, world_z)
+    }
+
+    /// Height parameters sampled every `BLEND_STEP` blocks over the chunk plus a
+    /// `BLEND_RADIUS` margin on each side.
+    fn biome_height_grid(&self, chunk_world_x: i32, chunk_world_z: i32) -> Vec<Vec<HeightParams>> {
+        let points = (CHUNK_SIZE as i32 + 2 * BLEND_RADIUS) / BLEND_STEP + 1;
+        (0..points)
+            .map(|gx| {
+                (0..points)
+                    .map(|gz| {
+                        let world_x = chunk_world_x - BLEND_RADIUS + gx * BLEND_STEP;
+                        let world_z = chunk_world_z - BLEND_RADIUS + gz * BLEND_STEP;
+                        self.biomes.biome_at(world_x, world_z).height_params()
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Average the grid samples within `BLEND_RADIUS` of a column.
+    fn blend_height_params(grid: &[Vec<HeightParams>], x: usize, z: usize) -> HeightParams {
+        let mut base = 0.0;
+        let mut amplitude = 0.0;
+        let mut count = 0.0;
+        for (gx, column) in grid.iter().enumerate() {
+            let dx = gx as i32 * BLEND_STEP - BLEND_RADIUS - x as i32;
+            if dx.abs() > BLEND_RADIUS {
+                continue;
+            }
+            for (gz, params) in column.iter().enumerate() {
+                let dz = gz as i32 * BLEND_STEP - BLEND_RADIUS - z as i32;
+                if dz.abs() > BLEND_RADIUS {
+                    continue;
+                }
+                base += params.base;
+                amplitude += params.amplitude;
+                count += 1.0;
+            }
+        }
+        HeightParams {
+            base: base / count,
+            amplitude: amplitude / count,
+        }
+    }
+
+    fn sample_noise(&self, x: f64, z: f64, octaves: i32, persistence: f64, scale: f64) -> f64 {
+        let mut value = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = scale;
+        let mut max_value = 0.0;
+
+        for _ in 0..octaves {
+            value += self.noise.get([x * frequency, z * frequency]) * amplitude;
+            max_value += amplitude;
+            amplitude *= persistence;
+            frequency *= 2.0;
+        }
+
+        (value / max_value + 1.0) / 2.0 // Normalize to 0-1
+    }
+}
+
+impl WorldGenerator for TerrainGenerator {
+    fn name(&self) -> &'static str {
+        "noise"
+    }
+
+    fn generate_chunk(&self, chunk_x: i32, chunk_z: i32) -> (Chunk, Vec<PendingBlock>) {
+        let mut chunk = Chunk::new(chunk_x, chunk_z);
+        let chunk_world_x = chunk_x * CHUNK_SIZE as i32;
+        let chunk_world_z = chunk_z * CHUNK_SIZE as i32;
+
+        // Biome per column, plus a coarse grid of biome samples around the chunk that
+        // the height curve is blended over so neighbouring biomes meet without cliffs.
+        let grid = self.biome_height_grid(chunk_world_x, chunk_world_z);
+        let mut height_map = [[0usize; CHUNK_SIZE]; CHUNK_SIZE];
+        for x in 0..CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE {
+                let world_x = chunk_world_x + x as i32;
+                let world_z = chunk_world_z + z as i32;
+                chunk.set_biome(x, z, self.biomes.biome_at(world_x, world_z));
+
+                let params = Self::blend_height_params(&grid, x, z);
+                let terrain =
+                    self.sample_noise(world_x as f64, world_z as f64, 6, 0.5, 0.005) * 2.0 - 1.0;
+                let hills =
+                    self.sample_noise(world_x as f64, world_z as f64, 3, 0.7, 0.02) * 2.0 - 1.0;
+
+                height_map[x][z] = (params.base
+                    + terrain * params.amplitude
+                    + hills * params.amplitude * 0.3)
+                    .max(1.0)
+                    .min(WORLD_HEIGHT as f64 - 1.0) as usize;
+            }
+        }
+
+        // Generate blocks
+        for x in 0..CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE {
+                let height = height_map[x][z];
+                let biome = chunk.biome(x, z);
+                let surface = if height <= SEA_LEVEL {
+                    biome.underwater_surface_block()
+                } else if height > BARE_PEAK_HEIGHT {
+                    BlockType::Stone // Bare mountain peaks
+                } else {
+                    biome.surface_block()
+                };
+
+                for y in 0..WORLD_HEIGHT {
+                    let block = if y == 0 {
+                        // Bedrock layer
+                        BlockType::Bedrock
+                    } else if y < height.saturating_sub(3) {
+                        // Deep stone
+                        if y < 16 {
+                            BlockType::Deepslate
+                        } else {
+                            BlockType::Stone
+                        }
+                    } else if y < height.saturating_sub(1) {
+                        // Filler layer
+                        if surface == BlockType::Stone {
+                            BlockType::Stone
+                        } else {
+                            biome.filler_block()
+                        }
+                    } else if y < height {
+                        surface
+                    } else if y < SEA_LEVEL {
+                        // Water below sea level
+                        BlockType::Water
+                    } else {
+                        BlockType::Air
+                    };
+
+                    chunk.set_block(x, y, z, block);
+                }
+
+            }
+        }
+
+        self.ores
+            .place_veins(self.seed, &mut chunk, |x, z| self.biomes.biome_at(x, z));
+        self.caves.carve_chunk(&mut chunk, &height_map);
+        let spill = decoration::decorate_chunk(self.seed, &mut chunk, &height_map);
+
+        (chunk, spill)
+    }
+}
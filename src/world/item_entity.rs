@@ -0,0 +1,227 @@
+#![allow(dead_code)]
+/// Dropped items lying in the world.
+///
+/// Items pop out of broken blocks according to the block's drop table, or are
+/// thrown by the player. They fall under gravity, slide to a stop on the
+/// ground, merge with identical items nearby and despawn after five minutes.
+/// Once their pickup delay is over they drift towards a player in reach and
+/// go into the inventory, leaving behind whatever doesn't fit. They only live
+/// in memory and aren't saved with the world.
+use super::block::BlockType;
+use super::chunk::World;
+use super::mining::{self, Tool};
+use super::shape::BlockBox;
+use crate::inventory::{Inventory, ItemStack};
+use glam::{IVec3, Vec3};
+use rand::Rng;
+
+// Speeds are in blocks per second, accelerations in blocks per second squared.
+const GRAVITY: f32 = 16.0;
+const TERMINAL_VELOCITY: f32 = 40.0;
+/// Share of horizontal speed kept after a second in the air, and on the ground.
+const AIR_DRAG: f32 = 0.67;
+const GROUND_DRAG: f32 = 0.01;
+/// Half the edge of an item's collision box.
+pub const ITEM_HALF_SIZE: f32 = 0.125;
+/// Seconds an item lies around before it disappears.
+pub const DESPAWN_TIME: f32 = 300.0;
+/// Seconds before anyone can pick up an item from a broken block, or a thrown one.
+pub const BLOCK_DROP_PICKUP_DELAY: f32 = 0.5;
+pub const THROW_PICKUP_DELAY: f32 = 2.0;
+/// Items of the same kind closer than this merge into one.
+const MERGE_DISTANCE: f32 = 0.5;
+/// Items within this distance of a player are pulled towards them.
+const MAGNET_DISTANCE: f32 = 3.0;
+const MAGNET_ACCELERATION: f32 = 40.0;
+/// Items this close to a player are picked up.
+const PICKUP_DISTANCE: f32 = 1.0;
+const THROW_SPEED: f32 = 6.0;
+
+#[derive(Debug, Clone)]
+pub struct ItemEntity {
+    pub stack: ItemStack,
+    /// Centre of the item.
+    pub position: Vec3,
+    pub velocity: Vec3,
+    /// Seconds since the item was dropped.
+    pub age: f32,
+    /// Seconds left before it can be picked up.
+    pub pickup_delay: f32,
+    pub on_ground: bool,
+}
+
+impl ItemEntity {
+    pub fn new(stack: ItemStack, position: Vec3, velocity: Vec3, pickup_delay: f32) -> Self {
+        Self {
+            stack,
+            position,
+            velocity,
+            age: 0.0,
+            pickup_delay,
+            on_ground: false,
+        }
+    }
+
+    fn collision_box(&self) -> BlockBox {
+        let min = self.position - Vec3::splat(ITEM_HALF_SIZE);
+        let max = self.position + Vec3::splat(ITEM_HALF_SIZE);
+        BlockBox::new(min.to_array(), max.to_array())
+    }
+
+    fn update(&mut self, world: &World, dt: f32) {
+        self.age += dt;
+        self.pickup_delay = (self.pickup_delay - dt).max(0.0);
+
+        self.velocity.y = (self.velocity.y - GRAVITY * dt).max(-TERMINAL_VELOCITY);
+        let drag = if self.on_ground { GROUND_DRAG } else { AIR_DRAG }.powf(dt);
+        self.velocity.x *= drag;
+        self.velocity.z *= drag;
+
+        for axis in 0..3 {
+            let delta = self.velocity[axis] * dt;
+            if delta.abs() <= f32::EPSILON {
+                continue;
+            }
+            let allowed = world.sweep_box(self.collision_box(), axis, delta);
+            self.position[axis] += allowed;
+            if allowed != delta {
+                self.velocity[axis] = 0.0;
+            }
+        }
+
+        let below = world.sweep_box(self.collision_box(), 1, -0.01);
+        self.on_ground = below > -0.01;
+    }
+}
+
+/// The dropped items in a world.
+#[derive(Debug, Default)]
+pub struct ItemEntities {
+    items: Vec<ItemEntity>,
+}
+
+impl ItemEntities {
+    pub fn iter(&self) -> impl Iterator<Item = &ItemEntity> {
+        self.items.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    pub fn spawn(&mut self, item: ItemEntity) {
+        if item.stack.count > 0 {
+            self.items.push(item);
+        }
+    }
+
+    /// Pop a stack out of the middle of the block at `pos` with a small random hop.
+    pub fn drop_at_block(&mut self, pos: IVec3, stack: ItemStack) {
+        let mut rng = rand::thread_rng();
+        let position = pos.as_vec3() + Vec3::new(
+            rng.gen_range(0.25..0.75),
+            rng.gen_range(0.25..0.75),
+            rng.gen_range(0.25..0.75),
+        );
+        let velocity = Vec3::new(rng.gen_range(-1.0..1.0), 2.0, rng.gen_range(-1.0..1.0));
+        self.spawn(ItemEntity::new(stack, position, velocity, BLOCK_DROP_PICKUP_DELAY));
+    }
+
+    /// Drop what `block`'s drop table gives when it is mined with `tool`. Blocks
+    /// that need a better tool than `tool` drop nothing.
+    pub fn drop_block_loot(&mut self, pos: IVec3, block: BlockType, tool: Option<Tool>) {
+        if !mining::can_harvest(block, tool) {
+            return;
+        }
+        let mut rng = rand::thread_rng();
+        for drop in block.definition().drops() {
+            if drop.chance < 1.0 && !rng.gen_bool(drop.chance.clamp(0.0, 1.0) as f64) {
+                continue;
+            }
+            let count = drop.count.min(u16::MAX as u32) as u16;
+            self.drop_at_block(pos, ItemStack { id: drop.item, count });
+        }
+    }
+
+    /// Throw a stack from `eye` in the direction the player is looking.
+    pub fn throw(&mut self, stack: ItemStack, eye: Vec3, direction: Vec3) {
+        let position = eye - Vec3::Y * 0.3 + direction * 0.3;
+        let velocity = direction.normalize_or_zero() * THROW_SPEED + Vec3::Y;
+        self.spawn(ItemEntity::new(stack, position, velocity, THROW_PICKUP_DELAY));
+    }
+
+    /// Move every item, merge neighbours and remove those that have despawned.
+    pub fn update(&mut self, world: &World, dt: f32) {
+        for item in &mut self.items {
+            item.update(world, dt);
+        }
+        self.items.retain(|item| item.age < DESPAWN_TIME);
+        self.merge();
+    }
+
+    /// Fold items into an older neighbour of the same kind while its stack has room.
+    fn merge(&mut self) {
+        for i in 0..self.items.len() {
+            for j in i + 1..self.items.len() {
+                let (head, tail) = self.items.split_at_mut(j);
+                let (a, b) = (&mut head[i], &mut tail[0]);
+                if a.stack.count == 0 || b.stack.count == 0 || a.stack.id != b.stack.id {
+                    continue;
+                }
+                if a.position.distance(b.position) > MERGE_DISTANCE {
+                    continue;
+                }
+                let (into, from) = if a.age >= b.age { (a, b) } else { (b, a) };
+                let moved = from.stack.count.min(into.stack.max_stack_size().saturating_sub(into.stack.count));
+                into.stack.count += moved;
+                from.stack.count -= moved;
+                into.pickup_delay = into.pickup_delay.max(from.pickup_delay);
+            }
+        }
+        self.items.retain(|item| item.stack.count > 0);
+    }
+
+    /// Pull items towards a player whose body is centred on `target` and put
+    /// those in reach into `inventory`. Returns the stacks picked up.
+    pub fn collect(&mut self, target: Vec3, inventory: &mut Inventory, dt: f32) -> Vec<ItemStack> {
+        let mut picked_up = Vec::new();
+        for item in &mut self.items {
+            if item.pickup_delay > 0.0 {
+                continue;
+            }
+            let offset = target - item.position;
+            let distance = offset.length();
+            if distance > MAGNET_DISTANCE {
+                continue;
+            }
+            if distance > PICKUP_DISTANCE {
+                item.velocity += offset / distance * MAGNET_ACCELERATION * dt;
+                continue;
+            }
+
+            let count = item.stack.count;
+            match inventory.add_item(item.stack.clone()) {
+                Ok(()) => {
+                    picked_up.push(item.stack.clone());
+                    item.stack.count = 0;
+                }
+                Err(left) => {
+                    if left.count < count {
+                        picked_up.push(ItemStack { id: left.id.clone(), count: count - left.count });
+                    }
+                    item.stack = left;
+                }
+            }
+        }
+        self.items.retain(|item| item.stack.count > 0);
+        picked_up
+    }
+}
@@ -0,0 +1,418 @@
+#![allow(dead_code)]
+/// Sky light and block light, 0 to 15 per block.
+///
+/// Light lives next to the blocks in each `Chunk` but is not saved; it is
+/// computed when a chunk is loaded. A fresh chunk is first lit on its own
+/// (`light_chunk`): sky light falls straight down each column until something
+/// absorbs it, then both kinds spread outwards with a breadth-first flood fill
+/// that loses one level per step (more through water and leaves). Once the
+/// chunk is in the world, `stitch_chunk` lets light flow across its borders,
+/// and `update_block` repairs the light around a changed block by first
+/// removing what the old block let through and then refilling from the
+/// surrounding light.
+use super::block::BlockType;
+use super::chunk::{Chunk, CHUNK_SIZE, WORLD_HEIGHT};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+pub const MAX_LIGHT: u8 = 15;
+
+const LIGHT_VOLUME: usize = CHUNK_SIZE * WORLD_HEIGHT * CHUNK_SIZE;
+
+const DIRECTIONS: [(i32, i32, i32); 6] = [
+    (1, 0, 0),
+    (-1, 0, 0),
+    (0, 1, 0),
+    (0, -1, 0),
+    (0, 0, 1),
+    (0, 0, -1),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LightKind {
+    Sky,
+    Block,
+}
+
+impl LightKind {
+    const ALL: [LightKind; 2] = [LightKind::Sky, LightKind::Block];
+}
+
+/// Both light values of every block in a chunk, packed one byte per block with
+/// sky light in the high nibble. Empty until the chunk has been lit.
+#[derive(Debug, Clone, Default)]
+pub struct LightMap {
+    data: Vec<u8>,
+}
+
+impl LightMap {
+    /// A fully dark map.
+    pub fn dark() -> Self {
+        Self {
+            data: vec![0; LIGHT_VOLUME],
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn heap_size(&self) -> usize {
+        self.data.capacity()
+    }
+
+    fn index(x: usize, y: usize, z: usize) -> usize {
+        x + z * CHUNK_SIZE + y * CHUNK_SIZE * CHUNK_SIZE
+    }
+
+    /// Unlit maps read as full sky light so unlit chunks don't render black.
+    pub fn get(&self, kind: LightKind, x: usize, y: usize, z: usize) -> u8 {
+        let Some(&packed) = self.data.get(Self::index(x, y, z)) else {
+            return if kind == LightKind::Sky { MAX_LIGHT } else { 0 };
+        };
+        match kind {
+            LightKind::Sky => packed >> 4,
+            LightKind::Block => packed & 0x0F,
+        }
+    }
+
+    pub fn set(&mut self, kind: LightKind, x: usize, y: usize, z: usize, level: u8) {
+        let Some(packed) = self.data.get_mut(Self::index(x, y, z)) else {
+            return;
+        };
+        let level = level.min(MAX_LIGHT);
+        *packed = match kind {
+            LightKind::Sky => (*packed & 0x0F) | (level << 4),
+            LightKind::Block => (*packed & 0xF0) | level,
+        };
+    }
+}
+
+/// Light of the blocks just outside a chunk's four sides, so a mesher working on
+/// a copy of the chunk can shade faces on its border. Sides whose neighbour is
+/// not loaded are `None`.
+#[derive(Debug, Clone, Default)]
+pub struct BorderLight {
+    /// -X, +X, -Z, +Z; each holds `WORLD_HEIGHT * CHUNK_SIZE` packed values indexed `i + y * CHUNK_SIZE`,
+    /// where `i` runs along the side.
+    sides: [Option<Vec<u8>>; 4],
+}
+
+impl BorderLight {
+    /// Light at a position one block outside the chunk, in the chunk's local coordinates.
+    /// Falls back to open sky where the neighbour is unknown.
+    pub fn get(&self, kind: LightKind, x: i32, y: usize, z: i32) -> u8 {
+        let size = CHUNK_SIZE as i32;
+        let (side, along) = if x < 0 {
+            (0, z)
+        } else if x >= size {
+            (1, z)
+        } else if z < 0 {
+            (2, x)
+        } else {
+            (3, x)
+        };
+        let packed = self.sides[side]
+            .as_ref()
+            .and_then(|values| values.get(along as usize + y * CHUNK_SIZE).copied());
+        match (packed, kind) {
+            (Some(packed), LightKind::Sky) => packed >> 4,
+            (Some(packed), LightKind::Block) => packed & 0x0F,
+            (None, LightKind::Sky) => MAX_LIGHT,
+            (None, LightKind::Block) => 0,
+        }
+    }
+
+    /// Collect the border light of chunk `(chunk_x, chunk_z)` from its loaded neighbours.
+    pub fn collect(chunks: &HashMap<(i32, i32), Chunk>, chunk_x: i32, chunk_z: i32) -> Self {
+        let last = CHUNK_SIZE - 1;
+        let sides = [
+            ((chunk_x - 1, chunk_z), true, last),
+            ((chunk_x + 1, chunk_z), true, 0),
+            ((chunk_x, chunk_z - 1), false, last),
+            ((chunk_x, chunk_z + 1), false, 0),
+        ];
+        Self {
+            sides: sides.map(|(coords, along_z, fixed)| {
+                let neighbour = chunks.get(&coords)?;
+                let mut values = Vec::with_capacity(WORLD_HEIGHT * CHUNK_SIZE);
+                for y in 0..WORLD_HEIGHT {
+                    for i in 0..CHUNK_SIZE {
+                        let (x, z) = if along_z { (fixed, i) } else { (i, fixed) };
+                        let sky = neighbour.light(LightKind::Sky, x, y, z);
+                        let block = neighbour.light(LightKind::Block, x, y, z);
+                        values.push((sky << 4) | block);
+                    }
+                }
+                Some(values)
+            }),
+        }
+    }
+}
+
+/// Blocks and light addressed by world coordinates. Positions outside the store
+/// (unloaded chunks, or outside the single chunk being lit) read as `None` and are
+/// never written.
+trait LightStore {
+    fn block(&self, x: i32, y: i32, z: i32) -> Option<BlockType>;
+    fn light(&self, kind: LightKind, x: i32, y: i32, z: i32) -> u8;
+    fn set_light(&mut self, kind: LightKind, x: i32, y: i32, z: i32, level: u8);
+}
+
+fn split(coord: i32) -> (i32, usize) {
+    let size = CHUNK_SIZE as i32;
+    (coord.div_euclid(size), coord.rem_euclid(size) as usize)
+}
+
+impl LightStore for Chunk {
+    fn block(&self, x: i32, y: i32, z: i32) -> Option<BlockType> {
+        let (chunk_x, local_x) = split(x);
+        let (chunk_z, local_z) = split(z);
+        if chunk_x != self.x || chunk_z != self.z || !(0..WORLD_HEIGHT as i32).contains(&y) {
+            return None;
+        }
+        Some(self.get_block(local_x, y as usize, local_z))
+    }
+
+    fn light(&self, kind: LightKind, x: i32, y: i32, z: i32) -> u8 {
+        Chunk::light(self, kind, split(x).1, y as usize, split(z).1)
+    }
+
+    fn set_light(&mut self, kind: LightKind, x: i32, y: i32, z: i32, level: u8) {
+        Chunk::set_light(self, kind, split(x).1, y as usize, split(z).1, level);
+    }
+}
+
+/// The loaded chunks of a world, recording which chunks had their light changed.
+pub struct WorldLight<'a> {
+    chunks: &'a mut HashMap<(i32, i32), Chunk>,
+    pub changed: HashSet<(i32, i32)>,
+}
+
+impl<'a> WorldLight<'a> {
+    pub fn new(chunks: &'a mut HashMap<(i32, i32), Chunk>) -> Self {
+        Self {
+            chunks,
+            changed: HashSet::new(),
+        }
+    }
+}
+
+impl LightStore for WorldLight<'_> {
+    fn block(&self, x: i32, y: i32, z: i32) -> Option<BlockType> {
+        if !(0..WORLD_HEIGHT as i32).contains(&y) {
+            return None;
+        }
+        let (chunk_x, local_x) = split(x);
+        let (chunk_z, local_z) = split(z);
+        let chunk = self.chunks.get(&(chunk_x, chunk_z))?;
+        Some(chunk.get_block(local_x, y as usize, local_z))
+    }
+
+    fn light(&self, kind: LightKind, x: i32, y: i32, z: i32) -> u8 {
+        let (chunk_x, local_x) = split(x);
+        let (chunk_z, local_z) = split(z);
+        self.chunks
+            .get(&(chunk_x, chunk_z))
+            .map_or(0, |chunk| chunk.light(kind, local_x, y as usize, local_z))
+    }
+
+    fn set_light(&mut self, kind: LightKind, x: i32, y: i32, z: i32, level: u8) {
+        let (chunk_x, local_x) = split(x);
+        let (chunk_z, local_z) = split(z);
+        if let Some(chunk) = self.chunks.get_mut(&(chunk_x, chunk_z)) {
+            chunk.set_light(kind, local_x, y as usize, local_z, level);
+            self.changed.insert((chunk_x, chunk_z));
+        }
+    }
+}
+
+/// Level a neighbour gets from a block lit at `level`, moving in direction `dy`.
+fn spread_level(kind: LightKind, level: u8, dy: i32, into: BlockType) -> u8 {
+    let opacity = into.light_opacity();
+    if kind == LightKind::Sky && level == MAX_LIGHT && dy < 0 && opacity == 0 {
+        // Unobstructed sky light falls without fading.
+        return MAX_LIGHT;
+    }
+    level.saturating_sub(opacity.max(1))
+}
+
+/// Flood light outwards from every queued position.
+fn propagate(store: &mut impl LightStore, kind: LightKind, mut queue: VecDeque<(i32, i32, i32)>) {
+    while let Some((x, y, z)) = queue.pop_front() {
+        let level = store.light(kind, x, y, z);
+        if level <= 1 {
+            continue;
+        }
+        for (dx, dy, dz) in DIRECTIONS {
+            let (nx, ny, nz) = (x + dx, y + dy, z + dz);
+            let Some(block) = store.block(nx, ny, nz) else {
+                continue;
+            };
+            if block.light_opacity() >= MAX_LIGHT {
+                continue;
+            }
+            let next = spread_level(kind, level, dy, block);
+            if next > store.light(kind, nx, ny, nz) {
+                store.set_light(kind, nx, ny, nz, next);
+                queue.push_back((nx, ny, nz));
+            }
+        }
+    }
+}
+
+/// Darken everything lit through the queued positions (given with the level they
+/// had), then refill the darkened area from the light around it.
+fn remove(store: &mut impl LightStore, kind: LightKind, mut queue: VecDeque<(i32, i32, i32, u8)>) {
+    let mut refill = VecDeque::new();
+    while let Some((x, y, z, level)) = queue.pop_front() {
+        for (dx, dy, dz) in DIRECTIONS {
+            let (nx, ny, nz) = (x + dx, y + dy, z + dz);
+            let Some(block) = store.block(nx, ny, nz) else {
+                continue;
+            };
+            let neighbour = store.light(kind, nx, ny, nz);
+            if neighbour == 0 {
+                continue;
+            }
+            let fed_by_removed = neighbour < level
+                || (kind == LightKind::Sky && dy < 0 && level == MAX_LIGHT && neighbour == MAX_LIGHT);
+            if fed_by_removed {
+                store.set_light(kind, nx, ny, nz, 0);
+                queue.push_back((nx, ny, nz, neighbour));
+                if kind == LightKind::Block && block.light_emission() > 0 {
+                    store.set_light(kind, nx, ny, nz, block.light_emission());
+                    refill.push_back((nx, ny, nz));
+                }
+            } else {
+                refill.push_back((nx, ny, nz));
+            }
+        }
+    }
+    propagate(store, kind, refill);
+}
+
+/// Light a chunk on its own, as if it had no neighbours.
+pub fn light_chunk(chunk: &mut Chunk) {
+    chunk.reset_light();
+    let origin_x = chunk.x * CHUNK_SIZE as i32;
+    let origin_z = chunk.z * CHUNK_SIZE as i32;
+
+    // Sky light straight down each column.
+    let mut sky_top = [[WORLD_HEIGHT; CHUNK_SIZE]; CHUNK_SIZE];
+    for x in 0..CHUNK_SIZE {
+        for z in 0..CHUNK_SIZE {
+            let mut level = MAX_LIGHT;
+            for y in (0..WORLD_HEIGHT).rev() {
+                let opacity = chunk.get_block(x, y, z).light_opacity();
+                if opacity > 0 {
+                    level = level.saturating_sub(opacity);
+                    if sky_top[x][z] == WORLD_HEIGHT {
+                        sky_top[x][z] = y;
+                    }
+                }
+                if level == 0 {
+                    break;
+                }
+                chunk.set_light(LightKind::Sky, x, y, z, level);
+            }
+        }
+    }
+
+    // Spread sideways wherever a neighbouring column is covered higher up.
+    let mut sky_queue = VecDeque::new();
+    for x in 0..CHUNK_SIZE {
+        for z in 0..CHUNK_SIZE {
+            let mut highest_neighbour = 0;
+            for (dx, _, dz) in DIRECTIONS {
+                let (nx, nz) = (x as i32 + dx, z as i32 + dz);
+                if (0..CHUNK_SIZE as i32).contains(&nx) && (0..CHUNK_SIZE as i32).contains(&nz) {
+                    let top = sky_top[nx as usize][nz as usize];
+                    if top != WORLD_HEIGHT {
+                        highest_neighbour = highest_neighbour.max(top + 1);
+                    }
+                }
+            }
+            for y in 0..highest_neighbour.min(WORLD_HEIGHT) {
+                if chunk.light(LightKind::Sky, x, y, z) > 1 {
+                    sky_queue.push_back((origin_x + x as i32, y as i32, origin_z + z as i32));
+                }
+            }
+        }
+    }
+    propagate(chunk, LightKind::Sky, sky_queue);
+
+    let mut block_queue = VecDeque::new();
+    for y in 0..WORLD_HEIGHT {
+        if chunk.is_section_empty(y / super::section::SECTION_SIZE) {
+            continue;
+        }
+        for x in 0..CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE {
+                let emission = chunk.get_block(x, y, z).light_emission();
+                if emission > 0 {
+                    chunk.set_light(LightKind::Block, x, y, z, emission);
+                    block_queue.push_back((origin_x + x as i32, y as i32, origin_z + z as i32));
+                }
+            }
+        }
+    }
+    propagate(chunk, LightKind::Block, block_queue);
+}
+
+/// Let light flow both ways across the border between a newly added chunk and
+/// each of its loaded neighbours.
+pub fn stitch_chunk(world: &mut WorldLight, chunk_x: i32, chunk_z: i32) {
+    let size = CHUNK_SIZE as i32;
+    let (min_x, min_z) = (chunk_x * size, chunk_z * size);
+    let mut border = Vec::new();
+    for i in 0..size {
+        // Each pair: a cell on the chunk's edge and the one across the border.
+        border.push(((min_x, min_z + i), (min_x - 1, min_z + i)));
+        border.push(((min_x + size - 1, min_z + i), (min_x + size, min_z + i)));
+        border.push(((min_x + i, min_z), (min_x + i, min_z - 1)));
+        border.push(((min_x + i, min_z + size - 1), (min_x + i, min_z + size)));
+    }
+
+    for kind in LightKind::ALL {
+        let mut queue = VecDeque::new();
+        for &((x, z), (ox, oz)) in &border {
+            if world.block(ox, 0, oz).is_none() {
+                continue;
+            }
+            for y in 0..WORLD_HEIGHT as i32 {
+                queue.push_back((x, y, z));
+                queue.push_back((ox, y, oz));
+            }
+        }
+        propagate(world, kind, queue);
+    }
+}
+
+/// Repair the light around a block that was just changed.
+pub fn update_block(world: &mut WorldLight, x: i32, y: i32, z: i32) {
+    let Some(block) = world.block(x, y, z) else {
+        return;
+    };
+    for kind in LightKind::ALL {
+        let old = world.light(kind, x, y, z);
+        world.set_light(kind, x, y, z, 0);
+        remove(world, kind, VecDeque::from([(x, y, z, old)]));
+
+        let mut refill: VecDeque<_> = DIRECTIONS
+            .iter()
+            .map(|&(dx, dy, dz)| (x + dx, y + dy, z + dz))
+            .filter(|&(nx, ny, nz)| world.block(nx, ny, nz).is_some())
+            .collect();
+        let own = match kind {
+            LightKind::Block => block.light_emission(),
+            // Nothing above the top of the world blocks the sky.
+            LightKind::Sky if y == WORLD_HEIGHT as i32 - 1 => MAX_LIGHT.saturating_sub(block.light_opacity()),
+            LightKind::Sky => 0,
+        };
+        if own > world.light(kind, x, y, z) {
+            world.set_light(kind, x, y, z, own);
+            refill.push_back((x, y, z));
+        }
+        propagate(world, kind, refill);
+    }
+}
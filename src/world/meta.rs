@@ -0,0 +1,245 @@
+#![allow(dead_code)]
+/// Typed, versioned `world_meta.json`.
+///
+/// The file carries a `format_version`. Older files are upgraded on load by
+/// running every migration from their version up to `CURRENT_FORMAT_VERSION`
+/// in order, on the raw JSON, before it is parsed into `WorldMeta`. A migration
+/// may also rewrite other files in the world folder. Worlds written by a newer
+/// build are refused rather than half-understood.
+use super::chunk::Chunk;
+use super::game_mode::GameMode;
+use super::generator::DEFAULT_GENERATOR;
+use super::region::{write_atomic, RegionStorage};
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const WORLD_META_FILE: &str = "world_meta.json";
+pub const CURRENT_FORMAT_VERSION: u32 = 1;
+
+/// Upgrades metadata (and, if needed, world files) from version `index` to `index + 1`.
+type Migration = fn(&mut serde_json::Value, &Path) -> anyhow::Result<()>;
+
+const MIGRATIONS: [Migration; CURRENT_FORMAT_VERSION as usize] = [migrate_v0_to_v1];
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct GameRules {
+    pub do_daylight_cycle: bool,
+    /// Broken blocks drop items.
+    pub do_tile_drops: bool,
+    pub keep_inventory: bool,
+    pub random_tick_speed: u32,
+}
+
+impl Default for GameRules {
+    fn default() -> Self {
+        Self {
+            do_daylight_cycle: true,
+            do_tile_drops: true,
+            keep_inventory: false,
+            random_tick_speed: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WorldMeta {
+    pub format_version: u32,
+    pub seed: u32,
+    /// Name of the `WorldGenerator`, see `generator::create_generator`.
+    pub generator: String,
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    pub generator_options: serde_json::Value,
+    /// Unix timestamps in seconds.
+    pub created_at: u64,
+    pub last_played: u64,
+    pub game_time: f64,
+    pub spawn: [f32; 3],
+    #[serde(default)]
+    pub game_rules: GameRules,
+    /// The local player's game mode.
+    #[serde(default)]
+    pub game_mode: GameMode,
+}
+
+pub const DEFAULT_SPAWN: [f32; 3] = [8.0, 80.0, 8.0];
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+impl WorldMeta {
+    pub fn new(seed: u32, generator: &str, generator_options: serde_json::Value) -> Self {
+        let now = unix_now();
+        Self {
+            format_version: CURRENT_FORMAT_VERSION,
+            seed,
+            generator: generator.to_string(),
+            generator_options,
+            created_at: now,
+            last_played: now,
+            game_time: 0.0,
+            spawn: DEFAULT_SPAWN,
+            game_rules: GameRules::default(),
+            game_mode: GameMode::default(),
+        }
+    }
+
+    /// Read the world's metadata, migrating it to the current format. `Ok(None)` if the
+    /// world has no metadata file yet.
+    pub fn load(world_dir: &Path) -> anyhow::Result<Option<Self>> {
+        let path = world_dir.join(WORLD_META_FILE);
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err).with_context(|| format!("reading {}", path.display())),
+        };
+        let mut value: serde_json::Value = serde_json::from_str(&contents)
+            .with_context(|| format!("parsing {}", path.display()))?;
+
+        // Files from before versioning have no `format_version` and count as version 0.
+        let version = value
+            .get("format_version")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as u32;
+        if version > CURRENT_FORMAT_VERSION {
+            anyhow::bail!(
+                "world format version {} is newer than this build supports ({})",
+                version,
+                CURRENT_FORMAT_VERSION
+            );
+        }
+
+        for (from, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+            migration(&mut value, world_dir)
+                .with_context(|| format!("migrating world from format version {}", from))?;
+            value["format_version"] = serde_json::json!(from as u32 + 1);
+            log::info!("Migrated {} to format version {}", path.display(), from + 1);
+        }
+
+        let meta: WorldMeta = serde_json::from_value(value)
+            .with_context(|| format!("reading {}", path.display()))?;
+        if version < CURRENT_FORMAT_VERSION {
+            meta.save(world_dir)?;
+        }
+        Ok(Some(meta))
+    }
+
+    pub fn save(&self, world_dir: &Path) -> anyhow::Result<()> {
+        std::fs::create_dir_all(world_dir)?;
+        write_atomic(
+            &world_dir.join(WORLD_META_FILE),
+            serde_json::to_string_pretty(self)?.as_bytes(),
+        )?;
+        Ok(())
+    }
+}
+
+/// Version 0 was `{ "seed": n }`, optionally with a generator name and options. Chunks
+/// may still be in the one-file-per-chunk flat layout; they are converted and folded
+/// into region files here.
+fn migrate_v0_to_v1(meta: &mut serde_json::Value, world_dir: &Path) -> anyhow::Result<()> {
+    let object = meta
+        .as_object_mut()
+        .context("world metadata is not a JSON object")?;
+    let seed = object
+        .get("seed")
+        .and_then(|v| v.as_u64())
+        .context("world metadata has no seed")?;
+
+    // Best guess at creation time for worlds that never recorded it.
+    let created_at = std::fs::metadata(world_dir.join(WORLD_META_FILE))
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or_else(unix_now);
+
+    object.insert("seed".into(), serde_json::json!(seed as u32));
+    object
+        .entry("generator")
+        .or_insert_with(|| serde_json::json!(DEFAULT_GENERATOR));
+    object.insert("created_at".into(), serde_json::json!(created_at));
+    object.insert("last_played".into(), serde_json::json!(created_at));
+    object.insert("game_time".into(), serde_json::json!(0.0));
+    object.insert("spawn".into(), serde_json::json!(DEFAULT_SPAWN));
+    object.insert("game_rules".into(), serde_json::to_value(GameRules::default())?);
+
+    let migrated = RegionStorage::new(world_dir.join("regions"))
+        .migrate_legacy_chunks(&world_dir.join("chunks"), |data| {
+            Chunk::decode_legacy(data)?.encode().ok()
+        })?;
+    if migrated > 0 {
+        log::info!("Migrated {} legacy chunk files into region storage", migrated);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::block::BlockType;
+    use crate::world::chunk::{CHUNK_SIZE, WORLD_HEIGHT};
+    use crate::world::region::QUARANTINE_DIR;
+
+    /// An empty directory for one test, removed first if an earlier run left it behind.
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("meta-test-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Position a first-release save used for `block`.
+    fn legacy_position(block: BlockType) -> u32 {
+        BlockType::ALL.iter().position(|b| *b == block).unwrap() as u32
+    }
+
+    #[test]
+    fn load_migrates_version_0_worlds_and_their_chunk_files() {
+        let dir = temp_dir("v0");
+        std::fs::write(dir.join(WORLD_META_FILE), r#"{ "seed": 42 }"#).unwrap();
+        let chunks = dir.join("chunks");
+        std::fs::create_dir_all(&chunks).unwrap();
+        // Blocks are stored x-fastest, then y, then z.
+        let mut blocks = vec![0u32; CHUNK_SIZE * WORLD_HEIGHT * CHUNK_SIZE];
+        blocks[0] = legacy_position(BlockType::Bedrock);
+        blocks[1 + 64 * CHUNK_SIZE + 2 * CHUNK_SIZE * WORLD_HEIGHT] = legacy_position(BlockType::Chest);
+        std::fs::write(chunks.join("chunk_3_-2.bin"), bincode::serialize(&(3i32, -2i32, blocks)).unwrap()).unwrap();
+        std::fs::write(chunks.join("chunk_4_4.bin"), b"not a chunk").unwrap();
+
+        let meta = WorldMeta::load(&dir).unwrap().unwrap();
+        assert_eq!(meta.format_version, CURRENT_FORMAT_VERSION);
+        assert_eq!(meta.seed, 42);
+        assert_eq!(meta.generator, DEFAULT_GENERATOR);
+
+        let mut regions = RegionStorage::new(dir.join("regions"));
+        let chunk = Chunk::decode(&regions.read_chunk(3, -2).unwrap().unwrap()).unwrap();
+        assert_eq!((chunk.x, chunk.z), (3, -2));
+        assert_eq!(chunk.get_block(0, 0, 0), BlockType::Bedrock);
+        assert_eq!(chunk.get_block(1, 64, 2), BlockType::Chest);
+        assert_eq!(chunk.get_block(2, 64, 1), BlockType::Air);
+        assert!(!chunks.exists());
+        assert!(dir.join(QUARANTINE_DIR).join("chunk_4_4.bin").exists());
+
+        // The migrated metadata was saved, so loading again changes nothing.
+        assert_eq!(WorldMeta::load(&dir).unwrap().unwrap(), meta);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn load_refuses_newer_worlds() {
+        let dir = temp_dir("newer");
+        let mut meta = serde_json::to_value(WorldMeta::new(1, DEFAULT_GENERATOR, serde_json::Value::Null)).unwrap();
+        meta["format_version"] = serde_json::json!(CURRENT_FORMAT_VERSION + 1);
+        std::fs::write(dir.join(WORLD_META_FILE), meta.to_string()).unwrap();
+
+        assert!(WorldMeta::load(&dir).is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
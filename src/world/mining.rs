@@ -0,0 +1,139 @@
+#![allow(dead_code)]
+/// Tools and how long blocks take to mine.
+///
+/// Mining time follows the vanilla formula. Each tick adds `speed / hardness / 30`
+/// to a block's progress when the held item can harvest it, or `/ 100` when it
+/// can't. The block breaks once progress reaches 1. `speed` is 1 by hand and the
+/// tier's speed with the block's preferred tool. Blocks name their preferred tool
+/// and the lowest tier that harvests them in their definition (see `registry`).
+/// Which items are tools, and of what kind and tier, is set in the item registry.
+use super::block::BlockType;
+use crate::item::ItemRegistry;
+use serde::{Deserialize, Serialize};
+
+/// Game ticks per second, which mining progress is counted in.
+const TICKS_PER_SECOND: f32 = 20.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolKind {
+    Pickaxe,
+    Axe,
+    Shovel,
+    Hoe,
+    Sword,
+    Shears,
+}
+
+impl ToolKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            ToolKind::Pickaxe => "pickaxe",
+            ToolKind::Axe => "axe",
+            ToolKind::Shovel => "shovel",
+            ToolKind::Hoe => "hoe",
+            ToolKind::Sword => "sword",
+            ToolKind::Shears => "shears",
+        }
+    }
+}
+
+/// Tool materials, in the order they harvest more blocks. Gold is fast but only
+/// harvests what wood does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolTier {
+    Wooden,
+    Stone,
+    Iron,
+    Diamond,
+    Netherite,
+    Golden,
+}
+
+impl ToolTier {
+    pub fn name(self) -> &'static str {
+        match self {
+            ToolTier::Wooden => "wooden",
+            ToolTier::Stone => "stone",
+            ToolTier::Iron => "iron",
+            ToolTier::Diamond => "diamond",
+            ToolTier::Netherite => "netherite",
+            ToolTier::Golden => "golden",
+        }
+    }
+
+    /// Harvest level; a tool harvests blocks needing this level or lower.
+    pub fn level(self) -> u8 {
+        match self {
+            ToolTier::Wooden | ToolTier::Golden => 0,
+            ToolTier::Stone => 1,
+            ToolTier::Iron => 2,
+            ToolTier::Diamond => 3,
+            ToolTier::Netherite => 4,
+        }
+    }
+
+    pub fn speed(self) -> f32 {
+        match self {
+            ToolTier::Wooden => 2.0,
+            ToolTier::Stone => 4.0,
+            ToolTier::Iron => 6.0,
+            ToolTier::Diamond => 8.0,
+            ToolTier::Netherite => 9.0,
+            ToolTier::Golden => 12.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Tool {
+    pub kind: ToolKind,
+    /// Shears have no tier.
+    #[serde(default)]
+    pub tier: Option<ToolTier>,
+}
+
+impl Tool {
+    /// The tool an item is, if it is one.
+    pub fn from_item(item: &str) -> Option<Self> {
+        ItemRegistry::global().get(item)?.tool
+    }
+
+    fn speed(self) -> f32 {
+        match (self.kind, self.tier) {
+            (ToolKind::Shears, _) => 2.0,
+            (_, Some(tier)) => tier.speed(),
+            (_, None) => 1.0,
+        }
+    }
+}
+
+/// Whether mining `block` with `tool` (or by hand) drops anything.
+pub fn can_harvest(block: BlockType, tool: Option<Tool>) -> bool {
+    let definition = block.definition();
+    let Some(required) = definition.tool_tier else {
+        return true;
+    };
+    tool.is_some_and(|tool| {
+        Some(tool.kind) == definition.tool && tool.tier.is_some_and(|tier| tier.level() >= required.level())
+    })
+}
+
+/// Seconds of mining it takes to break `block`, or `None` if it can't be broken.
+pub fn break_time(block: BlockType, tool: Option<Tool>) -> Option<f32> {
+    let hardness = block.hardness();
+    if hardness < 0.0 {
+        return None;
+    }
+    if hardness == 0.0 {
+        return Some(0.0);
+    }
+    let speed = match tool {
+        Some(tool) if Some(tool.kind) == block.definition().tool => tool.speed(),
+        _ => 1.0,
+    };
+    let divisor = if can_harvest(block, tool) { 30.0 } else { 100.0 };
+    let per_tick = speed / hardness / divisor;
+    Some((1.0 / per_tick).ceil() / TICKS_PER_SECOND)
+}
@@ -1,5 +1,24 @@
+pub mod biome;
 pub mod block;
+pub mod block_entity;
+pub mod caves;
 pub mod chunk;
+pub mod decoration;
+pub mod fluid;
+pub mod game_mode;
+pub mod generator;
+pub mod item_entity;
+pub mod light;
+pub mod meta;
+pub mod mining;
+pub mod ores;
+pub mod presets;
+pub mod provider;
+pub mod region;
+pub mod registry;
+pub mod section;
+pub mod shape;
+pub mod state;
 
 pub use block::BlockType;
-pub use chunk::{Chunk, World, CHUNK_SIZE, WORLD_HEIGHT};
+pub use chunk::{Chunk, RetentionPolicy, World, CHUNK_SIZE, WORLD_HEIGHT};
@@ -0,0 +1,204 @@
+#![allow(dead_code)]
+/// Ore vein placement driven by `data/worldgen/ores.json`.
+///
+/// Each entry describes one ore: how many veins to try per chunk, how large a
+/// vein is, the height distribution and optionally the biomes it is limited
+/// to. A vein is a string of small blobs along a short random segment and only
+/// replaces stone (or deepslate, with the ore's deepslate variant). Veins may
+/// start in a neighbouring chunk, so every chunk replays its neighbours' veins
+/// and keeps the blocks that land inside it.
+use super::biome::Biome;
+use super::block::BlockType;
+use super::chunk::{Chunk, CHUNK_SIZE, WORLD_HEIGHT};
+use super::generator::chunk_rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+use std::path::Path;
+
+pub const ORE_CONFIG_PATH: &str = "data/worldgen/ores.json";
+const DEFAULT_ORE_CONFIG: &str = include_str!("../../data/worldgen/ores.json");
+
+/// Largest vein that still fits within one neighbouring chunk of its origin.
+pub const MAX_VEIN_SIZE: u32 = 64;
+const ORE_SALT: u64 = 0x0_4E5;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HeightDistribution {
+    /// Every height between `min_y` and `max_y` (inclusive) is equally likely.
+    Uniform { min_y: i32, max_y: i32 },
+    /// Most likely halfway between `min_y` and `max_y`, tapering off linearly to both ends.
+    Triangle { min_y: i32, max_y: i32 },
+}
+
+impl HeightDistribution {
+    fn sample(&self, rng: &mut StdRng) -> i32 {
+        match *self {
+            HeightDistribution::Uniform { min_y, max_y } => rng.gen_range(min_y..=max_y.max(min_y)),
+            HeightDistribution::Triangle { min_y, max_y } => {
+                let span = (max_y - min_y).max(0);
+                let half = span / 2;
+                min_y + rng.gen_range(0..=half) + rng.gen_range(0..=span - half)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OreVein {
+    pub name: String,
+    pub block: BlockType,
+    /// Used where the vein passes through deepslate; defaults to `block`.
+    #[serde(default)]
+    pub deepslate_block: Option<BlockType>,
+    /// Veins attempted per chunk; the fractional part is the chance of one more.
+    pub count_per_chunk: f64,
+    /// Roughly the number of blocks in one vein.
+    pub vein_size: u32,
+    pub height: HeightDistribution,
+    /// Biomes the vein may start in. Empty means everywhere.
+    #[serde(default)]
+    pub biomes: Vec<Biome>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OreConfig {
+    pub veins: Vec<OreVein>,
+}
+
+impl Default for OreConfig {
+    fn default() -> Self {
+        serde_json::from_str(DEFAULT_ORE_CONFIG).expect("Built-in ore config is invalid")
+    }
+}
+
+impl OreConfig {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        let mut config: OreConfig = serde_json::from_str(&contents)?;
+        for vein in &mut config.veins {
+            vein.vein_size = vein.vein_size.min(MAX_VEIN_SIZE);
+        }
+        Ok(config)
+    }
+
+    /// `ORE_CONFIG_PATH` relative to the working directory if present, otherwise the built-in table.
+    pub fn load_or_default() -> Self {
+        let path = Path::new(ORE_CONFIG_PATH);
+        if !path.exists() {
+            return Self::default();
+        }
+        match Self::load(path) {
+            Ok(config) => config,
+            Err(err) => {
+                log::warn!("Failed to load {}: {}; using built-in ore table", ORE_CONFIG_PATH, err);
+                Self::default()
+            }
+        }
+    }
+
+    /// Place every configured vein that reaches into `chunk`.
+    pub fn place_veins(&self, seed: u32, chunk: &mut Chunk, biome_at: impl Fn(i32, i32) -> Biome) {
+        for (index, vein) in self.veins.iter().enumerate() {
+            let salt = ORE_SALT ^ ((index as u64 + 1) << 32);
+            for offset_x in -1..=1 {
+                for offset_z in -1..=1 {
+                    let origin_chunk = (chunk.x + offset_x, chunk.z + offset_z);
+                    let mut rng = chunk_rng(seed, origin_chunk.0, origin_chunk.1, salt);
+                    vein.place_from(&mut rng, origin_chunk, chunk, &biome_at);
+                }
+            }
+        }
+    }
+}
+
+impl OreVein {
+    /// Replay the veins that start in `origin_chunk`, writing the blocks that fall inside `chunk`.
+    fn place_from(
+        &self,
+        rng: &mut StdRng,
+        origin_chunk: (i32, i32),
+        chunk: &mut Chunk,
+        biome_at: &impl Fn(i32, i32) -> Biome,
+    ) {
+        let mut count = self.count_per_chunk.max(0.0).floor() as usize;
+        if rng.gen_bool(self.count_per_chunk.max(0.0).fract()) {
+            count += 1;
+        }
+
+        for _ in 0..count {
+            let x = (origin_chunk.0 * CHUNK_SIZE as i32 + rng.gen_range(0..CHUNK_SIZE as i32)) as f64;
+            let z = (origin_chunk.1 * CHUNK_SIZE as i32 + rng.gen_range(0..CHUNK_SIZE as i32)) as f64;
+            let y = self.height.sample(rng) as f64;
+            let angle = rng.gen_range(0.0..PI);
+            let rise = rng.gen_range(-2.0..2.0);
+            let blob_seed: u64 = rng.gen();
+
+            if !self.biomes.is_empty() && !self.biomes.contains(&biome_at(x as i32, z as i32)) {
+                continue;
+            }
+            self.place_vein(chunk, [x, y, z], angle, rise, blob_seed);
+        }
+    }
+
+    fn place_vein(&self, chunk: &mut Chunk, origin: [f64; 3], angle: f64, rise: f64, blob_seed: u64) {
+        if self.vein_size <= 2 {
+            for i in 0..self.vein_size as i32 {
+                self.place_ore(chunk, origin[0] as i32 + i, origin[1] as i32, origin[2] as i32);
+            }
+            return;
+        }
+
+        // Blobs are spaced along a segment centered on the origin; radii swell in the middle.
+        let size = self.vein_size as f64;
+        let spread = size / 8.0;
+        let start = [origin[0] + angle.sin() * spread, origin[1] + rise, origin[2] + angle.cos() * spread];
+        let end = [origin[0] - angle.sin() * spread, origin[1] - rise, origin[2] - angle.cos() * spread];
+        // Blob radii come from their own stream so skipped veins don't shift the caller's.
+        let mut blob_rng = StdRng::seed_from_u64(blob_seed);
+
+        for step in 0..self.vein_size {
+            let t = step as f64 / size;
+            let center = [
+                start[0] + (end[0] - start[0]) * t,
+                start[1] + (end[1] - start[1]) * t,
+                start[2] + (end[2] - start[2]) * t,
+            ];
+            let radius = ((t * PI).sin() + 1.0) * blob_rng.gen_range(0.0..1.0) * size / 32.0 + 0.5;
+
+            let min = center.map(|c| (c - radius).floor() as i32);
+            let max = center.map(|c| (c + radius).ceil() as i32);
+            for bx in min[0]..=max[0] {
+                for by in min[1]..=max[1] {
+                    for bz in min[2]..=max[2] {
+                        let dx = bx as f64 + 0.5 - center[0];
+                        let dy = by as f64 + 0.5 - center[1];
+                        let dz = bz as f64 + 0.5 - center[2];
+                        if dx * dx + dy * dy + dz * dz < radius * radius {
+                            self.place_ore(chunk, bx, by, bz);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Replace stone or deepslate at a world position if it lies in `chunk`.
+    fn place_ore(&self, chunk: &mut Chunk, world_x: i32, y: i32, world_z: i32) {
+        let local_x = world_x - chunk.x * CHUNK_SIZE as i32;
+        let local_z = world_z - chunk.z * CHUNK_SIZE as i32;
+        let size = CHUNK_SIZE as i32;
+        if !(0..size).contains(&local_x) || !(0..size).contains(&local_z) || y < 1 || y >= WORLD_HEIGHT as i32 {
+            return;
+        }
+        let (x, y, z) = (local_x as usize, y as usize, local_z as usize);
+        let ore = match chunk.get_block(x, y, z) {
+            BlockType::Stone => self.block,
+            BlockType::Deepslate => self.deepslate_block.unwrap_or(self.block),
+            _ => return,
+        };
+        chunk.set_block(x, y, z, ore);
+    }
+}
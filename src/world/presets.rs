@@ -0,0 +1,112 @@
+#![allow(dead_code)]
+/// Non-noise world generators: superflat and void.
+use super::biome::Biome;
+use super::block::BlockType;
+use super::chunk::{Chunk, CHUNK_SIZE, WORLD_HEIGHT};
+use super::decoration::PendingBlock;
+use super::generator::WorldGenerator;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlatLayer {
+    pub block: BlockType,
+    pub count: u32,
+}
+
+/// Options for the `flat` generator, read from `generator_options` in `world_meta.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FlatOptions {
+    /// Layers from the bottom of the world up.
+    pub layers: Vec<FlatLayer>,
+    pub biome: Biome,
+}
+
+impl Default for FlatOptions {
+    fn default() -> Self {
+        Self {
+            layers: vec![
+                FlatLayer {
+                    block: BlockType::Bedrock,
+                    count: 1,
+                },
+                FlatLayer {
+                    block: BlockType::Dirt,
+                    count: 2,
+                },
+                FlatLayer {
+                    block: BlockType::GrassBlock,
+                    count: 1,
+                },
+            ],
+            biome: Biome::Plains,
+        }
+    }
+}
+
+/// Every chunk is the same stack of layers.
+pub struct FlatGenerator {
+    /// One block per y level, precomputed from the layer list.
+    column: Vec<BlockType>,
+    biome: Biome,
+}
+
+impl FlatGenerator {
+    pub fn new(options: FlatOptions) -> Self {
+        let column = options
+            .layers
+            .iter()
+            .flat_map(|layer| std::iter::repeat(layer.block).take(layer.count as usize))
+            .take(WORLD_HEIGHT)
+            .collect();
+        Self {
+            column,
+            biome: options.biome,
+        }
+    }
+}
+
+impl WorldGenerator for FlatGenerator {
+    fn name(&self) -> &'static str {
+        "flat"
+    }
+
+    fn generate_chunk(&self, chunk_x: i32, chunk_z: i32) -> (Chunk, Vec<PendingBlock>) {
+        let mut chunk = Chunk::new(chunk_x, chunk_z);
+        for x in 0..CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE {
+                chunk.set_biome(x, z, self.biome);
+                for (y, block) in self.column.iter().enumerate() {
+                    chunk.set_block(x, y, z, *block);
+                }
+            }
+        }
+        (chunk, Vec::new())
+    }
+}
+
+/// Height of the spawn platform in void worlds.
+const VOID_PLATFORM_Y: usize = 64;
+
+/// Empty chunks, apart from a small stone platform at the spawn point so players
+/// have somewhere to stand.
+pub struct VoidGenerator;
+
+impl WorldGenerator for VoidGenerator {
+    fn name(&self) -> &'static str {
+        "void"
+    }
+
+    fn generate_chunk(&self, chunk_x: i32, chunk_z: i32) -> (Chunk, Vec<PendingBlock>) {
+        let mut chunk = Chunk::new(chunk_x, chunk_z);
+        if chunk_x == 0 && chunk_z == 0 {
+            let center = CHUNK_SIZE / 2;
+            for x in center - 1..=center + 1 {
+                for z in center - 1..=center + 1 {
+                    chunk.set_block(x, VOID_PLATFORM_Y, z, BlockType::Stone);
+                }
+            }
+        }
+        (chunk, Vec::new())
+    }
+}
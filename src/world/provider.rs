@@ -0,0 +1,243 @@
+#![allow(dead_code)]
+/// Background chunk loading and generation.
+///
+/// Requests are queued by distance to the current load center and served by a
+/// small pool of worker threads, which first try the region files and fall back
+/// to generating fresh terrain, then light the chunk on its own. Finished chunks
+/// come back through a channel and are handed to `World` when it polls. A chunk
+/// whose record can't be read comes back as a failure instead of being generated.
+use super::chunk::{Chunk, World};
+use super::decoration::PendingBlock;
+use super::generator::WorldGenerator;
+use super::light;
+use super::region::RegionStorage;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashSet};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+
+/// A finished request: the chunk, or the coordinates of one that couldn't be read.
+pub type LoadResult = Result<LoadedChunk, (i32, i32)>;
+
+pub struct LoadedChunk {
+    pub chunk: Chunk,
+    /// True if the chunk was freshly generated rather than read from disk.
+    pub generated: bool,
+    /// Decoration blocks the generator placed outside the chunk.
+    pub spill: Vec<PendingBlock>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct ChunkRequest {
+    coords: (i32, i32),
+    distance: i64,
+}
+
+impl Ord for ChunkRequest {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // BinaryHeap is a max-heap; reverse so the nearest chunk is popped first.
+        other
+            .distance
+            .cmp(&self.distance)
+            .then_with(|| other.coords.cmp(&self.coords))
+    }
+}
+
+impl PartialOrd for ChunkRequest {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn distance_sq(coords: (i32, i32), center: (i32, i32)) -> i64 {
+    let dx = (coords.0 - center.0) as i64;
+    let dz = (coords.1 - center.1) as i64;
+    dx * dx + dz * dz
+}
+
+#[derive(Default)]
+struct QueueState {
+    queue: BinaryHeap<ChunkRequest>,
+    /// Chunks that are queued, being worked on, or finished but not yet collected.
+    pending: HashSet<(i32, i32)>,
+    center: (i32, i32),
+    shutdown: bool,
+}
+
+struct Shared {
+    state: Mutex<QueueState>,
+    available: Condvar,
+}
+
+pub struct ChunkProvider {
+    shared: Arc<Shared>,
+    results: Receiver<LoadResult>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl ChunkProvider {
+    pub fn new(
+        generator: Arc<dyn WorldGenerator>,
+        regions: Option<Arc<Mutex<RegionStorage>>>,
+        worker_count: usize,
+    ) -> Self {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(QueueState::default()),
+            available: Condvar::new(),
+        });
+        let (result_tx, results) = mpsc::channel();
+
+        let workers = (0..worker_count.max(1))
+            .map(|index| {
+                let shared = shared.clone();
+                let generator = generator.clone();
+                let regions = regions.clone();
+                let result_tx = result_tx.clone();
+                thread::Builder::new()
+                    .name(format!("chunk-worker-{}", index))
+                    .spawn(move || Self::worker_loop(&shared, generator.as_ref(), regions.as_deref(), &result_tx))
+                    .expect("Failed to start chunk worker")
+            })
+            .collect();
+
+        Self {
+            shared,
+            results,
+            workers,
+        }
+    }
+
+    /// One worker per spare core, capped so generation doesn't starve the render thread.
+    pub fn default_worker_count() -> usize {
+        thread::available_parallelism()
+            .map(|n| n.get().saturating_sub(1))
+            .unwrap_or(1)
+            .clamp(1, 4)
+    }
+
+    fn worker_loop(
+        shared: &Shared,
+        generator: &dyn WorldGenerator,
+        regions: Option<&Mutex<RegionStorage>>,
+        result_tx: &Sender<LoadResult>,
+    ) {
+        loop {
+            let coords = {
+                let mut state = shared.state.lock().unwrap();
+                loop {
+                    if state.shutdown {
+                        return;
+                    }
+                    if let Some(request) = state.queue.pop() {
+                        break request.coords;
+                    }
+                    state = shared.available.wait(state).unwrap();
+                }
+            };
+
+            let (chunk_x, chunk_z) = coords;
+            let stored = match regions {
+                Some(regions) => World::load_chunk_from_disk(regions, chunk_x, chunk_z),
+                None => Ok(None),
+            };
+            let result = stored.map_err(|_| coords).map(|stored| {
+                let mut loaded = match stored {
+                    Some(chunk) => LoadedChunk {
+                        chunk,
+                        generated: false,
+                        spill: Vec::new(),
+                    },
+                    None => {
+                        let (chunk, spill) = generator.generate_chunk(chunk_x, chunk_z);
+                        LoadedChunk {
+                            chunk,
+                            generated: true,
+                            spill,
+                        }
+                    }
+                };
+                light::light_chunk(&mut loaded.chunk);
+                loaded
+            });
+
+            if result_tx.send(result).is_err() {
+                return;
+            }
+        }
+    }
+
+    /// Queue a chunk unless it is already pending. Returns true if a new request was made.
+    pub fn request(&self, chunk_x: i32, chunk_z: i32) -> bool {
+        let mut state = self.shared.state.lock().unwrap();
+        let coords = (chunk_x, chunk_z);
+        if !state.pending.insert(coords) {
+            return false;
+        }
+        let distance = distance_sq(coords, state.center);
+        state.queue.push(ChunkRequest { coords, distance });
+        drop(state);
+        self.shared.available.notify_one();
+        true
+    }
+
+    pub fn is_pending(&self, chunk_x: i32, chunk_z: i32) -> bool {
+        self.shared
+            .state
+            .lock()
+            .unwrap()
+            .pending
+            .contains(&(chunk_x, chunk_z))
+    }
+
+    /// Move the load center, re-prioritising queued requests and dropping the ones
+    /// further than `radius` chunks away.
+    pub fn set_center(&self, chunk_x: i32, chunk_z: i32, radius: i32) {
+        let mut state = self.shared.state.lock().unwrap();
+        let center = (chunk_x, chunk_z);
+        if state.center == center && state.queue.is_empty() {
+            return;
+        }
+        state.center = center;
+
+        let requests = std::mem::take(&mut state.queue).into_vec();
+        let mut queue = BinaryHeap::with_capacity(requests.len());
+        for request in requests {
+            let (x, z) = request.coords;
+            if (x - chunk_x).abs() > radius || (z - chunk_z).abs() > radius {
+                state.pending.remove(&request.coords);
+                continue;
+            }
+            queue.push(ChunkRequest {
+                coords: request.coords,
+                distance: distance_sq(request.coords, center),
+            });
+        }
+        state.queue = queue;
+    }
+
+    /// Take the next finished request, if any.
+    pub fn try_recv(&self) -> Option<LoadResult> {
+        match self.results.try_recv() {
+            Ok(result) => {
+                let coords = match &result {
+                    Ok(loaded) => (loaded.chunk.x, loaded.chunk.z),
+                    Err(coords) => *coords,
+                };
+                self.shared.state.lock().unwrap().pending.remove(&coords);
+                Some(result)
+            }
+            Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => None,
+        }
+    }
+}
+
+impl Drop for ChunkProvider {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().shutdown = true;
+        self.shared.available.notify_all();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
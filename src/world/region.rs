@@ -0,0 +1,669 @@
+#![allow(dead_code)]
+/// Region-file chunk storage.
+///
+/// Chunks are grouped into 32x32 regions that share one file,
+/// `regions/r.{rx}.{rz}.region`. Each file starts with a header table holding
+/// one `(sector offset, byte length)` entry per chunk, followed by chunk
+/// records stored in 4 KiB sectors.
+///
+/// Records carry a CRC32 of their payload. A rewritten chunk always goes to
+/// fresh sectors that are synced before the header is pointed at them, and the
+/// sectors it replaced are only reused after the next `sync`, so a crash at any
+/// point leaves either the old or the new record intact.
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+pub const REGION_SIZE: i32 = 32;
+const CHUNKS_PER_REGION: usize = (REGION_SIZE * REGION_SIZE) as usize;
+const SECTOR_SIZE: u64 = 4096;
+const ENTRY_SIZE: usize = 8;
+const HEADER_BYTES: usize = CHUNKS_PER_REGION * ENTRY_SIZE;
+const HEADER_SECTORS: u32 = (HEADER_BYTES as u64 / SECTOR_SIZE) as u32;
+/// Regions smaller than this are never compacted; the savings aren't worth the rewrite.
+const COMPACT_MIN_SECTORS: u32 = 64;
+/// Compact a region once more than this fraction of its data sectors is unused.
+const COMPACT_FREE_RATIO: f32 = 0.5;
+/// Set in an entry's length when the record starts with a CRC32 of the payload.
+/// Records written before checksums were introduced don't have it.
+const CHECKSUM_FLAG: u32 = 1 << 31;
+const CHECKSUM_BYTES: usize = 4;
+/// Where unreadable chunk records are moved, relative to the world folder.
+pub const QUARANTINE_DIR: &str = "quarantine";
+
+#[derive(Debug, Clone, Copy, Default)]
+struct RegionEntry {
+    /// Offset of the record in sectors from the start of the file.
+    offset: u32,
+    /// Record length in bytes, plus `CHECKSUM_FLAG`. Zero means the chunk is not stored.
+    length: u32,
+}
+
+impl RegionEntry {
+    fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    fn byte_len(&self) -> usize {
+        (self.length & !CHECKSUM_FLAG) as usize
+    }
+
+    fn has_checksum(&self) -> bool {
+        self.length & CHECKSUM_FLAG != 0
+    }
+
+    fn sectors(&self) -> u32 {
+        sectors_for(self.byte_len())
+    }
+}
+
+/// CRC-32 (IEEE), as used by zip and png.
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Replace `path` with `data` without ever leaving a partially written file behind:
+/// write a sibling temp file, sync it, then rename it over the target.
+pub fn write_atomic(path: &Path, data: &[u8]) -> io::Result<()> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+    {
+        let mut tmp = File::create(&tmp_path)?;
+        tmp.write_all(data)?;
+        tmp.sync_all()?;
+    }
+    std::fs::rename(&tmp_path, path)?;
+    // Make the rename itself durable where the platform allows syncing a directory.
+    if let Some(dir) = path.parent() {
+        if let Ok(dir) = File::open(dir) {
+            let _ = dir.sync_all();
+        }
+    }
+    Ok(())
+}
+
+fn sectors_for(length: usize) -> u32 {
+    ((length as u64 + SECTOR_SIZE - 1) / SECTOR_SIZE) as u32
+}
+
+pub struct RegionFile {
+    path: PathBuf,
+    file: File,
+    entries: Vec<RegionEntry>,
+    /// Sector usage map, including the header sectors.
+    used: Vec<bool>,
+    /// Records replaced since the last `sync`. Their sectors stay reserved until then,
+    /// so the header on disk never points at data that has been overwritten.
+    pending_free: Vec<RegionEntry>,
+    /// Entries changed by `write` since the last `sync`. They only go to the header
+    /// on disk once the records they point at have been synced.
+    unsynced: Vec<usize>,
+}
+
+impl RegionFile {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+
+        let mut entries = vec![RegionEntry::default(); CHUNKS_PER_REGION];
+        let len = file.metadata()?.len();
+        if len < HEADER_BYTES as u64 {
+            file.set_len(HEADER_BYTES as u64)?;
+        } else {
+            let mut header = vec![0u8; HEADER_BYTES];
+            file.seek(SeekFrom::Start(0))?;
+            file.read_exact(&mut header)?;
+            for (entry, raw) in entries.iter_mut().zip(header.chunks_exact(ENTRY_SIZE)) {
+                entry.offset = u32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]);
+                entry.length = u32::from_le_bytes([raw[4], raw[5], raw[6], raw[7]]);
+            }
+        }
+
+        let total_sectors = sectors_for(file.metadata()?.len() as usize).max(HEADER_SECTORS);
+        let mut used = vec![false; total_sectors as usize];
+        for sector in used.iter_mut().take(HEADER_SECTORS as usize) {
+            *sector = true;
+        }
+
+        for (index, entry) in entries.iter_mut().enumerate() {
+            if entry.is_empty() {
+                continue;
+            }
+            let start = entry.offset;
+            let end = start + entry.sectors();
+            if start < HEADER_SECTORS || end > total_sectors {
+                log::warn!(
+                    "Region {:?}: entry {} points outside the file, dropping it",
+                    path,
+                    index
+                );
+                *entry = RegionEntry::default();
+                continue;
+            }
+            for sector in start..end {
+                used[sector as usize] = true;
+            }
+        }
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            entries,
+            used,
+            pending_free: Vec::new(),
+            unsynced: Vec::new(),
+        })
+    }
+
+    pub fn contains(&self, index: usize) -> bool {
+        !self.entries[index].is_empty()
+    }
+
+    /// The stored record exactly as on disk, checksum included.
+    fn read_record(&mut self, index: usize) -> io::Result<Option<Vec<u8>>> {
+        let entry = self.entries[index];
+        if entry.is_empty() {
+            return Ok(None);
+        }
+        let mut record = vec![0u8; entry.byte_len()];
+        self.file
+            .seek(SeekFrom::Start(entry.offset as u64 * SECTOR_SIZE))?;
+        self.file.read_exact(&mut record)?;
+        Ok(Some(record))
+    }
+
+    /// Read a chunk's payload. A record whose checksum doesn't match fails with
+    /// `io::ErrorKind::InvalidData`.
+    pub fn read(&mut self, index: usize) -> io::Result<Option<Vec<u8>>> {
+        let entry = self.entries[index];
+        let Some(mut record) = self.read_record(index)? else {
+            return Ok(None);
+        };
+        if !entry.has_checksum() {
+            return Ok(Some(record));
+        }
+        if record.len() < CHECKSUM_BYTES {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "truncated chunk record"));
+        }
+        let payload = record.split_off(CHECKSUM_BYTES);
+        let stored = u32::from_le_bytes([record[0], record[1], record[2], record[3]]);
+        if crc32(&payload) != stored {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "chunk checksum mismatch"));
+        }
+        Ok(Some(payload))
+    }
+
+    /// Read a chunk's payload without verifying it, for salvaging damaged records.
+    pub fn read_unchecked(&mut self, index: usize) -> io::Result<Option<Vec<u8>>> {
+        let entry = self.entries[index];
+        let record = self.read_record(index)?;
+        Ok(record.map(|mut record| {
+            if entry.has_checksum() {
+                record.drain(..CHECKSUM_BYTES.min(record.len()));
+            }
+            record
+        }))
+    }
+
+    /// Store a chunk's payload. It can be read back straight away, but the header
+    /// only points at it on disk after the next `sync`.
+    pub fn write(&mut self, index: usize, data: &[u8]) -> io::Result<()> {
+        let mut record = Vec::with_capacity(CHECKSUM_BYTES + data.len());
+        record.extend_from_slice(&crc32(data).to_le_bytes());
+        record.extend_from_slice(data);
+
+        // Never overwrite the current record in place: a torn write would lose both versions.
+        let needed = sectors_for(record.len());
+        let offset = self.allocate(needed);
+        let entry = RegionEntry {
+            offset,
+            length: record.len() as u32 | CHECKSUM_FLAG,
+        };
+
+        let end = (offset + needed) as usize;
+        if end > self.used.len() {
+            self.used.resize(end, false);
+            self.file.set_len(end as u64 * SECTOR_SIZE)?;
+        }
+        self.mark(entry, true);
+
+        self.file.seek(SeekFrom::Start(offset as u64 * SECTOR_SIZE))?;
+        self.file.write_all(&record)?;
+
+        let old = std::mem::replace(&mut self.entries[index], entry);
+        self.unsynced.push(index);
+        if !old.is_empty() {
+            self.pending_free.push(old);
+        }
+        Ok(())
+    }
+
+    pub fn remove(&mut self, index: usize) -> io::Result<()> {
+        let old = self.entries[index];
+        if old.is_empty() {
+            return Ok(());
+        }
+        self.entries[index] = RegionEntry::default();
+        self.write_entry(index)?;
+        self.pending_free.push(old);
+        Ok(())
+    }
+
+    /// Flush written records, then the header entries pointing at them, and release
+    /// the sectors of replaced records.
+    pub fn sync(&mut self) -> io::Result<()> {
+        if !self.unsynced.is_empty() {
+            // The records must be on disk before the header points at them.
+            self.file.sync_data()?;
+            for index in std::mem::take(&mut self.unsynced) {
+                self.write_entry(index)?;
+            }
+        }
+        self.file.sync_data()?;
+        for entry in std::mem::take(&mut self.pending_free) {
+            self.mark(entry, false);
+        }
+        Ok(())
+    }
+
+    /// Fraction of data sectors (excluding the header) that hold no record.
+    pub fn free_ratio(&self) -> f32 {
+        let data_sectors = self.used.len() as u32 - HEADER_SECTORS;
+        if data_sectors == 0 {
+            return 0.0;
+        }
+        let free = self.used.iter().filter(|used| !**used).count() as u32;
+        free as f32 / data_sectors as f32
+    }
+
+    pub fn needs_compaction(&self) -> bool {
+        self.used.len() as u32 >= COMPACT_MIN_SECTORS && self.free_ratio() > COMPACT_FREE_RATIO
+    }
+
+    /// Rewrite the file with all records packed back to back, dropping unused sectors.
+    /// Records are copied verbatim, so damaged ones stay detectable.
+    pub fn compact(&mut self) -> io::Result<()> {
+        let mut records = Vec::new();
+        for index in 0..CHUNKS_PER_REGION {
+            let entry = self.entries[index];
+            if let Some(record) = self.read_record(index)? {
+                records.push((index, entry.length, record));
+            }
+        }
+
+        let mut file = vec![0u8; HEADER_BYTES];
+        let mut next_sector = HEADER_SECTORS;
+        for (index, length, record) in &records {
+            let sectors = sectors_for(record.len());
+            let raw = &mut file[index * ENTRY_SIZE..(index + 1) * ENTRY_SIZE];
+            raw[0..4].copy_from_slice(&next_sector.to_le_bytes());
+            raw[4..8].copy_from_slice(&length.to_le_bytes());
+            let start = file.len();
+            file.extend_from_slice(record);
+            file.resize(start + (sectors as u64 * SECTOR_SIZE) as usize, 0);
+            next_sector += sectors;
+        }
+        write_atomic(&self.path, &file)?;
+
+        *self = Self::open(&self.path)?;
+        Ok(())
+    }
+
+    fn mark(&mut self, entry: RegionEntry, used: bool) {
+        if entry.is_empty() {
+            return;
+        }
+        let start = entry.offset as usize;
+        let end = (start + entry.sectors() as usize).min(self.used.len());
+        for sector in &mut self.used[start..end] {
+            *sector = used;
+        }
+    }
+
+    /// First-fit search for a run of free sectors, appending at the end if none fits.
+    fn allocate(&self, needed: u32) -> u32 {
+        let mut run_start = 0usize;
+        let mut run_len = 0u32;
+        for (sector, used) in self.used.iter().enumerate() {
+            if *used {
+                run_len = 0;
+                continue;
+            }
+            if run_len == 0 {
+                run_start = sector;
+            }
+            run_len += 1;
+            if run_len == needed {
+                return run_start as u32;
+            }
+        }
+        if run_len > 0 && run_start + run_len as usize == self.used.len() {
+            // Extend the trailing free run instead of leaving a gap.
+            return run_start as u32;
+        }
+        self.used.len() as u32
+    }
+
+    fn write_entry(&mut self, index: usize) -> io::Result<()> {
+        let entry = self.entries[index];
+        let mut raw = [0u8; ENTRY_SIZE];
+        raw[0..4].copy_from_slice(&entry.offset.to_le_bytes());
+        raw[4..8].copy_from_slice(&entry.length.to_le_bytes());
+        self.file
+            .seek(SeekFrom::Start((index * ENTRY_SIZE) as u64))?;
+        self.file.write_all(&raw)
+    }
+}
+
+/// All region files of one world, opened lazily and kept open while in use.
+pub struct RegionStorage {
+    dir: PathBuf,
+    regions: HashMap<(i32, i32), RegionFile>,
+}
+
+impl RegionStorage {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            regions: HashMap::new(),
+        }
+    }
+
+    /// Region coordinates and header index for a chunk.
+    pub fn locate(chunk_x: i32, chunk_z: i32) -> ((i32, i32), usize) {
+        let region = (chunk_x.div_euclid(REGION_SIZE), chunk_z.div_euclid(REGION_SIZE));
+        let local_x = chunk_x.rem_euclid(REGION_SIZE) as usize;
+        let local_z = chunk_z.rem_euclid(REGION_SIZE) as usize;
+        (region, local_x + local_z * REGION_SIZE as usize)
+    }
+
+    fn region_path(&self, region_x: i32, region_z: i32) -> PathBuf {
+        self.dir.join(format!("r.{}.{}.region", region_x, region_z))
+    }
+
+    fn region(&mut self, region: (i32, i32), create: bool) -> io::Result<Option<&mut RegionFile>> {
+        if !self.regions.contains_key(&region) {
+            let path = self.region_path(region.0, region.1);
+            if !create && !path.exists() {
+                return Ok(None);
+            }
+            std::fs::create_dir_all(&self.dir)?;
+            let file = RegionFile::open(&path)?;
+            self.regions.insert(region, file);
+        }
+        Ok(self.regions.get_mut(&region))
+    }
+
+    /// Read a chunk's payload; fails with `io::ErrorKind::InvalidData` if its checksum is wrong.
+    pub fn read_chunk(&mut self, chunk_x: i32, chunk_z: i32) -> io::Result<Option<Vec<u8>>> {
+        let (region, index) = Self::locate(chunk_x, chunk_z);
+        match self.region(region, false)? {
+            Some(file) => file.read(index),
+            None => Ok(None),
+        }
+    }
+
+    pub fn write_chunk(&mut self, chunk_x: i32, chunk_z: i32, data: &[u8]) -> io::Result<()> {
+        let (region, index) = Self::locate(chunk_x, chunk_z);
+        let file = self
+            .region(region, true)?
+            .expect("region is created on demand");
+        file.write(index, data)?;
+        if file.needs_compaction() {
+            file.compact()?;
+        }
+        Ok(())
+    }
+
+    /// Sync every open region file. Call after a batch of writes.
+    pub fn sync(&mut self) -> io::Result<()> {
+        for file in self.regions.values_mut() {
+            file.sync()?;
+        }
+        Ok(())
+    }
+
+    /// Move a damaged chunk record out of its region into the world's quarantine
+    /// folder, so it can be inspected or repaired instead of being overwritten.
+    /// The payload is saved in the legacy `chunk_{x}_{z}.bin` layout under a
+    /// timestamped name. Returns the path it was written to.
+    pub fn quarantine_chunk(&mut self, chunk_x: i32, chunk_z: i32) -> io::Result<Option<PathBuf>> {
+        let (region, index) = Self::locate(chunk_x, chunk_z);
+        let quarantine_dir = self
+            .dir
+            .parent()
+            .unwrap_or(&self.dir)
+            .join(QUARANTINE_DIR);
+        let Some(file) = self.region(region, false)? else {
+            return Ok(None);
+        };
+        let Some(payload) = file.read_unchecked(index)? else {
+            return Ok(None);
+        };
+
+        std::fs::create_dir_all(&quarantine_dir)?;
+        let stamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let path = quarantine_dir.join(format!("chunk_{}_{}.{}.bin", chunk_x, chunk_z, stamp));
+        write_atomic(&path, &payload)?;
+
+        file.remove(index)?;
+        file.sync()?;
+        Ok(Some(path))
+    }
+
+    /// Compact every region file on disk, not just the ones currently open.
+    pub fn compact_all(&mut self) -> io::Result<()> {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return Ok(());
+        };
+        for entry in entries.flatten() {
+            let Some(region) = parse_region_name(&entry.path()) else {
+                continue;
+            };
+            if let Some(file) = self.region(region, false)? {
+                file.compact()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Move legacy `chunk_{x}_{z}.bin` files from `legacy_dir` into region files,
+    /// storing what `convert` makes of each. Files it can't convert are moved to the
+    /// quarantine folder instead.
+    ///
+    /// Each file is deleted once the records are synced; the directory itself is
+    /// removed when it ends up empty. Returns the number of migrated chunks.
+    pub fn migrate_legacy_chunks(
+        &mut self,
+        legacy_dir: &Path,
+        mut convert: impl FnMut(&[u8]) -> Option<Vec<u8>>,
+    ) -> io::Result<usize> {
+        let Ok(entries) = std::fs::read_dir(legacy_dir) else {
+            return Ok(0);
+        };
+
+        let mut migrated = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some((chunk_x, chunk_z)) = parse_legacy_chunk_name(&path) else {
+                continue;
+            };
+            let Some(data) = convert(&std::fs::read(&path)?) else {
+                let quarantine_dir = self.dir.parent().unwrap_or(&self.dir).join(QUARANTINE_DIR);
+                std::fs::create_dir_all(&quarantine_dir)?;
+                log::warn!("Legacy chunk file {:?} is unreadable; moved to {:?}", path, quarantine_dir);
+                std::fs::rename(&path, quarantine_dir.join(entry.file_name()))?;
+                continue;
+            };
+            self.write_chunk(chunk_x, chunk_z, &data)?;
+            migrated.push(path);
+        }
+        self.sync()?;
+        for path in &migrated {
+            std::fs::remove_file(path)?;
+        }
+
+        // Only succeeds if nothing else was left in the directory.
+        let _ = std::fs::remove_dir(legacy_dir);
+        Ok(migrated.len())
+    }
+}
+
+fn parse_region_name(path: &Path) -> Option<(i32, i32)> {
+    let name = path.file_name()?.to_str()?;
+    let coords = name.strip_prefix("r.")?.strip_suffix(".region")?;
+    let (x, z) = coords.split_once('.')?;
+    Some((x.parse().ok()?, z.parse().ok()?))
+}
+
+fn parse_legacy_chunk_name(path: &Path) -> Option<(i32, i32)> {
+    let name = path.file_name()?.to_str()?;
+    let coords = name.strip_prefix("chunk_")?.strip_suffix(".bin")?;
+    let (x, z) = coords.split_once('_')?;
+    Some((x.parse().ok()?, z.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An empty directory for one test, removed first if an earlier run left it behind.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("region-test-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn payload(byte: u8, len: usize) -> Vec<u8> {
+        vec![byte; len]
+    }
+
+    #[test]
+    fn chunks_read_back_after_reopening() {
+        let dir = temp_dir("round-trip");
+        let mut storage = RegionStorage::new(dir.clone());
+        storage.write_chunk(0, 0, &payload(1, 100)).unwrap();
+        storage.write_chunk(-1, 40, &payload(2, 9000)).unwrap();
+        storage.sync().unwrap();
+        drop(storage);
+
+        let mut storage = RegionStorage::new(dir.clone());
+        assert_eq!(storage.read_chunk(0, 0).unwrap(), Some(payload(1, 100)));
+        assert_eq!(storage.read_chunk(-1, 40).unwrap(), Some(payload(2, 9000)));
+        assert_eq!(storage.read_chunk(1, 0).unwrap(), None);
+        assert_eq!(storage.read_chunk(100, 100).unwrap(), None);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn rewritten_records_free_their_sectors_only_after_sync() {
+        let dir = temp_dir("allocation");
+        let mut file = RegionFile::open(&dir.join("r.0.0.region")).unwrap();
+        file.write(0, &payload(1, 5000)).unwrap();
+        assert_eq!(file.entries[0].offset, HEADER_SECTORS);
+
+        // The replaced record stays reserved until the new one is synced.
+        file.write(0, &payload(2, 5000)).unwrap();
+        assert_eq!(file.entries[0].offset, HEADER_SECTORS + 2);
+        file.write(1, &payload(3, 100)).unwrap();
+        assert_eq!(file.entries[1].offset, HEADER_SECTORS + 4);
+
+        file.sync().unwrap();
+        file.write(2, &payload(4, 100)).unwrap();
+        assert_eq!(file.entries[2].offset, HEADER_SECTORS);
+        assert_eq!(file.read(0).unwrap(), Some(payload(2, 5000)));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn compaction_packs_records_and_keeps_them_readable() {
+        let dir = temp_dir("compaction");
+        let mut file = RegionFile::open(&dir.join("r.0.0.region")).unwrap();
+        for index in 0..40 {
+            file.write(index, &payload(index as u8, 5000)).unwrap();
+        }
+        file.sync().unwrap();
+        for index in 0..40 {
+            file.write(index, &payload(index as u8 + 100, 100)).unwrap();
+        }
+        file.sync().unwrap();
+        assert!(file.needs_compaction());
+
+        file.compact().unwrap();
+        assert_eq!(file.used.len() as u32, HEADER_SECTORS + 40);
+        assert_eq!(file.free_ratio(), 0.0);
+        for index in 0..40 {
+            assert_eq!(file.read(index).unwrap(), Some(payload(index as u8 + 100, 100)));
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn header_only_points_at_records_once_synced() {
+        let dir = temp_dir("sync");
+        let path = dir.join("r.0.0.region");
+        let mut file = RegionFile::open(&path).unwrap();
+        file.write(0, &payload(1, 100)).unwrap();
+        drop(file);
+        assert!(!RegionFile::open(&path).unwrap().contains(0));
+
+        let mut file = RegionFile::open(&path).unwrap();
+        file.write(0, &payload(1, 100)).unwrap();
+        file.sync().unwrap();
+        file.write(0, &payload(2, 100)).unwrap();
+        drop(file);
+        let mut file = RegionFile::open(&path).unwrap();
+        assert_eq!(file.read(0).unwrap(), Some(payload(1, 100)));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn corrupt_records_fail_their_checksum_and_are_quarantined() {
+        let dir = temp_dir("corrupt");
+        let regions = dir.join("regions");
+        let mut storage = RegionStorage::new(regions.clone());
+        storage.write_chunk(3, 4, &payload(7, 100)).unwrap();
+        storage.sync().unwrap();
+        drop(storage);
+
+        let (_, index) = RegionStorage::locate(3, 4);
+        let path = regions.join("r.0.0.region");
+        let mut bytes = std::fs::read(&path).unwrap();
+        let offset = u32::from_le_bytes(bytes[index * ENTRY_SIZE..index * ENTRY_SIZE + 4].try_into().unwrap());
+        bytes[(offset as u64 * SECTOR_SIZE) as usize + CHECKSUM_BYTES + 10] ^= 0xFF;
+        std::fs::write(&path, &bytes).unwrap();
+
+        let mut storage = RegionStorage::new(regions);
+        let err = storage.read_chunk(3, 4).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let quarantined = storage.quarantine_chunk(3, 4).unwrap().unwrap();
+        assert!(quarantined.starts_with(dir.join(QUARANTINE_DIR)));
+        let mut expected = payload(7, 100);
+        expected[10] ^= 0xFF;
+        assert_eq!(std::fs::read(&quarantined).unwrap(), expected);
+        assert_eq!(storage.read_chunk(3, 4).unwrap(), None);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
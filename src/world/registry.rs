@@ -0,0 +1,289 @@
+#![allow(dead_code)]
+/// Block definitions driven by `data/blocks.json`.
+///
+/// Every `BlockType` has one definition giving its namespaced name, numeric id,
+/// physical and lighting facts, mining tool, shape, colour, per-face textures,
+/// state properties and drops. `BlockType`'s own methods (`is_solid`, `hardness`, ...) read from the
+/// global registry, so world code, the renderer and mods all see the same data.
+/// Code that needs to name a particular block still uses the `BlockType`
+/// variant; the registry ties each variant to its definition through the
+/// numeric id, which is the variant's discriminant.
+use super::block::BlockType;
+use super::mining::{ToolKind, ToolTier};
+use super::shape::BlockShape;
+use super::state::Property;
+use anyhow::{bail, Context};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+pub const BLOCK_REGISTRY_PATH: &str = "data/blocks.json";
+const DEFAULT_BLOCK_REGISTRY: &str = include_str!("../../data/blocks.json");
+
+static REGISTRY: OnceLock<BlockRegistry> = OnceLock::new();
+
+/// Texture names per face, as understood by the renderer's atlas. Faces fall back
+/// to `all` when they have no texture of their own.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BlockTextures {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub all: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bottom: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub side: Option<String>,
+    /// Faces along the `axis` property, such as the cut ends of a log.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<String>,
+    /// The face a `horizontal_facing` block points towards.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub front: Option<String>,
+}
+
+impl BlockTextures {
+    fn pick<'a>(&'a self, face: &'a Option<String>) -> Option<&'a str> {
+        face.as_deref().or(self.all.as_deref())
+    }
+
+    pub fn top(&self) -> Option<&str> {
+        self.pick(&self.top).or(self.end.as_deref())
+    }
+
+    pub fn bottom(&self) -> Option<&str> {
+        self.pick(&self.bottom).or(self.end.as_deref())
+    }
+
+    pub fn side(&self) -> Option<&str> {
+        self.pick(&self.side)
+    }
+
+    pub fn end(&self) -> Option<&str> {
+        self.pick(&self.end)
+    }
+
+    pub fn front(&self) -> Option<&str> {
+        self.pick(&self.front).or(self.side.as_deref())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockDrop {
+    /// Namespaced item name.
+    pub item: String,
+    #[serde(default = "default_drop_count")]
+    pub count: u32,
+    /// Odds of the drop happening at all, from 0.0 to 1.0.
+    #[serde(default = "default_drop_chance")]
+    pub chance: f32,
+}
+
+fn default_drop_count() -> u32 {
+    1
+}
+
+fn default_drop_chance() -> f32 {
+    1.0
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_hardness() -> f32 {
+    1.0
+}
+
+fn default_color() -> [f32; 3] {
+    [0.7, 0.7, 0.7]
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockDefinition {
+    /// Namespaced name such as `minecraft:stone`.
+    pub name: String,
+    /// Numeric id used by mods and the network protocol; the `BlockType` discriminant.
+    pub id: u16,
+    /// Collides, gets targeted and is meshed as a block.
+    #[serde(default = "default_true")]
+    pub solid: bool,
+    /// Lets neighbouring faces show through.
+    #[serde(default)]
+    pub transparent: bool,
+    /// See `shape::BlockShape`.
+    #[serde(default)]
+    pub shape: BlockShape,
+    /// Breaking effort; negative means unbreakable.
+    #[serde(default = "default_hardness")]
+    pub hardness: f32,
+    /// Tool that mines the block fastest.
+    #[serde(default)]
+    pub tool: Option<ToolKind>,
+    /// Lowest tier of `tool` that harvests the block. Anything less mines it
+    /// slowly and gets no drops; `None` means anything harvests it.
+    #[serde(default)]
+    pub tool_tier: Option<ToolTier>,
+    #[serde(default)]
+    pub light_emission: u8,
+    /// Light lost passing through; defaults to 0 for transparent blocks and 15 otherwise.
+    #[serde(default)]
+    pub light_opacity: Option<u8>,
+    #[serde(default = "default_color")]
+    pub color: [f32; 3],
+    #[serde(default)]
+    pub properties: Vec<Property>,
+    #[serde(default)]
+    pub textures: BlockTextures,
+    /// What breaking the block gives; defaults to one of the block itself.
+    #[serde(default)]
+    pub drops: Option<Vec<BlockDrop>>,
+}
+
+impl BlockDefinition {
+    /// Stand-in for a block type the definitions file doesn't cover.
+    fn placeholder(block: BlockType) -> Self {
+        Self {
+            name: format!("minecraft:unknown_{}", block as u16),
+            id: block as u16,
+            solid: true,
+            transparent: false,
+            shape: BlockShape::Cube,
+            hardness: default_hardness(),
+            tool: None,
+            tool_tier: None,
+            light_emission: 0,
+            light_opacity: None,
+            color: default_color(),
+            properties: Vec::new(),
+            textures: BlockTextures::default(),
+            drops: None,
+        }
+    }
+
+    pub fn is_full_cube(&self) -> bool {
+        self.solid && self.shape == BlockShape::Cube
+    }
+
+    pub fn light_opacity(&self) -> u8 {
+        self.light_opacity
+            .unwrap_or(if self.transparent { 0 } else { 15 })
+    }
+
+    pub fn drops(&self) -> Vec<BlockDrop> {
+        match &self.drops {
+            Some(drops) => drops.clone(),
+            None => vec![BlockDrop {
+                item: self.name.clone(),
+                count: 1,
+                chance: 1.0,
+            }],
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct BlockFile {
+    blocks: Vec<BlockDefinition>,
+}
+
+#[derive(Debug)]
+pub struct BlockRegistry {
+    /// Indexed by numeric id.
+    definitions: Vec<Option<BlockDefinition>>,
+    by_name: HashMap<String, BlockType>,
+}
+
+impl BlockRegistry {
+    pub fn from_json(contents: &str) -> anyhow::Result<Self> {
+        let file: BlockFile = serde_json::from_str(contents)?;
+        let mut definitions: Vec<Option<BlockDefinition>> = Vec::new();
+        let mut by_name = HashMap::new();
+
+        for definition in file.blocks {
+            if !definition.name.contains(':') {
+                bail!("block name {:?} has no namespace", definition.name);
+            }
+            let Some(block) = BlockType::ALL.iter().copied().find(|b| *b as u16 == definition.id) else {
+                bail!("{} has id {}, which this build has no block type for", definition.name, definition.id);
+            };
+            if by_name.insert(definition.name.clone(), block).is_some() {
+                bail!("block {} is defined twice", definition.name);
+            }
+            let index = definition.id as usize;
+            if definitions.len() <= index {
+                definitions.resize(index + 1, None);
+            }
+            if let Some(existing) = &definitions[index] {
+                bail!("{} and {} share id {}", existing.name, definition.name, definition.id);
+            }
+            definitions[index] = Some(definition);
+        }
+
+        for block in BlockType::ALL {
+            let index = *block as usize;
+            if definitions.get(index).map_or(true, Option::is_none) {
+                log::warn!("No definition for block {:?}; using a placeholder", block);
+                let placeholder = BlockDefinition::placeholder(*block);
+                by_name.insert(placeholder.name.clone(), *block);
+                if definitions.len() <= index {
+                    definitions.resize(index + 1, None);
+                }
+                definitions[index] = Some(placeholder);
+            }
+        }
+
+        Ok(Self { definitions, by_name })
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("reading {}", path.display()))?;
+        Self::from_json(&contents).with_context(|| format!("loading {}", path.display()))
+    }
+
+    /// `BLOCK_REGISTRY_PATH` relative to the working directory if present, otherwise the built-in definitions.
+    pub fn load_or_default() -> Self {
+        let path = Path::new(BLOCK_REGISTRY_PATH);
+        if path.exists() {
+            match Self::load(path) {
+                Ok(registry) => return registry,
+                Err(err) => log::warn!("Failed to load {}: {:#}; using built-in block definitions", BLOCK_REGISTRY_PATH, err),
+            }
+        }
+        Self::from_json(DEFAULT_BLOCK_REGISTRY).expect("Built-in block definitions are invalid")
+    }
+
+    /// The registry shared by the whole game, loaded on first use.
+    pub fn global() -> &'static Self {
+        REGISTRY.get_or_init(Self::load_or_default)
+    }
+
+    /// Install the shared registry before anything has used it. Returns the registry
+    /// back if one is already in place.
+    pub fn install(registry: Self) -> Result<(), Self> {
+        REGISTRY.set(registry)
+    }
+
+    pub fn get(&self, block: BlockType) -> &BlockDefinition {
+        self.definitions[block as usize]
+            .as_ref()
+            .expect("every block type has a definition")
+    }
+
+    pub fn by_name(&self, name: &str) -> Option<BlockType> {
+        self.by_name.get(name).copied()
+    }
+
+    pub fn by_id(&self, id: u16) -> Option<BlockType> {
+        self.definitions
+            .get(id as usize)
+            .and_then(Option::as_ref)
+            .and_then(|definition| self.by_name(&definition.name))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (BlockType, &BlockDefinition)> {
+        BlockType::ALL.iter().map(|block| (*block, self.get(*block)))
+    }
+}
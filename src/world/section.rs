@@ -0,0 +1,236 @@
+#![allow(dead_code)]
+/// Palette-compressed 16x16x16 chunk sections.
+///
+/// Each section stores the distinct block states it contains in a palette and
+/// packs one palette index per block into `u64` words. Indices never straddle
+/// word boundaries, so a word holds `64 / bits` entries.
+use super::block::BlockType;
+use super::state::BlockState;
+use serde::{Deserialize, Serialize};
+
+pub const SECTION_SIZE: usize = 16;
+pub const SECTION_VOLUME: usize = SECTION_SIZE * SECTION_SIZE * SECTION_SIZE;
+const MIN_BITS: u8 = 4;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkSection {
+    palette: Vec<BlockState>,
+    bits: u8,
+    data: Vec<u64>,
+    /// Number of blocks that are not `BlockType::Air`; zero means the section can be dropped.
+    non_air: u16,
+}
+
+impl ChunkSection {
+    /// A section filled with air.
+    pub fn new() -> Self {
+        Self {
+            palette: vec![BlockState::default()],
+            bits: MIN_BITS,
+            data: vec![0; Self::words_for(MIN_BITS)],
+            non_air: 0,
+        }
+    }
+
+    fn words_for(bits: u8) -> usize {
+        let per_word = 64 / bits as usize;
+        (SECTION_VOLUME + per_word - 1) / per_word
+    }
+
+    fn index(x: usize, y: usize, z: usize) -> usize {
+        x + z * SECTION_SIZE + y * SECTION_SIZE * SECTION_SIZE
+    }
+
+    fn read_index(&self, slot: usize) -> usize {
+        let per_word = 64 / self.bits as usize;
+        let word = self.data[slot / per_word];
+        let shift = (slot % per_word) * self.bits as usize;
+        let mask = (1u64 << self.bits) - 1;
+        ((word >> shift) & mask) as usize
+    }
+
+    fn write_index(&mut self, slot: usize, value: usize) {
+        let per_word = 64 / self.bits as usize;
+        let shift = (slot % per_word) * self.bits as usize;
+        let mask = (1u64 << self.bits) - 1;
+        let word = &mut self.data[slot / per_word];
+        *word = (*word & !(mask << shift)) | ((value as u64 & mask) << shift);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.non_air == 0
+    }
+
+    pub fn palette(&self) -> &[BlockState] {
+        &self.palette
+    }
+
+    /// Approximate heap footprint in bytes, used for the world's memory budget.
+    pub fn heap_size(&self) -> usize {
+        self.palette.capacity() * std::mem::size_of::<BlockState>()
+            + self.data.capacity() * std::mem::size_of::<u64>()
+    }
+
+    /// Local coordinates must be below `SECTION_SIZE`.
+    pub fn get(&self, x: usize, y: usize, z: usize) -> BlockState {
+        self.palette[self.read_index(Self::index(x, y, z))]
+    }
+
+    /// Local coordinates must be below `SECTION_SIZE`.
+    pub fn set(&mut self, x: usize, y: usize, z: usize, block: BlockState) {
+        let slot = Self::index(x, y, z);
+        let previous = self.palette[self.read_index(slot)];
+        if previous == block {
+            return;
+        }
+
+        let palette_index = match self.palette.iter().position(|b| *b == block) {
+            Some(index) => index,
+            None => self.insert_palette_entry(block),
+        };
+        self.write_index(slot, palette_index);
+
+        if previous.block == BlockType::Air && block.block != BlockType::Air {
+            self.non_air += 1;
+        } else if previous.block != BlockType::Air && block.block == BlockType::Air {
+            self.non_air -= 1;
+        }
+    }
+
+    /// Add `block` to the palette, pruning unused entries or widening indices as needed.
+    fn insert_palette_entry(&mut self, block: BlockState) -> usize {
+        if self.palette.len() >= 1 << self.bits {
+            self.prune_palette();
+        }
+        if self.palette.len() >= 1 << self.bits {
+            self.repack(self.bits + 1);
+        }
+        self.palette.push(block);
+        self.palette.len() - 1
+    }
+
+    /// Drop palette entries no block refers to any more and renumber the indices.
+    fn prune_palette(&mut self) {
+        let mut used = vec![false; self.palette.len()];
+        for slot in 0..SECTION_VOLUME {
+            used[self.read_index(slot)] = true;
+        }
+        if used.iter().all(|u| *u) {
+            return;
+        }
+
+        let mut remap = vec![0usize; self.palette.len()];
+        let mut palette = Vec::with_capacity(self.palette.len());
+        for (old, block) in self.palette.iter().enumerate() {
+            if used[old] {
+                remap[old] = palette.len();
+                palette.push(*block);
+            }
+        }
+        for slot in 0..SECTION_VOLUME {
+            let old = self.read_index(slot);
+            self.write_index(slot, remap[old]);
+        }
+        self.palette = palette;
+    }
+
+    fn repack(&mut self, bits: u8) {
+        let indices: Vec<usize> = (0..SECTION_VOLUME).map(|slot| self.read_index(slot)).collect();
+        self.bits = bits;
+        self.data = vec![0; Self::words_for(bits)];
+        for (slot, index) in indices.into_iter().enumerate() {
+            self.write_index(slot, index);
+        }
+    }
+
+    /// Check invariants after deserializing untrusted data.
+    pub fn is_valid(&self) -> bool {
+        self.bits >= MIN_BITS
+            && self.bits <= 16
+            && !self.palette.is_empty()
+            && self.palette.len() <= 1 << self.bits
+            && self.palette.iter().all(|state| state.is_valid())
+            && self.data.len() == Self::words_for(self.bits)
+            && (0..SECTION_VOLUME).all(|slot| self.read_index(slot) < self.palette.len())
+            && self.non_air as usize == self.count_non_air()
+    }
+
+    fn count_non_air(&self) -> usize {
+        (0..SECTION_VOLUME)
+            .filter(|slot| self.palette[self.read_index(*slot)].block != BlockType::Air)
+            .count()
+    }
+}
+
+impl Default for ChunkSection {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `count` distinct blocks other than air.
+    fn solid_blocks(count: usize) -> Vec<BlockState> {
+        BlockType::ALL
+            .iter()
+            .filter(|block| **block != BlockType::Air)
+            .take(count)
+            .map(|block| BlockState::new(*block))
+            .collect()
+    }
+
+    #[test]
+    fn set_widens_indices_when_the_palette_fills_up() {
+        let mut section = ChunkSection::new();
+        let blocks = solid_blocks(20);
+        for (i, block) in blocks.iter().enumerate() {
+            section.set(i % SECTION_SIZE, i / SECTION_SIZE, 3, *block);
+        }
+
+        assert_eq!(section.bits, MIN_BITS + 1);
+        assert_eq!(section.palette().len(), 21);
+        for (i, block) in blocks.iter().enumerate() {
+            assert_eq!(section.get(i % SECTION_SIZE, i / SECTION_SIZE, 3), *block);
+        }
+        assert_eq!(section.get(0, 0, 0), BlockState::default());
+        assert_eq!(section.non_air, 20);
+        assert!(section.is_valid());
+    }
+
+    #[test]
+    fn set_prunes_unused_entries_before_widening() {
+        let mut section = ChunkSection::new();
+        for block in solid_blocks(40) {
+            section.set(1, 2, 3, block);
+        }
+
+        assert_eq!(section.bits, MIN_BITS);
+        assert!(section.palette().len() <= 1 << MIN_BITS);
+        assert_eq!(section.get(1, 2, 3), solid_blocks(40)[39]);
+        assert_eq!(section.non_air, 1);
+        assert!(section.is_valid());
+    }
+
+    #[test]
+    fn clearing_every_block_empties_the_section() {
+        let mut section = ChunkSection::new();
+        section.set(0, 0, 0, BlockState::new(BlockType::Stone));
+        section.set(15, 15, 15, BlockState::new(BlockType::Dirt));
+        assert!(!section.is_empty());
+
+        section.set(0, 0, 0, BlockState::default());
+        section.set(15, 15, 15, BlockState::default());
+        assert!(section.is_empty());
+    }
+
+    #[test]
+    fn is_valid_rejects_a_wrong_non_air_count() {
+        let mut section = ChunkSection::new();
+        section.set(4, 5, 6, BlockState::new(BlockType::Stone));
+        section.non_air = 0;
+        assert!(!section.is_valid());
+    }
+}
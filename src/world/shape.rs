@@ -0,0 +1,132 @@
+#![allow(dead_code)]
+/// Shapes of blocks that don't fill their whole space.
+///
+/// A shape is a list of axis-aligned boxes in block-local coordinates, 0.0 to
+/// 1.0 on each axis. Slabs fill the half given by their `half` property.
+/// Stairs are a slab with a raised step covering the side away from their
+/// `facing`, so the low side faces the player who placed them. Fences and
+/// panes are a post that grows arms towards the neighbours they connect to:
+/// blocks of the same shape and full cubes. Connections aren't stored in the
+/// block state; callers work them out from the neighbours when they need them.
+use super::block::BlockType;
+use super::state::{BlockState, Facing, Half};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockShape {
+    #[default]
+    Cube,
+    Slab,
+    Stairs,
+    Fence,
+    Pane,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlockBox {
+    pub min: [f32; 3],
+    pub max: [f32; 3],
+}
+
+impl BlockBox {
+    pub const FULL: BlockBox = BlockBox {
+        min: [0.0; 3],
+        max: [1.0; 3],
+    };
+
+    pub const fn new(min: [f32; 3], max: [f32; 3]) -> Self {
+        Self { min, max }
+    }
+
+    /// The box moved from block-local coordinates to the block at `x, y, z`.
+    pub fn offset(self, x: i32, y: i32, z: i32) -> Self {
+        let origin = [x as f32, y as f32, z as f32];
+        Self {
+            min: [0, 1, 2].map(|axis| self.min[axis] + origin[axis]),
+            max: [0, 1, 2].map(|axis| self.max[axis] + origin[axis]),
+        }
+    }
+}
+
+pub const HORIZONTAL_FACINGS: [Facing; 4] = [Facing::North, Facing::South, Facing::East, Facing::West];
+
+/// Half-width of a fence post; its rails are narrower.
+const FENCE_POST: f32 = 0.125;
+const FENCE_RAIL: f32 = 0.0625;
+const FENCE_RAILS: [(f32, f32); 2] = [(0.375, 0.5625), (0.75, 0.9375)];
+const PANE_HALF_WIDTH: f32 = 0.0625;
+
+impl BlockShape {
+    /// Whether a fence or pane reaches out to join this neighbour.
+    pub fn connects_to(self, neighbour: BlockType) -> bool {
+        match self {
+            BlockShape::Fence | BlockShape::Pane => {
+                neighbour.is_full_cube() || neighbour.shape() == self
+            }
+            _ => false,
+        }
+    }
+}
+
+/// The boxes making up a block. `connected` says whether a fence or pane joins
+/// the neighbour on a horizontal side; other shapes never ask.
+pub fn block_boxes(state: BlockState, connected: impl Fn(Facing) -> bool) -> Vec<BlockBox> {
+    let (lower, upper) = match state.half() {
+        Half::Bottom => ((0.0, 0.5), (0.5, 1.0)),
+        Half::Top => ((0.5, 1.0), (0.0, 0.5)),
+    };
+    match state.block.shape() {
+        BlockShape::Cube => vec![BlockBox::FULL],
+        BlockShape::Slab => vec![BlockBox::new([0.0, lower.0, 0.0], [1.0, lower.1, 1.0])],
+        BlockShape::Stairs => {
+            let (dx, _, dz) = state.facing().opposite().offset();
+            let span = |d: i32| match d {
+                1 => (0.5, 1.0),
+                -1 => (0.0, 0.5),
+                _ => (0.0, 1.0),
+            };
+            let (x, z) = (span(dx), span(dz));
+            vec![
+                BlockBox::new([0.0, lower.0, 0.0], [1.0, lower.1, 1.0]),
+                BlockBox::new([x.0, upper.0, z.0], [x.1, upper.1, z.1]),
+            ]
+        }
+        BlockShape::Fence => {
+            let mut boxes = vec![post(FENCE_POST, (0.0, 1.0))];
+            for facing in HORIZONTAL_FACINGS.into_iter().filter(|f| connected(*f)) {
+                for rail in FENCE_RAILS {
+                    boxes.push(arm(facing, FENCE_POST, FENCE_RAIL, rail));
+                }
+            }
+            boxes
+        }
+        BlockShape::Pane => {
+            let mut boxes = vec![post(PANE_HALF_WIDTH, (0.0, 1.0))];
+            for facing in HORIZONTAL_FACINGS.into_iter().filter(|f| connected(*f)) {
+                boxes.push(arm(facing, PANE_HALF_WIDTH, PANE_HALF_WIDTH, (0.0, 1.0)));
+            }
+            boxes
+        }
+    }
+}
+
+/// A column centred in the block.
+fn post(half_width: f32, (bottom, top): (f32, f32)) -> BlockBox {
+    BlockBox::new(
+        [0.5 - half_width, bottom, 0.5 - half_width],
+        [0.5 + half_width, top, 0.5 + half_width],
+    )
+}
+
+/// A bar from the edge of a post of `post_half_width` out to the side of the block.
+fn arm(facing: Facing, post_half_width: f32, half_width: f32, (bottom, top): (f32, f32)) -> BlockBox {
+    let (dx, _, dz) = facing.offset();
+    let along = |d: i32| match d {
+        1 => (0.5 + post_half_width, 1.0),
+        -1 => (0.0, 0.5 - post_half_width),
+        _ => (0.5 - half_width, 0.5 + half_width),
+    };
+    let (x, z) = (along(dx), along(dz));
+    BlockBox::new([x.0, bottom, z.0], [x.1, top, z.1])
+}
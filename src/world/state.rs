@@ -0,0 +1,254 @@
+#![allow(dead_code)]
+/// Block states: a `BlockType` plus the values of its properties.
+///
+/// Properties are packed into a `u16` so a state is small enough to sit in a
+/// section palette next to thousands of others. Which properties a block has
+/// is given by `BlockType::properties`; setting a property the block doesn't
+/// have is ignored, so every block has exactly one state per combination of
+/// the values that matter and palettes stay small.
+use super::block::BlockType;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Property {
+    Axis,
+    /// Any of the six directions.
+    Facing,
+    /// North, south, east or west only.
+    HorizontalFacing,
+    Half,
+    Waterlogged,
+    /// Fluid level, see `fluid`.
+    Level,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+/// Directions use the same convention as the renderer: north is +Z, east is +X.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Facing {
+    North,
+    South,
+    East,
+    West,
+    Up,
+    Down,
+}
+
+impl Facing {
+    const ALL: [Facing; 6] = [
+        Facing::North,
+        Facing::South,
+        Facing::East,
+        Facing::West,
+        Facing::Up,
+        Facing::Down,
+    ];
+
+    pub fn offset(self) -> (i32, i32, i32) {
+        match self {
+            Facing::North => (0, 0, 1),
+            Facing::South => (0, 0, -1),
+            Facing::East => (1, 0, 0),
+            Facing::West => (-1, 0, 0),
+            Facing::Up => (0, 1, 0),
+            Facing::Down => (0, -1, 0),
+        }
+    }
+
+    /// The direction pointing along a unit offset, if it is one.
+    pub fn from_offset(offset: (i32, i32, i32)) -> Option<Self> {
+        Self::ALL.into_iter().find(|facing| facing.offset() == offset)
+    }
+
+    pub fn opposite(self) -> Self {
+        match self {
+            Facing::North => Facing::South,
+            Facing::South => Facing::North,
+            Facing::East => Facing::West,
+            Facing::West => Facing::East,
+            Facing::Up => Facing::Down,
+            Facing::Down => Facing::Up,
+        }
+    }
+
+    pub fn axis(self) -> Axis {
+        match self {
+            Facing::East | Facing::West => Axis::X,
+            Facing::Up | Facing::Down => Axis::Y,
+            Facing::North | Facing::South => Axis::Z,
+        }
+    }
+
+    /// Horizontal direction closest to a camera yaw in degrees (0 looks towards +X, 90 towards +Z).
+    pub fn from_yaw(yaw: f32) -> Self {
+        match ((yaw.rem_euclid(360.0) + 45.0) / 90.0) as u32 % 4 {
+            0 => Facing::East,
+            1 => Facing::North,
+            2 => Facing::West,
+            _ => Facing::South,
+        }
+    }
+
+    fn is_horizontal(self) -> bool {
+        !matches!(self, Facing::Up | Facing::Down)
+    }
+}
+
+/// Which half of the block space a slab or stair occupies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Half {
+    Bottom,
+    Top,
+}
+
+const AXIS_SHIFT: u16 = 0;
+const AXIS_MASK: u16 = 0b11;
+const FACING_SHIFT: u16 = 2;
+const FACING_MASK: u16 = 0b111;
+const HALF_SHIFT: u16 = 5;
+const WATERLOGGED_SHIFT: u16 = 6;
+const LEVEL_SHIFT: u16 = 7;
+const LEVEL_MASK: u16 = 0b1111;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BlockState {
+    #[serde(with = "super::block::by_id")]
+    pub block: BlockType,
+    properties: u16,
+}
+
+impl From<BlockType> for BlockState {
+    fn from(block: BlockType) -> Self {
+        Self::new(block)
+    }
+}
+
+impl Default for BlockState {
+    fn default() -> Self {
+        Self::new(BlockType::Air)
+    }
+}
+
+impl BlockState {
+    /// The block's default state: Y axis, facing north, bottom half, dry, level 0.
+    pub const fn new(block: BlockType) -> Self {
+        Self {
+            block,
+            properties: 0,
+        }
+    }
+
+    pub fn has(self, property: Property) -> bool {
+        self.block.properties().contains(&property)
+    }
+
+    fn get(self, shift: u16, mask: u16) -> u16 {
+        (self.properties >> shift) & mask
+    }
+
+    fn with(mut self, property: Property, shift: u16, mask: u16, value: u16) -> Self {
+        if self.has(property) {
+            self.properties = (self.properties & !(mask << shift)) | ((value & mask) << shift);
+        }
+        self
+    }
+
+    pub fn axis(self) -> Axis {
+        match self.get(AXIS_SHIFT, AXIS_MASK) {
+            1 => Axis::X,
+            2 => Axis::Z,
+            _ => Axis::Y,
+        }
+    }
+
+    pub fn with_axis(self, axis: Axis) -> Self {
+        let value = match axis {
+            Axis::Y => 0,
+            Axis::X => 1,
+            Axis::Z => 2,
+        };
+        self.with(Property::Axis, AXIS_SHIFT, AXIS_MASK, value)
+    }
+
+    pub fn facing(self) -> Facing {
+        Facing::ALL
+            .get(self.get(FACING_SHIFT, FACING_MASK) as usize)
+            .copied()
+            .unwrap_or(Facing::North)
+    }
+
+    pub fn with_facing(self, facing: Facing) -> Self {
+        let value = Facing::ALL.iter().position(|f| *f == facing).unwrap_or(0) as u16;
+        if self.has(Property::HorizontalFacing) && facing.is_horizontal() {
+            return self.with(Property::HorizontalFacing, FACING_SHIFT, FACING_MASK, value);
+        }
+        self.with(Property::Facing, FACING_SHIFT, FACING_MASK, value)
+    }
+
+    pub fn half(self) -> Half {
+        if self.get(HALF_SHIFT, 1) == 1 {
+            Half::Top
+        } else {
+            Half::Bottom
+        }
+    }
+
+    pub fn with_half(self, half: Half) -> Self {
+        self.with(Property::Half, HALF_SHIFT, 1, (half == Half::Top) as u16)
+    }
+
+    pub fn waterlogged(self) -> bool {
+        self.get(WATERLOGGED_SHIFT, 1) == 1
+    }
+
+    pub fn with_waterlogged(self, waterlogged: bool) -> Self {
+        self.with(Property::Waterlogged, WATERLOGGED_SHIFT, 1, waterlogged as u16)
+    }
+
+    pub fn level(self) -> u8 {
+        self.get(LEVEL_SHIFT, LEVEL_MASK) as u8
+    }
+
+    pub fn with_level(self, level: u8) -> Self {
+        self.with(Property::Level, LEVEL_SHIFT, LEVEL_MASK, level as u16)
+    }
+
+    /// Check that no bits are set for properties the block doesn't have, after
+    /// deserializing untrusted data.
+    pub fn is_valid(self) -> bool {
+        let canonical = BlockState::new(self.block)
+            .with_axis(self.axis())
+            .with_facing(self.facing())
+            .with_half(self.half())
+            .with_waterlogged(self.waterlogged())
+            .with_level(self.level());
+        canonical == self && self.get(FACING_SHIFT, FACING_MASK) < Facing::ALL.len() as u16
+    }
+
+    /// The state to place when a block is put against `face` of the block that was
+    /// clicked. `hit_y` is the height within that block where the ray hit, 0.0 to 1.0,
+    /// and `yaw` the placing player's camera yaw in degrees.
+    pub fn for_placement(block: BlockType, face: Facing, hit_y: f32, yaw: f32) -> Self {
+        let state = BlockState::new(block).with_axis(face.axis());
+        let state = if state.has(Property::HorizontalFacing) {
+            // Fronts face the player.
+            state.with_facing(Facing::from_yaw(yaw).opposite())
+        } else {
+            state.with_facing(face)
+        };
+        let half = match face {
+            Facing::Up => Half::Bottom,
+            Facing::Down => Half::Top,
+            _ if hit_y > 0.5 => Half::Top,
+            _ => Half::Bottom,
+        };
+        state.with_half(half)
+    }
+}